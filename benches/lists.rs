@@ -0,0 +1,88 @@
+#![allow(clippy::needless_return)]
+
+use data_structures::linked_list::{DoublyLinkedList, SinglyLinkedList, UnrolledList};
+use std::{hint::black_box, time::Instant};
+
+const ITEMS: usize = 20_000;
+const EDITS: usize = 2_000;
+
+fn time<F>(name: &str, mut run: F) where F: FnMut() -> usize {
+    let start = Instant::now();
+    let result = run();
+    println!("{:<40} {:>10.2?}  ({})", name, start.elapsed(), black_box(result));
+}
+
+fn positions() -> Vec<usize> {
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    return (0..EDITS).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        return (state % ITEMS as u64) as usize;
+    }).collect();
+}
+
+fn main() {
+    let positions = positions();
+
+    time("singly: push_begin", || {
+        let mut list = SinglyLinkedList::new();
+        for value in 0..ITEMS {
+            list.push_begin(value);
+        }
+        return *list.size();
+    });
+    time("doubly: push_back", || {
+        let mut list = DoublyLinkedList::new();
+        for value in 0..ITEMS {
+            list.push_back(value);
+        }
+        return *list.size();
+    });
+    time("unrolled: push_back", || {
+        let mut list = UnrolledList::new();
+        for value in 0..ITEMS {
+            list.push_back(value);
+        }
+        return *list.size();
+    });
+
+    let mut singly = SinglyLinkedList::new();
+    let mut doubly = DoublyLinkedList::new();
+    let mut unrolled = UnrolledList::new();
+    for value in 0..ITEMS {
+        singly.push_begin(value);
+        doubly.push_back(value);
+        unrolled.push_back(value);
+    }
+
+    time("singly: scan", || singly.count_matching(|value| value % 7 == 0));
+    time("doubly: scan", || doubly.count_matching(|value| value % 7 == 0));
+    time("unrolled: scan", || unrolled.iter().filter(|value| value % 7 == 0).count());
+
+    time("singly: random get", || positions.iter().map(|index| singly.get(*index).unwrap()).sum());
+    time("doubly: random get", || positions.iter().map(|index| doubly.get(*index).unwrap()).sum());
+    time("unrolled: random get", || positions.iter().map(|index| unrolled.get(*index).unwrap()).sum());
+
+    time("singly: random insert + erase", || {
+        for index in &positions {
+            singly.insert(*index, *index);
+            singly.erase(*index).unwrap();
+        }
+        return *singly.size();
+    });
+    time("doubly: random insert + erase", || {
+        for index in &positions {
+            doubly.insert(*index, *index);
+            doubly.erase(*index).unwrap();
+        }
+        return *doubly.size();
+    });
+    time("unrolled: random insert + erase", || {
+        for index in &positions {
+            unrolled.insert(*index, *index);
+            unrolled.erase(*index).unwrap();
+        }
+        return *unrolled.size();
+    });
+}
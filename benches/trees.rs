@@ -0,0 +1,50 @@
+#![allow(clippy::needless_return)]
+
+use data_structures::tree::{AvlTree, RbTree};
+use std::{hint::black_box, time::Instant};
+
+const KEYS: u64 = 200_000;
+
+fn time<F>(name: &str, mut run: F) where F: FnMut() -> usize {
+    let start = Instant::now();
+    let result = run();
+    println!("{:<32} {:>10.2?}  ({})", name, start.elapsed(), black_box(result));
+}
+
+fn shuffled() -> Vec<u64> {
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut keys: Vec<u64> = (0..KEYS).collect();
+    for index in (1..keys.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        keys.swap(index, (state % (index as u64 + 1)) as usize);
+    }
+    return keys;
+}
+
+fn main() {
+    let random = shuffled();
+    let sorted: Vec<u64> = (0..KEYS).collect();
+    for (order, keys) in [("random", &random), ("sorted", &sorted)] {
+        let mut avl = AvlTree::new();
+        let mut rb = RbTree::new();
+        time(&format!("avl: insert {}", order), || {
+            for key in keys {
+                avl.insert(*key, *key);
+            }
+            return *avl.size();
+        });
+        time(&format!("rb: insert {}", order), || {
+            for key in keys {
+                rb.insert(*key, *key);
+            }
+            return *rb.size();
+        });
+        println!("{:<32} avl {} / rb {}", "height", avl.height(), rb.height());
+        time(&format!("avl: lookup {}", order), || keys.iter().filter(|key| avl.get(key).is_some()).count());
+        time(&format!("rb: lookup {}", order), || keys.iter().filter(|key| rb.get(key).is_some()).count());
+        time(&format!("avl: remove {}", order), || keys.iter().filter(|key| avl.remove(key).is_some()).count());
+        time(&format!("rb: remove {}", order), || keys.iter().filter(|key| rb.remove(key).is_some()).count());
+    }
+}
@@ -1,694 +1,10001 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+// Every function in this crate ends in an explicit `return`, and containers are built through
+// `new()` rather than `Default`. Both are house style, so the matching clippy lints stay off.
+#![allow(clippy::needless_return, clippy::new_without_default)]
+extern crate alloc;
+// Xorshift generator shared by the randomized tests.
+#[cfg(test)]
+pub(crate) struct Rng(pub(crate) u64);
+#[cfg(test)]
+impl Rng {
+    pub(crate) fn next(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        return self.0 % bound;
+    }
+}
 pub mod stack {
-    use std::{cell::RefCell, rc::Rc};
+    use alloc::{boxed::Box, vec::Vec};
+    use core::ptr::NonNull;
+    use crate::allocator::{Allocator, Global, NodePool};
     struct Node<T: Clone> {
         data: T,
-        next: Option<Rc<RefCell<Node<T>>>>,
+        next: Option<NonNull<Node<T>>>,
     }
-    pub struct Stack<T: Clone> {
-        root: Option<Rc<RefCell<Node<T>>>>,
+    pub struct Stack<T: Clone, A: Allocator = Global> {
+        root: Option<NonNull<Node<T>>>,
         amount: usize,
+        allocator: A,
     }
     impl<T> Stack<T> where T: Clone {
         pub fn new() -> Self {
+            return Stack::new_in(Global);
+        }
+    }
+    impl<T> Stack<T, NodePool> where T: Clone {
+        pub fn with_node_cache(max_cached: usize) -> Self {
+            return Stack::new_in(NodePool::with_limit(max_cached));
+        }
+    }
+    impl<T, A> Stack<T, NodePool<A>> where T: Clone, A: Allocator {
+        pub fn shrink_to_fit(&mut self) {
+            self.allocator.release();
+        }
+    }
+    impl<T, A> Stack<T, A> where T: Clone, A: Allocator {
+        pub fn new_in(allocator: A) -> Self {
             return Stack {
                 root: None,
                 amount: 0,
+                allocator,
             };
         }
         pub fn push(&mut self, data: T) {
-            match &self.root {
-                None => {
-                    self.root = Some(Rc::new(RefCell::new(Node {
-                        data,
-                        next: None,
-                    })));
-                },
-                Some(value) => {
-                    let node = Rc::new(RefCell::new(Node {
-                        data,
-                        next: Some(Rc::clone(value)),
-                    }));
-                    self.root = Some(Rc::clone(&node));
-                }
-            }
+            let node = crate::allocator::allocate_node(&self.allocator, Node {
+                data,
+                next: self.root,
+            });
+            self.root = Some(node);
             self.amount += 1;
         }
-        pub fn get(&mut self) -> Result<T, Box<dyn std::error::Error>> {
+        pub fn get(&mut self) -> Result<T, Box<dyn core::error::Error>> {
             match self.root.take() {
                 None => {
-                    return Err(Box::new(std::fmt::Error));
+                    return Err(Box::new(core::fmt::Error));
                 },
                 Some(value) => {
-                    let data = value.borrow().data.clone();
-                    let next = &value.borrow().next;
-                    match next {
-                        None => {
-                            self.root = None;
-                        },
-                        Some(content) => {
-                            self.root = Some(Rc::clone(content));
-                        }
-                    }
+                    let node = unsafe { crate::allocator::deallocate_node(&self.allocator, value) };
+                    self.root = node.next;
                     self.amount -= 1;
-                    return Ok(data);
+                    return Ok(node.data);
                 }
             }
         }
-        pub fn top(&self) -> Result<T, Box<dyn std::error::Error>> {
+        pub fn top(&self) -> Result<T, Box<dyn core::error::Error>> {
             match &self.root {
                 None => {
-                    return Err(Box::new(std::fmt::Error));
+                    return Err(Box::new(core::fmt::Error));
                 },
                 Some(value) => {
-                    return Ok(value.borrow().data.clone());
+                    return Ok(unsafe { value.as_ref() }.data.clone());
                 }
             }
         }
         pub fn size(&self) -> &usize {
             return &self.amount;
         }
-
+        pub fn allocator(&self) -> &A {
+            return &self.allocator;
+        }
     }
-}
-pub mod queue {
-    use std::{cell::RefCell, rc::Rc};
-    struct Node<T: Clone> {
-        data: T,
-        next: Option<Rc<RefCell<Node<T>>>>,
+    impl<T, A> Drop for Stack<T, A> where T: Clone, A: Allocator {
+        fn drop(&mut self) {
+            while self.get().is_ok() {}
+        }
+    }
+    #[cfg(feature = "std")]
+    pub struct AsyncStack<T> {
+        core: crate::queue::AsyncCore<T>,
+    }
+    #[cfg(feature = "std")]
+    impl<T> AsyncStack<T> {
+        pub fn new() -> Self {
+            return AsyncStack {
+                core: crate::queue::AsyncCore::new(None, true),
+            };
+        }
+        pub fn with_capacity(capacity: usize) -> Result<Self, Box<dyn core::error::Error>> {
+            if capacity == 0 {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(AsyncStack {
+                core: crate::queue::AsyncCore::new(Some(capacity), true),
+            });
+        }
+        pub fn push(&self, data: T) -> crate::queue::AsyncPush<'_, T> {
+            return self.core.push(data);
+        }
+        pub fn try_push(&self, data: T) -> Result<(), Box<dyn core::error::Error>> {
+            return self.core.try_push(data);
+        }
+        pub fn pop(&self) -> crate::queue::AsyncPop<'_, T> {
+            return self.core.pop();
+        }
+        pub fn try_pop(&self) -> Result<T, Box<dyn core::error::Error>> {
+            return self.core.try_pop();
+        }
+        pub fn size(&self) -> usize {
+            return self.core.size();
+        }
     }
-    pub struct Queue<T: Clone> {
-        root: Option<Rc<RefCell<Node<T>>>>,
+    pub struct ArrayStack<T, const N: usize> {
+        data: [core::mem::MaybeUninit<T>; N],
         amount: usize,
     }
-    impl<T> Queue<T> where T: Clone {
+    impl<T, const N: usize> ArrayStack<T, N> {
         pub fn new() -> Self {
-            return Queue {
-                root: None,
+            return ArrayStack {
+                data: [const { core::mem::MaybeUninit::uninit() }; N],
                 amount: 0,
             };
         }
-        pub fn push(&mut self, data: T) {
-            match &self.root {
-                None => {
-                    self.root = Some(Rc::new(RefCell::new(Node {
-                        data,
-                        next: None,
-                    })));
-                },
-                Some(temp) => {
-                    let mut current = Some(Rc::clone(temp));
-                    while let Some(value) = current {
-                        match &value.borrow().next {
-                            None => {
-                                value.borrow_mut().next = Some(Rc::new(RefCell::new(Node {
-                                    data,
-                                    next: None,
-                                })));
-                                return;
-                            },
-                            Some(next) => {
-                                current = Some(Rc::clone(next));
-                            }
-                        }
-                    }
-                }
+        pub fn push(&mut self, data: T) -> Result<(), Box<dyn core::error::Error>> {
+            if self.amount == N {
+                return Err(Box::new(core::fmt::Error));
             }
+            self.data[self.amount].write(data);
+            self.amount += 1;
+            return Ok(());
         }
-        pub fn get(&mut self) -> Result<T, Box<dyn std::error::Error>> {
-            match self.root.take() {
-                None => {
-                    return Err(Box::new(std::fmt::Error));
-                },
-                Some(value) => {
-                    let temp = value.borrow().data.clone();
-                    match &value.borrow().next {
-                        None => {
-                            self.root = None;
-                        },
-                        Some(content) => {
-                            self.root = Some(Rc::clone(content));
-                        }
-                    }
-                    self.amount -= 1;
-                    return Ok(temp);
-                }
+        pub fn get(&mut self) -> Result<T, Box<dyn core::error::Error>> {
+            if self.amount == 0 {
+                return Err(Box::new(core::fmt::Error));
             }
+            self.amount -= 1;
+            return Ok(unsafe { self.data[self.amount].assume_init_read() });
         }
-        pub fn top(&self) -> Result<T, Box<dyn std::error::Error>> {
-            match &self.root {
-                None => {
-                    return Err(Box::new(std::fmt::Error));
-                },
-                Some(value) => {
-                    let temp = value.borrow().data.clone();
-                    return Ok(temp);
-                }
+        pub fn top(&self) -> Result<T, Box<dyn core::error::Error>> where T: Clone {
+            if self.amount == 0 {
+                return Err(Box::new(core::fmt::Error));
             }
+            return Ok(unsafe { self.data[self.amount - 1].assume_init_ref() }.clone());
         }
         pub fn size(&self) -> &usize {
             return &self.amount;
         }
     }
-}
-pub mod linked_list {
-    use std::{rc::{Rc, Weak}, cell::RefCell};
-    struct Single<T: Clone + std::fmt::Display + std::fmt::Debug> {
-        data: T,
-        next: Option<Rc<RefCell<Single<T>>>>,
+    impl<T, const N: usize> Drop for ArrayStack<T, N> {
+        fn drop(&mut self) {
+            for slot in &mut self.data[..self.amount] {
+                unsafe { slot.assume_init_drop() };
+            }
+        }
     }
-    pub struct SinglyLinkedList<T: Clone + std::fmt::Display + std::fmt::Debug> {
-        root: Option<Rc<RefCell<Single<T>>>>,
+    enum SmallStorage<T: Clone, const N: usize> {
+        Inline(ArrayStack<T, N>),
+        Heap(Vec<T>),
+    }
+    pub struct SmallStack<T: Clone, const N: usize> {
+        storage: SmallStorage<T, N>,
         amount: usize,
     }
-    impl<T> SinglyLinkedList<T> where T: Clone + std::fmt::Display + std::fmt::Debug {
+    impl<T, const N: usize> SmallStack<T, N> where T: Clone {
         pub fn new() -> Self {
-            return SinglyLinkedList {
-                root: None,
+            return SmallStack {
+                storage: SmallStorage::Inline(ArrayStack::new()),
                 amount: 0,
             };
         }
-        pub fn push_begin(&mut self, data: T) {
-            match self.root.take() {
-                None => {
-                    self.root = Some(Rc::new(RefCell::new(Single {
-                        data,
-                        next: None,
-                    })));
+        pub fn push(&mut self, data: T) {
+            if let SmallStorage::Inline(inline) = &mut self.storage && self.amount == N {
+                let mut heap = Vec::with_capacity(N * 2 + 1);
+                while let Ok(value) = inline.get() {
+                    heap.push(value);
+                }
+                heap.reverse();
+                self.storage = SmallStorage::Heap(heap);
+            }
+            match &mut self.storage {
+                SmallStorage::Inline(inline) => {
+                    // Still inline means the spill above left room.
+                    let pushed = inline.push(data);
+                    debug_assert!(pushed.is_ok());
                 },
-                Some(value) => {
-                    let node = Rc::new(RefCell::new(Single {
-                        data,
-                        next: Some(Rc::clone(&value)),
-                    }));
-                    self.root = Some(node);
+                SmallStorage::Heap(heap) => {
+                    heap.push(data);
                 }
             }
             self.amount += 1;
         }
-        pub fn push_back(&mut self, data: T) {
-            match &self.root {
-                None => {
-                    self.root = Some(Rc::new(RefCell::new(Single {
-                        data,
-                        next: None,
-                    })));
+        pub fn get(&mut self) -> Result<T, Box<dyn core::error::Error>> {
+            let data = match &mut self.storage {
+                SmallStorage::Inline(inline) => {
+                    inline.get()?
                 },
-                Some(value) => {
-                    let mut current = Some(Rc::clone(value));
-                    while let Some(temp) = current {
-                        match &temp.borrow().next {
-                            None => {
-                                temp.borrow_mut().next = Some(Rc::new(RefCell::new(Single {
-                                    data,
-                                    next: None,
-                                })));
-                                return;
-                            },
-                            Some(next) => {
-                                current = Some(Rc::clone(next));
-                            }
-                        }
-                    }
+                SmallStorage::Heap(heap) => {
+                    heap.pop().ok_or(core::fmt::Error)?
                 }
-            }
-            self.amount += 1;
+            };
+            self.amount -= 1;
+            return Ok(data);
         }
-        pub fn insert(&mut self, data: T, index: usize) {
-            if index >= self.amount {
-                self.push_back(data);
-            } else {
-                if index == 0 {
-                    self.push_begin(data);
-                } else {
-                    match &self.root {
+        pub fn top(&self) -> Result<T, Box<dyn core::error::Error>> {
+            match &self.storage {
+                SmallStorage::Inline(inline) => {
+                    return inline.top();
+                },
+                SmallStorage::Heap(heap) => {
+                    match heap.last() {
                         None => {
-                            self.root = Some(Rc::new(RefCell::new(Single {
-                                data,
-                                next: None,
-                            })));
+                            return Err(Box::new(core::fmt::Error));
                         },
                         Some(value) => {
-                            let mut current = Some(Rc::clone(value));
-                            let mut counter = 1;
-                            while let Some(temp) = current {
-                                match &temp.borrow().next {
-                                    None => {
-                                        temp.borrow_mut().next = Some(Rc::new(RefCell::new(Single {
-                                            data,
-                                            next: None,
-                                        })));
-                                        return;
-                                    },
-                                    Some(content) => {
-                                        if counter == index {
-                                            let node = Rc::new(RefCell::new(Single {
-                                                data,
-                                                next: Some(Rc::clone(content)),
-                                            }));
-                                            temp.borrow_mut().next = Some(Rc::clone(&node));
-                                            return;
-                                        } else {
-                                            current = Some(Rc::clone(content));
-                                            counter += 1;
-                                        }
-                                    }
-                                }
-                            }
+                            return Ok(value.clone());
                         }
                     }
                 }
             }
         }
-        pub fn pop_begin(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-            match self.root.take() {
-                None => {
-                    return Err(Box::new(std::fmt::Error));
-                },
-                Some(value) => {
-                    match &value.borrow().next {
-                        None => {
-                            self.root = None;
-                        },
-                        Some(content) => {
-                            self.root = Some(Rc::clone(content));
-                        }
-                    }
-                    self.amount -= 1;
-                    return Ok(());
-                }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        pub fn is_spilled(&self) -> bool {
+            return matches!(self.storage, SmallStorage::Heap(_));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use core::cell::Cell;
+
+        // Not `Clone`, so these tests also check that the array containers only need it for `top`.
+        struct Counted<'a>(usize, &'a Cell<usize>);
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
             }
         }
-        pub fn pop_back(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-            match self.root.clone() {
-                None => {
-                    return Err(Box::new(std::fmt::Error));
-                },
-                Some(value) => {
-                    match &value.borrow().next {
-                        None => {
-                            self.root = None;
-                        },
-                        Some(_) => {
-                            let mut current = Some(Rc::clone(&value));
-                            while let Some(content) = current {
-                                match &content.borrow().next {
-                                    None => {
-                                        return Err(Box::new(std::fmt::Error));
-                                    },
-                                    Some(temp) => {
-                                        match &temp.borrow().next {
-                                            None => {
-                                                content.borrow_mut().next = None;
-                                                break;
-                                            },
-                                            Some(_) => {
-                                                current = Some(Rc::clone(temp));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+
+        #[test]
+        fn array_stack_drops_only_initialized_slots() {
+            for filled in [0, 1, 3, 4] {
+                let drops = Cell::new(0);
+                {
+                    let mut stack: ArrayStack<Counted, 4> = ArrayStack::new();
+                    for value in 0..filled {
+                        stack.push(Counted(value, &drops)).unwrap();
+                    }
+                    if filled == 4 {
+                        let rejected = stack.push(Counted(4, &drops));
+                        assert!(rejected.is_err());
+                        drop(rejected);
+                        assert_eq!(drops.get(), 1);
+                        drops.set(0);
+                    }
+                    if filled > 0 {
+                        assert_eq!(stack.get().unwrap().0, filled - 1);
+                        assert_eq!(drops.get(), 1);
                     }
-                    self.amount -= 1;
-                    return Ok(());
                 }
+                assert_eq!(drops.get(), filled);
             }
         }
-        pub fn erase(&mut self, index: usize) -> Result<(), Box<dyn std::error::Error>> {
-            if index >= self.amount {
-                return Err(Box::new(std::fmt::Error));
+
+        #[test]
+        fn array_stack_is_lifo_and_rejects_overflow() {
+            let mut stack: ArrayStack<u8, 2> = ArrayStack::new();
+            assert!(stack.get().is_err());
+            assert!(stack.top().is_err());
+            stack.push(1).unwrap();
+            stack.push(2).unwrap();
+            assert!(stack.push(3).is_err());
+            assert_eq!((stack.top().unwrap(), *stack.size()), (2, 2));
+            assert_eq!(stack.get().unwrap(), 2);
+            stack.push(4).unwrap();
+            assert_eq!(stack.get().unwrap(), 4);
+            assert_eq!(stack.get().unwrap(), 1);
+            let mut empty: ArrayStack<u8, 0> = ArrayStack::new();
+            assert!(empty.push(1).is_err());
+            assert!(empty.get().is_err());
+        }
+
+        fn small_stack_spills_once_full<const N: usize>() {
+            let mut stack: SmallStack<usize, N> = SmallStack::new();
+            assert!(stack.get().is_err());
+            assert!(stack.top().is_err());
+            for value in 0..N {
+                stack.push(value);
+                assert!(!stack.is_spilled());
             }
-            if index == 0 {
-                return self.pop_begin();
+            for value in N..3 * N + 2 {
+                stack.push(value);
+                assert!(stack.is_spilled());
+                assert_eq!(stack.top().unwrap(), value);
+            }
+            assert_eq!(*stack.size(), 3 * N + 2);
+            for value in (0..3 * N + 2).rev() {
+                assert_eq!(stack.get().unwrap(), value);
+            }
+            assert!(stack.get().is_err());
+            assert_eq!(*stack.size(), 0);
+            assert!(stack.is_spilled());
+        }
+
+        #[test]
+        fn small_stack_keeps_lifo_order_across_the_spill() {
+            small_stack_spills_once_full::<0>();
+            small_stack_spills_once_full::<1>();
+            small_stack_spills_once_full::<4>();
+        }
+
+        #[test]
+        fn small_stack_spill_neither_leaks_nor_double_drops() {
+            let shared = alloc::rc::Rc::new(());
+            {
+                let mut stack: SmallStack<alloc::rc::Rc<()>, 2> = SmallStack::new();
+                for _ in 0..2 {
+                    stack.push(alloc::rc::Rc::clone(&shared));
+                }
+                assert_eq!(alloc::rc::Rc::strong_count(&shared), 3);
+                stack.push(alloc::rc::Rc::clone(&shared));
+                assert!(stack.is_spilled());
+                assert_eq!(alloc::rc::Rc::strong_count(&shared), 4);
+                drop(stack.get().unwrap());
+                assert_eq!(alloc::rc::Rc::strong_count(&shared), 3);
             }
-            match self.root.clone() {
+            assert_eq!(alloc::rc::Rc::strong_count(&shared), 1);
+        }
+    }
+}
+pub mod queue {
+    use alloc::boxed::Box;
+    use core::ptr::NonNull;
+    use crate::allocator::{Allocator, Global, NodePool};
+    struct Node<T: Clone> {
+        data: T,
+        next: Option<NonNull<Node<T>>>,
+    }
+    pub struct Queue<T: Clone, A: Allocator = Global> {
+        root: Option<NonNull<Node<T>>>,
+        tail: Option<NonNull<Node<T>>>,
+        amount: usize,
+        allocator: A,
+    }
+    impl<T> Queue<T> where T: Clone {
+        pub fn new() -> Self {
+            return Queue::new_in(Global);
+        }
+    }
+    impl<T> Queue<T, NodePool> where T: Clone {
+        pub fn with_node_cache(max_cached: usize) -> Self {
+            return Queue::new_in(NodePool::with_limit(max_cached));
+        }
+    }
+    impl<T, A> Queue<T, NodePool<A>> where T: Clone, A: Allocator {
+        pub fn shrink_to_fit(&mut self) {
+            self.allocator.release();
+        }
+    }
+    impl<T, A> Queue<T, A> where T: Clone, A: Allocator {
+        pub fn new_in(allocator: A) -> Self {
+            return Queue {
+                root: None,
+                tail: None,
+                amount: 0,
+                allocator,
+            };
+        }
+        pub fn push(&mut self, data: T) {
+            let node = crate::allocator::allocate_node(&self.allocator, Node {
+                data,
+                next: None,
+            });
+            match self.tail {
                 None => {
-                    return Err(Box::new(std::fmt::Error));
+                    self.root = Some(node);
                 },
-                Some(value) => {
-                    match &value.borrow().next {
-                        None => {
-                            return Err(Box::new(std::fmt::Error));
-                        },
-                        Some(_) => {
-                            let mut current = Some(Rc::clone(&value));
-                            let mut counter = 1;
-                            while let Some(temp) = current {
-                                match &temp.borrow().next {
-                                    None => {
-                                        return Err(Box::new(std::fmt::Error));
-                                    },
-                                    Some(next) => {
-                                        match &next.borrow().next {
-                                            None => {
-                                                temp.borrow_mut().next = None;
-                                                break;
-                                            },
-                                            Some(further) => {
-                                                if counter == index {
-                                                    temp.borrow_mut().next = Some(Rc::clone(further));
-                                                    break;
-                                                } else {
-                                                    current = Some(Rc::clone(next));
-                                                    counter += 1;
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+                Some(mut value) => {
+                    unsafe { value.as_mut() }.next = Some(node);
                 }
             }
-            self.amount -= 1;
-            return Ok(());
+            self.tail = Some(node);
+            self.amount += 1;
         }
-        pub fn get(&self, index: usize) -> Result<T, Box<dyn std::error::Error>> {
-            if index >= self.amount {
-                return Err(Box::new(std::fmt::Error));
-            }
-            match &self.root {
+        pub fn get(&mut self) -> Result<T, Box<dyn core::error::Error>> {
+            match self.root.take() {
                 None => {
-                    return Err(Box::new(std::fmt::Error));
+                    return Err(Box::new(core::fmt::Error));
                 },
                 Some(value) => {
-                    let mut current = Some(Rc::clone(value));
-                    let mut counter = 0;
-                    while let Some(content) = current {
-                        if counter == index {
-                            return Ok(content.borrow().data.clone());
-                        } else {
-                            match &content.borrow().next {
-                                None => {
-                                    return Err(Box::new(std::fmt::Error));
-                                },
-                                Some(temp) => {
-                                    current = Some(Rc::clone(temp));
-                                    counter += 1;
-                                }
-                            }
-                        }
+                    let node = unsafe { crate::allocator::deallocate_node(&self.allocator, value) };
+                    self.root = node.next;
+                    if self.root.is_none() {
+                        self.tail = None;
                     }
-                    return Ok(value.borrow().data.clone());
+                    self.amount -= 1;
+                    return Ok(node.data);
                 }
             }
         }
-        pub fn show(&self) {
-            println!("=====================list=begin=====================");
+        pub fn top(&self) -> Result<T, Box<dyn core::error::Error>> {
             match &self.root {
                 None => {
-                    println!("\n");
-                }, 
+                    return Err(Box::new(core::fmt::Error));
+                },
                 Some(value) => {
-                    let mut current = Some(Rc::clone(value));
-                    while let Some(content) = current {
-                        println!("{}", &content.borrow().data);
-                        match &content.borrow().next {
-                            None => {
-                                break;
-                            },
-                            Some(next) => {
-                                current = Some(Rc::clone(next));
-                            }
-                        }
-                    }
+                    let temp = unsafe { value.as_ref() }.data.clone();
+                    return Ok(temp);
                 }
             }
-            println!("======================list=end======================");
         }
         pub fn size(&self) -> &usize {
             return &self.amount;
         }
+        pub fn allocator(&self) -> &A {
+            return &self.allocator;
+        }
     }
-    struct Double<T: Clone + std::fmt::Debug + std::fmt::Display> {
-        data: T,
-        prev: Option<Weak<RefCell<Double<T>>>>,
-        next: Option<Rc<RefCell<Double<T>>>>,
+    impl<T, A> Drop for Queue<T, A> where T: Clone, A: Allocator {
+        fn drop(&mut self) {
+            while self.get().is_ok() {}
+        }
     }
-    pub struct DoublyLinkedList<T: Clone + std::fmt::Debug + std::fmt::Display> {
-        head: Option<Rc<RefCell<Double<T>>>>,
-        tail: Option<Weak<RefCell<Double<T>>>>,
+    pub struct ArrayQueue<T, const N: usize> {
+        data: [core::mem::MaybeUninit<T>; N],
+        head: usize,
         amount: usize,
     }
-    impl<T> DoublyLinkedList<T> where T: Clone + std::fmt::Debug + std::fmt::Display {
+    impl<T, const N: usize> ArrayQueue<T, N> {
         pub fn new() -> Self {
-            return DoublyLinkedList {
-                head: None,
-                tail: None,
-                amount: 0
+            return ArrayQueue {
+                data: [const { core::mem::MaybeUninit::uninit() }; N],
+                head: 0,
+                amount: 0,
             };
         }
-        pub fn push_begin(&mut self, data: T) {
-            match &self.head {
-                None => {
-                    let node = Rc::new(RefCell::new(Double{
-                        data,
-                        prev: None,
-                        next: None,
-                    }));
-                    self.head = Some(Rc::clone(&node));
-                    self.tail = Some(Rc::downgrade(&node));
-                },
-                Some(value) => {
-                    let node = Rc::new(RefCell::new(Double {
-                        data,
-                        prev: None,
-                        next: Some(Rc::clone(value)),
-                    }));
-                    value.borrow_mut().prev = Some(Rc::downgrade(&node));
-                    self.head = Some(Rc::clone(&node));
-                }
+        pub fn push(&mut self, data: T) -> Result<(), Box<dyn core::error::Error>> {
+            if self.amount == N {
+                return Err(Box::new(core::fmt::Error));
             }
+            self.data[(self.head + self.amount) % N].write(data);
             self.amount += 1;
+            return Ok(());
         }
-        pub fn push_back(&mut self, data: T) {
-            match &self.tail {
-                None => {
-                    let node = Rc::new(RefCell::new(Double {
-                        data,
-                        prev: None,
-                        next: None,
-                    }));
-                    self.head = Some(Rc::clone(&node));
-                    self.tail = Some(Rc::downgrade(&node));
-                },
-                Some(value) => {
-                    let node = Rc::new(RefCell::new(Double {
-                        data,
-                        prev: Some(Weak::clone(value)),
-                        next: None,
-                    }));
-                    value.upgrade().unwrap().borrow_mut().next = Some(Rc::clone(&node));
-                    self.tail = Some(Rc::downgrade(&node));
-                }
+        pub fn get(&mut self) -> Result<T, Box<dyn core::error::Error>> {
+            if self.amount == 0 {
+                return Err(Box::new(core::fmt::Error));
             }
-            self.amount += 1;
+            let data = unsafe { self.data[self.head].assume_init_read() };
+            self.head = (self.head + 1) % N;
+            self.amount -= 1;
+            return Ok(data);
         }
-        pub fn insert(&mut self, data: T, index: usize) {
-            if index >= self.amount {
-                self.push_back(data);
-                return;
+        pub fn top(&self) -> Result<T, Box<dyn core::error::Error>> where T: Clone {
+            if self.amount == 0 {
+                return Err(Box::new(core::fmt::Error));
             }
-            if index == 0 {
-                self.push_begin(data);
-                return;
+            return Ok(unsafe { self.data[self.head].assume_init_ref() }.clone());
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+    }
+    impl<T, const N: usize> Drop for ArrayQueue<T, N> {
+        fn drop(&mut self) {
+            for offset in 0..self.amount {
+                unsafe { self.data[(self.head + offset) % N].assume_init_drop() };
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    pub struct BlockingQueue<T> {
+        state: std::sync::Mutex<BlockingState<T>>,
+        not_empty: std::sync::Condvar,
+        not_full: std::sync::Condvar,
+        capacity: Option<usize>,
+    }
+    #[cfg(feature = "std")]
+    struct BlockingState<T> {
+        items: std::collections::VecDeque<T>,
+        closed: bool,
+    }
+    #[cfg(feature = "std")]
+    impl<T> BlockingQueue<T> {
+        pub fn new() -> Self {
+            return BlockingQueue {
+                state: std::sync::Mutex::new(BlockingState {
+                    items: std::collections::VecDeque::new(),
+                    closed: false,
+                }),
+                not_empty: std::sync::Condvar::new(),
+                not_full: std::sync::Condvar::new(),
+                capacity: None,
+            };
+        }
+        pub fn with_capacity(capacity: usize) -> Result<Self, Box<dyn core::error::Error>> {
+            if capacity == 0 {
+                return Err(Box::new(core::fmt::Error));
             }
-            match &self.head {
+            let mut queue = BlockingQueue::new();
+            queue.capacity = Some(capacity);
+            return Ok(queue);
+        }
+        pub fn push(&self, data: T) -> Result<(), Box<dyn core::error::Error>> {
+            let mut state = self.state.lock().unwrap();
+            while !state.closed && self.is_full(&state) {
+                state = self.not_full.wait(state).unwrap();
+            }
+            if state.closed {
+                return Err(Box::new(core::fmt::Error));
+            }
+            state.items.push_back(data);
+            self.not_empty.notify_one();
+            return Ok(());
+        }
+        pub fn pop(&self) -> Result<T, Box<dyn core::error::Error>> {
+            let mut state = self.state.lock().unwrap();
+            while !state.closed && state.items.is_empty() {
+                state = self.not_empty.wait(state).unwrap();
+            }
+            return self.take(&mut state);
+        }
+        pub fn pop_timeout(&self, timeout: std::time::Duration) -> Result<T, Box<dyn core::error::Error>> {
+            let state = self.state.lock().unwrap();
+            let (mut state, _) = self.not_empty.wait_timeout_while(state, timeout, |state| {
+                return !state.closed && state.items.is_empty();
+            }).unwrap();
+            return self.take(&mut state);
+        }
+        pub fn try_pop(&self) -> Result<T, Box<dyn core::error::Error>> {
+            let mut state = self.state.lock().unwrap();
+            return self.take(&mut state);
+        }
+        pub fn close(&self) {
+            self.state.lock().unwrap().closed = true;
+            self.not_empty.notify_all();
+            self.not_full.notify_all();
+        }
+        pub fn is_closed(&self) -> bool {
+            return self.state.lock().unwrap().closed;
+        }
+        pub fn size(&self) -> usize {
+            return self.state.lock().unwrap().items.len();
+        }
+        fn is_full(&self, state: &BlockingState<T>) -> bool {
+            match self.capacity {
                 None => {
-                    let node = Rc::new(RefCell::new(Double {
-                        data,
-                        prev: None,
-                        next: None,
-                    }));
-                    self.head = Some(Rc::clone(&node));
-                    self.tail = Some(Rc::downgrade(&node));
+                    return false;
+                },
+                Some(capacity) => {
+                    return state.items.len() >= capacity;
+                }
+            }
+        }
+        fn take(&self, state: &mut BlockingState<T>) -> Result<T, Box<dyn core::error::Error>> {
+            match state.items.pop_front() {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
                 },
                 Some(value) => {
-                    let mut current = Some(Rc::clone(value));
-                    let mut counter = 1;
-                    while let Some(temp) = current {
-                        match &temp.borrow().next {
-                            None => {
-                                let node = Rc::new(RefCell::new(Double {
-                                    data,
-                                    prev: Some(Rc::downgrade(&temp)),
-                                    next: None,
-                                }));
-                                temp.borrow_mut().next = Some(Rc::clone(&node));
-                                self.tail = Some(Rc::downgrade(&node));
-                                break;
-                            },
-                            Some(content) => {
-                                if counter == index {
-                                    let node = Rc::new(RefCell::new(Double {
-                                        data,
-                                        prev: Some(Rc::downgrade(&temp)),
-                                        next: Some(Rc::clone(content)),
-                                    }));
-                                    temp.borrow_mut().next = Some(Rc::clone(&node));
-                                    content.borrow_mut().prev = Some(Rc::downgrade(&node));
-                                    break;
-                                }
-                                current = Some(Rc::clone(content));
-                                counter += 1;
-                            }
-                        }
-                    }
+                    self.not_full.notify_one();
+                    return Ok(value);
                 }
             }
-            self.amount += 1;
         }
-        pub fn pop_begin(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-            match self.head.take() {
+    }
+    #[cfg(feature = "std")]
+    pub struct AsyncQueue<T> {
+        core: AsyncCore<T>,
+    }
+    #[cfg(feature = "std")]
+    impl<T> AsyncQueue<T> {
+        pub fn new() -> Self {
+            return AsyncQueue {
+                core: AsyncCore::new(None, false),
+            };
+        }
+        pub fn with_capacity(capacity: usize) -> Result<Self, Box<dyn core::error::Error>> {
+            if capacity == 0 {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(AsyncQueue {
+                core: AsyncCore::new(Some(capacity), false),
+            });
+        }
+        pub fn push(&self, data: T) -> AsyncPush<'_, T> {
+            return self.core.push(data);
+        }
+        pub fn try_push(&self, data: T) -> Result<(), Box<dyn core::error::Error>> {
+            return self.core.try_push(data);
+        }
+        pub fn pop(&self) -> AsyncPop<'_, T> {
+            return self.core.pop();
+        }
+        pub fn try_pop(&self) -> Result<T, Box<dyn core::error::Error>> {
+            return self.core.try_pop();
+        }
+        pub fn size(&self) -> usize {
+            return self.core.size();
+        }
+    }
+    #[cfg(feature = "std")]
+    pub(crate) struct AsyncCore<T> {
+        state: std::sync::Mutex<AsyncState<T>>,
+        capacity: Option<usize>,
+        lifo: bool,
+    }
+    #[cfg(feature = "std")]
+    struct AsyncState<T> {
+        items: std::collections::VecDeque<T>,
+        pop_wakers: Vec<std::task::Waker>,
+        push_wakers: Vec<std::task::Waker>,
+    }
+    #[cfg(feature = "std")]
+    impl<T> AsyncCore<T> {
+        pub(crate) fn new(capacity: Option<usize>, lifo: bool) -> Self {
+            return AsyncCore {
+                state: std::sync::Mutex::new(AsyncState {
+                    items: std::collections::VecDeque::new(),
+                    pop_wakers: Vec::new(),
+                    push_wakers: Vec::new(),
+                }),
+                capacity,
+                lifo,
+            };
+        }
+        pub(crate) fn push(&self, data: T) -> AsyncPush<'_, T> {
+            return AsyncPush {
+                core: self,
+                data: Some(data),
+                waker: None,
+            };
+        }
+        pub(crate) fn try_push(&self, data: T) -> Result<(), Box<dyn core::error::Error>> {
+            let mut state = self.state.lock().unwrap();
+            if self.is_full(&state) {
+                return Err(Box::new(core::fmt::Error));
+            }
+            state.items.push_back(data);
+            Self::wake_one(&mut state.pop_wakers);
+            return Ok(());
+        }
+        pub(crate) fn pop(&self) -> AsyncPop<'_, T> {
+            return AsyncPop {
+                core: self,
+                waker: None,
+            };
+        }
+        pub(crate) fn try_pop(&self) -> Result<T, Box<dyn core::error::Error>> {
+            let mut state = self.state.lock().unwrap();
+            match self.take(&mut state) {
                 None => {
-                    return Err(Box::new(std::fmt::Error));
+                    return Err(Box::new(core::fmt::Error));
                 },
                 Some(value) => {
-                    match &value.borrow().next {
-                        None => {
-                            self.head = None;
-                            self.tail = None;
-                        },
-                        Some(content) => {
-                            content.borrow_mut().prev = None;
-                            self.head = Some(Rc::clone(&content));
-                        }
-                    }
-                    self.amount -= 1;
-                    return Ok(());
+                    return Ok(value);
+                }
+            }
+        }
+        pub(crate) fn size(&self) -> usize {
+            return self.state.lock().unwrap().items.len();
+        }
+        fn is_full(&self, state: &AsyncState<T>) -> bool {
+            match self.capacity {
+                None => {
+                    return false;
+                },
+                Some(capacity) => {
+                    return state.items.len() >= capacity;
                 }
             }
         }
-        pub fn pop_back(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-            match self.tail.take() {
+        fn take(&self, state: &mut AsyncState<T>) -> Option<T> {
+            let item = if self.lifo {
+                state.items.pop_back()
+            } else {
+                state.items.pop_front()
+            };
+            if item.is_some() {
+                Self::wake_one(&mut state.push_wakers);
+            }
+            return item;
+        }
+        fn register(wakers: &mut Vec<std::task::Waker>, waker: &std::task::Waker) {
+            if !wakers.iter().any(|value| value.will_wake(waker)) {
+                wakers.push(waker.clone());
+            }
+        }
+        fn deregister(wakers: &mut Vec<std::task::Waker>, waker: &std::task::Waker) {
+            wakers.retain(|value| !value.will_wake(waker));
+        }
+        // One item or one free slot can only satisfy one waiter, so the rest keep sleeping.
+        fn wake_one(wakers: &mut Vec<std::task::Waker>) {
+            if !wakers.is_empty() {
+                wakers.remove(0).wake();
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    pub struct AsyncPop<'a, T> {
+        core: &'a AsyncCore<T>,
+        waker: Option<std::task::Waker>,
+    }
+    #[cfg(feature = "std")]
+    impl<T> std::future::Future for AsyncPop<'_, T> {
+        type Output = T;
+        fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<T> {
+            let core = self.core;
+            let mut state = core.state.lock().unwrap();
+            match core.take(&mut state) {
                 None => {
-                    return Err(Box::new(std::fmt::Error));
+                    AsyncCore::<T>::register(&mut state.pop_wakers, cx.waker());
+                    self.waker = Some(cx.waker().clone());
+                    return std::task::Poll::Pending;
                 },
                 Some(value) => {
-                    match &value.upgrade().unwrap().borrow().prev {
-                        None => {
-                            self.head = None;
-                            self.tail = None;
-                        },
-                        Some(content) => {
-                            content.upgrade().unwrap().borrow_mut().next = None;
-                            self.tail = Some(Weak::clone(content));
+                    if let Some(waker) = self.waker.take() {
+                        AsyncCore::<T>::deregister(&mut state.pop_wakers, &waker);
+                    }
+                    return std::task::Poll::Ready(value);
+                }
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    impl<T> Drop for AsyncPop<'_, T> {
+        fn drop(&mut self) {
+            // A waiter that was woken and then dropped must hand its wakeup on to the next one.
+            if let Some(waker) = self.waker.take() && let Ok(mut state) = self.core.state.lock() {
+                AsyncCore::<T>::deregister(&mut state.pop_wakers, &waker);
+                if !state.items.is_empty() {
+                    AsyncCore::<T>::wake_one(&mut state.pop_wakers);
+                }
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    pub struct AsyncPush<'a, T> {
+        core: &'a AsyncCore<T>,
+        data: Option<T>,
+        waker: Option<std::task::Waker>,
+    }
+    #[cfg(feature = "std")]
+    impl<T> Unpin for AsyncPush<'_, T> {}
+    #[cfg(feature = "std")]
+    impl<T> std::future::Future for AsyncPush<'_, T> {
+        type Output = ();
+        fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<()> {
+            let core = self.core;
+            let mut state = core.state.lock().unwrap();
+            if core.is_full(&state) {
+                AsyncCore::<T>::register(&mut state.push_wakers, cx.waker());
+                self.waker = Some(cx.waker().clone());
+                return std::task::Poll::Pending;
+            }
+            if let Some(waker) = self.waker.take() {
+                AsyncCore::<T>::deregister(&mut state.push_wakers, &waker);
+            }
+            if let Some(data) = self.data.take() {
+                state.items.push_back(data);
+                AsyncCore::<T>::wake_one(&mut state.pop_wakers);
+            }
+            return std::task::Poll::Ready(());
+        }
+    }
+    #[cfg(feature = "std")]
+    impl<T> Drop for AsyncPush<'_, T> {
+        fn drop(&mut self) {
+            if let Some(waker) = self.waker.take() && let Ok(mut state) = self.core.state.lock() {
+                AsyncCore::<T>::deregister(&mut state.push_wakers, &waker);
+                if !self.core.is_full(&state) {
+                    AsyncCore::<T>::wake_one(&mut state.push_wakers);
+                }
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    #[repr(align(128))]
+    struct CachePadded<T>(T);
+    #[cfg(feature = "std")]
+    impl<T> std::ops::Deref for CachePadded<T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            return &self.0;
+        }
+    }
+    #[cfg(feature = "std")]
+    struct Signal {
+        lock: std::sync::Mutex<()>,
+        cond: std::sync::Condvar,
+        waiters: std::sync::atomic::AtomicUsize,
+    }
+    #[cfg(feature = "std")]
+    impl Signal {
+        fn new() -> Self {
+            return Signal {
+                lock: std::sync::Mutex::new(()),
+                cond: std::sync::Condvar::new(),
+                waiters: std::sync::atomic::AtomicUsize::new(0),
+            };
+        }
+        fn notify(&self) {
+            std::sync::atomic::fence(std::sync::atomic::Ordering::SeqCst);
+            if self.waiters.load(std::sync::atomic::Ordering::SeqCst) > 0 {
+                let _guard = self.lock.lock().unwrap();
+                self.cond.notify_all();
+            }
+        }
+        fn wait_until(&self, deadline: Option<std::time::Instant>, ready: impl Fn() -> bool) -> bool {
+            let mut guard = self.lock.lock().unwrap();
+            self.waiters.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            std::sync::atomic::fence(std::sync::atomic::Ordering::SeqCst);
+            let mut result = true;
+            while !ready() {
+                match deadline {
+                    None => {
+                        guard = self.cond.wait(guard).unwrap();
+                    },
+                    Some(deadline) => {
+                        let now = std::time::Instant::now();
+                        if now >= deadline {
+                            result = false;
+                            break;
                         }
+                        guard = self.cond.wait_timeout(guard, deadline - now).unwrap().0;
                     }
-                    self.amount -= 1;
-                    return Ok(());
                 }
             }
+            self.waiters.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+            return result;
         }
-        pub fn erase(&mut self, index: usize) -> Result<(), Box<dyn std::error::Error>> {
-            if index >= self.amount {
+    }
+    #[cfg(feature = "std")]
+    pub mod spsc {
+        use std::{cell::{Cell, UnsafeCell}, mem::MaybeUninit, sync::{Arc, atomic::{AtomicBool, AtomicUsize, Ordering}, mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError}}, time::{Duration, Instant}};
+        use super::{CachePadded, Signal};
+
+        struct Ring<T> {
+            buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+            head: CachePadded<AtomicUsize>,
+            tail: CachePadded<AtomicUsize>,
+            sender_alive: AtomicBool,
+            receiver_alive: AtomicBool,
+            not_empty: Signal,
+            not_full: Signal,
+        }
+        unsafe impl<T: Send> Send for Ring<T> {}
+        unsafe impl<T: Send> Sync for Ring<T> {}
+        impl<T> Ring<T> {
+            fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+                return self.buffer[index % self.buffer.len()].get();
+            }
+            fn size(&self) -> usize {
+                return self.tail.load(Ordering::Acquire).wrapping_sub(self.head.load(Ordering::Acquire));
+            }
+        }
+        impl<T> Drop for Ring<T> {
+            fn drop(&mut self) {
+                let mut head = self.head.load(Ordering::Relaxed);
+                let tail = self.tail.load(Ordering::Relaxed);
+                while head != tail {
+                    unsafe { (*self.slot(head)).assume_init_drop() };
+                    head = head.wrapping_add(1);
+                }
+            }
+        }
+        pub fn channel<T>(capacity: usize) -> Result<(Sender<T>, Receiver<T>), Box<dyn std::error::Error>> {
+            if capacity == 0 {
                 return Err(Box::new(std::fmt::Error));
             }
-            if index == 0 {
-                return self.pop_begin();
+            let ring = Arc::new(Ring {
+                buffer: (0..capacity).map(|_| UnsafeCell::new(MaybeUninit::uninit())).collect(),
+                head: CachePadded(AtomicUsize::new(0)),
+                tail: CachePadded(AtomicUsize::new(0)),
+                sender_alive: AtomicBool::new(true),
+                receiver_alive: AtomicBool::new(true),
+                not_empty: Signal::new(),
+                not_full: Signal::new(),
+            });
+            let sender = Sender {
+                ring: Arc::clone(&ring),
+                cached_head: Cell::new(0),
+            };
+            let receiver = Receiver {
+                ring,
+                cached_tail: Cell::new(0),
+            };
+            return Ok((sender, receiver));
+        }
+        pub struct Sender<T> {
+            ring: Arc<Ring<T>>,
+            cached_head: Cell<usize>,
+        }
+        unsafe impl<T: Send> Send for Sender<T> {}
+        impl<T> Sender<T> {
+            pub fn try_send(&self, data: T) -> Result<(), TrySendError<T>> {
+                let ring = &*self.ring;
+                if !ring.receiver_alive.load(Ordering::Acquire) {
+                    return Err(TrySendError::Disconnected(data));
+                }
+                let tail = ring.tail.load(Ordering::Relaxed);
+                if tail.wrapping_sub(self.cached_head.get()) == ring.buffer.len() {
+                    self.cached_head.set(ring.head.load(Ordering::Acquire));
+                    if tail.wrapping_sub(self.cached_head.get()) == ring.buffer.len() {
+                        return Err(TrySendError::Full(data));
+                    }
+                }
+                unsafe { (*ring.slot(tail)).write(data) };
+                ring.tail.store(tail.wrapping_add(1), Ordering::Release);
+                ring.not_empty.notify();
+                return Ok(());
             }
-            match &self.head {
-                None => {
-                    return Err(Box::new(std::fmt::Error));
-                },
-                Some(value) => {
-                    let mut current = Some(Rc::clone(value));
-                    let mut counter = 1;
-                    while let Some(content) = current {
-                        match &content.borrow().next {
-                            None => {
-                                return Err(Box::new(std::fmt::Error));
-                            },
-                            Some(temp) => {
-                                if counter == index {
-                                    match &temp.borrow().next {
-                                        None => {
-                                            content.borrow_mut().next = None;
-                                            break;
-                                        },
-                                        Some(further) => {
-                                            further.borrow_mut().prev = Some(Rc::downgrade(&content));
-                                            content.borrow_mut().next = Some(Rc::clone(further));
-                                            break;
-                                        }
-                                    }
-                                }
-                                current = Some(Rc::clone(temp));
-                                counter += 1;
+            pub fn send(&self, data: T) -> Result<(), SendError<T>> {
+                let mut data = data;
+                loop {
+                    match self.try_send(data) {
+                        Ok(()) => {
+                            return Ok(());
+                        },
+                        Err(TrySendError::Disconnected(value)) => {
+                            return Err(SendError(value));
+                        },
+                        Err(TrySendError::Full(value)) => {
+                            data = value;
+                            let ring = &*self.ring;
+                            ring.not_full.wait_until(None, || {
+                                return ring.size() < ring.buffer.len() || !ring.receiver_alive.load(Ordering::Acquire);
+                            });
+                        }
+                    }
+                }
+            }
+            pub fn is_disconnected(&self) -> bool {
+                return !self.ring.receiver_alive.load(Ordering::Acquire);
+            }
+        }
+        impl<T> Drop for Sender<T> {
+            fn drop(&mut self) {
+                self.ring.sender_alive.store(false, Ordering::Release);
+                self.ring.not_empty.notify();
+            }
+        }
+        pub struct Receiver<T> {
+            ring: Arc<Ring<T>>,
+            cached_tail: Cell<usize>,
+        }
+        unsafe impl<T: Send> Send for Receiver<T> {}
+        impl<T> Receiver<T> {
+            pub fn try_recv(&self) -> Result<T, TryRecvError> {
+                let ring = &*self.ring;
+                let head = ring.head.load(Ordering::Relaxed);
+                if head == self.cached_tail.get() {
+                    let alive = ring.sender_alive.load(Ordering::Acquire);
+                    self.cached_tail.set(ring.tail.load(Ordering::Acquire));
+                    if head == self.cached_tail.get() {
+                        if alive {
+                            return Err(TryRecvError::Empty);
+                        }
+                        return Err(TryRecvError::Disconnected);
+                    }
+                }
+                let data = unsafe { (*ring.slot(head)).assume_init_read() };
+                ring.head.store(head.wrapping_add(1), Ordering::Release);
+                ring.not_full.notify();
+                return Ok(data);
+            }
+            pub fn recv(&self) -> Result<T, RecvError> {
+                match self.wait(None) {
+                    Ok(data) => {
+                        return Ok(data);
+                    },
+                    Err(_) => {
+                        return Err(RecvError);
+                    }
+                }
+            }
+            pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+                return self.wait(Some(Instant::now() + timeout));
+            }
+            pub fn is_disconnected(&self) -> bool {
+                return !self.ring.sender_alive.load(Ordering::Acquire);
+            }
+            pub fn size(&self) -> usize {
+                return self.ring.size();
+            }
+            fn wait(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+                loop {
+                    match self.try_recv() {
+                        Ok(data) => {
+                            return Ok(data);
+                        },
+                        Err(TryRecvError::Disconnected) => {
+                            return Err(RecvTimeoutError::Disconnected);
+                        },
+                        Err(TryRecvError::Empty) => {
+                            let ring = &*self.ring;
+                            let ready = ring.not_empty.wait_until(deadline, || {
+                                return ring.size() > 0 || !ring.sender_alive.load(Ordering::Acquire);
+                            });
+                            if !ready {
+                                return Err(RecvTimeoutError::Timeout);
                             }
                         }
                     }
-                    self.amount -= 1;
-                    return Ok(());
                 }
             }
         }
-        pub fn get(&self, index: usize) -> Result<T, Box<dyn std::error::Error>> {
-            if index >= self.amount {
-                return Err(Box::new(std::fmt::Error));
+        impl<T> Drop for Receiver<T> {
+            fn drop(&mut self) {
+                self.ring.receiver_alive.store(false, Ordering::Release);
+                self.ring.not_full.notify();
             }
-            match &self.head {
-                None => {
-                    return Err(Box::new(std::fmt::Error));
-                },
-                Some(value) => {
-                    let mut current = Some(Rc::clone(value));
-                    let mut counter = 0;
-                    while let Some(content) = current {
-                        if counter == index {
-                            return Ok(content.borrow().data.clone());
-                        }
-                        current = match &content.borrow().next {
-                            None => {
-                                None
+        }
+    }
+    #[cfg(feature = "std")]
+    pub mod mpmc {
+        use std::{cell::UnsafeCell, mem::MaybeUninit, sync::{Arc, atomic::{AtomicUsize, Ordering}, mpsc::{RecvError, RecvTimeoutError, SendError, TryRecvError, TrySendError}}, time::{Duration, Instant}};
+        use super::{CachePadded, Signal};
+
+        struct Slot<T> {
+            stamp: AtomicUsize,
+            data: UnsafeCell<MaybeUninit<T>>,
+        }
+        struct Channel<T> {
+            slots: Box<[Slot<T>]>,
+            one_lap: usize,
+            enqueue: CachePadded<AtomicUsize>,
+            dequeue: CachePadded<AtomicUsize>,
+            senders: AtomicUsize,
+            receivers: AtomicUsize,
+            not_empty: Signal,
+            not_full: Signal,
+        }
+        unsafe impl<T: Send> Send for Channel<T> {}
+        unsafe impl<T: Send> Sync for Channel<T> {}
+        impl<T> Channel<T> {
+            fn advance(&self, position: usize) -> usize {
+                let index = position & (self.one_lap - 1);
+                let lap = position & !(self.one_lap - 1);
+                if index + 1 < self.slots.len() {
+                    return position + 1;
+                }
+                return lap.wrapping_add(self.one_lap);
+            }
+            fn push(&self, data: T) -> Result<(), T> {
+                let mut position = self.enqueue.load(Ordering::Relaxed);
+                loop {
+                    let slot = &self.slots[position & (self.one_lap - 1)];
+                    let stamp = slot.stamp.load(Ordering::Acquire);
+                    if stamp == position {
+                        match self.enqueue.compare_exchange_weak(position, self.advance(position), Ordering::SeqCst, Ordering::Relaxed) {
+                            Ok(_) => {
+                                unsafe { (*slot.data.get()).write(data) };
+                                slot.stamp.store(position + 1, Ordering::Release);
+                                return Ok(());
                             },
-                            Some(temp) => {
-                                Some(Rc::clone(temp))
+                            Err(current) => {
+                                position = current;
                             }
-                        };
-                        counter += 1;
+                        }
+                    } else if stamp.wrapping_add(self.one_lap) == position + 1 {
+                        std::sync::atomic::fence(Ordering::SeqCst);
+                        if self.dequeue.load(Ordering::Relaxed).wrapping_add(self.one_lap) == position {
+                            return Err(data);
+                        }
+                        position = self.enqueue.load(Ordering::Relaxed);
+                    } else {
+                        std::hint::spin_loop();
+                        position = self.enqueue.load(Ordering::Relaxed);
                     }
-                    return Ok(value.borrow().data.clone());
                 }
             }
-        }
-        pub fn show(&self) {
-            println!("=====================list=begin=====================");
-            match &self.head {
-                None => {
-                    println!("\n");
-                },
-                Some(value) => {
-                    let mut current = Some(Rc::clone(value));
-                    while let Some(content) = current {
-                        println!("{}", &content.borrow().data);
-                        current = match &content.borrow().next {
-                            None => {
-                                None
+            fn pop(&self) -> Option<T> {
+                let mut position = self.dequeue.load(Ordering::Relaxed);
+                loop {
+                    let slot = &self.slots[position & (self.one_lap - 1)];
+                    let stamp = slot.stamp.load(Ordering::Acquire);
+                    if stamp == position + 1 {
+                        match self.dequeue.compare_exchange_weak(position, self.advance(position), Ordering::SeqCst, Ordering::Relaxed) {
+                            Ok(_) => {
+                                let data = unsafe { (*slot.data.get()).assume_init_read() };
+                                slot.stamp.store(position.wrapping_add(self.one_lap), Ordering::Release);
+                                return Some(data);
                             },
-                            Some(temp) => {
-                                Some(Rc::clone(temp))
+                            Err(current) => {
+                                position = current;
                             }
                         }
+                    } else if stamp == position {
+                        std::sync::atomic::fence(Ordering::SeqCst);
+                        if self.enqueue.load(Ordering::Relaxed) == position {
+                            return None;
+                        }
+                        position = self.dequeue.load(Ordering::Relaxed);
+                    } else {
+                        std::hint::spin_loop();
+                        position = self.dequeue.load(Ordering::Relaxed);
+                    }
+                }
+            }
+            fn size(&self) -> usize {
+                loop {
+                    let enqueue = self.enqueue.load(Ordering::SeqCst);
+                    let dequeue = self.dequeue.load(Ordering::SeqCst);
+                    if self.enqueue.load(Ordering::SeqCst) == enqueue {
+                        let head = dequeue & (self.one_lap - 1);
+                        let tail = enqueue & (self.one_lap - 1);
+                        if head < tail {
+                            return tail - head;
+                        } else if head > tail {
+                            return self.slots.len() - head + tail;
+                        } else if enqueue == dequeue {
+                            return 0;
+                        }
+                        return self.slots.len();
                     }
                 }
             }
-            println!("======================list=end======================");
         }
-        pub fn size(&self) -> &usize {
-            return &self.amount;
+        impl<T> Drop for Channel<T> {
+            fn drop(&mut self) {
+                while self.pop().is_some() {}
+            }
         }
-        pub fn write(&self, index: usize) {
-            match &self.get(index) {
-                Ok(value) => {
-                    println!("{}", value);
-                },
-                Err(_) => {
-                    println!("there is nothing to see");
+        pub fn channel<T>(capacity: usize) -> Result<(Sender<T>, Receiver<T>), Box<dyn std::error::Error>> {
+            if capacity == 0 {
+                return Err(Box::new(std::fmt::Error));
+            }
+            let channel = Arc::new(Channel {
+                slots: (0..capacity).map(|index| Slot {
+                    stamp: AtomicUsize::new(index),
+                    data: UnsafeCell::new(MaybeUninit::uninit()),
+                }).collect(),
+                one_lap: (capacity + 1).next_power_of_two(),
+                enqueue: CachePadded(AtomicUsize::new(0)),
+                dequeue: CachePadded(AtomicUsize::new(0)),
+                senders: AtomicUsize::new(1),
+                receivers: AtomicUsize::new(1),
+                not_empty: Signal::new(),
+                not_full: Signal::new(),
+            });
+            let sender = Sender {
+                channel: Arc::clone(&channel),
+            };
+            return Ok((sender, Receiver { channel }));
+        }
+        pub struct Sender<T> {
+            channel: Arc<Channel<T>>,
+        }
+        impl<T> Sender<T> {
+            pub fn try_send(&self, data: T) -> Result<(), TrySendError<T>> {
+                let channel = &*self.channel;
+                if channel.receivers.load(Ordering::Acquire) == 0 {
+                    return Err(TrySendError::Disconnected(data));
+                }
+                match channel.push(data) {
+                    Ok(()) => {
+                        channel.not_empty.notify();
+                        return Ok(());
+                    },
+                    Err(value) => {
+                        return Err(TrySendError::Full(value));
+                    }
+                }
+            }
+            pub fn send(&self, data: T) -> Result<(), SendError<T>> {
+                let mut data = data;
+                loop {
+                    match self.try_send(data) {
+                        Ok(()) => {
+                            return Ok(());
+                        },
+                        Err(TrySendError::Disconnected(value)) => {
+                            return Err(SendError(value));
+                        },
+                        Err(TrySendError::Full(value)) => {
+                            data = value;
+                            let channel = &*self.channel;
+                            channel.not_full.wait_until(None, || {
+                                return channel.size() < channel.slots.len() || channel.receivers.load(Ordering::Acquire) == 0;
+                            });
+                        }
+                    }
+                }
+            }
+            pub fn is_disconnected(&self) -> bool {
+                return self.channel.receivers.load(Ordering::Acquire) == 0;
+            }
+        }
+        impl<T> Clone for Sender<T> {
+            fn clone(&self) -> Self {
+                self.channel.senders.fetch_add(1, Ordering::Relaxed);
+                return Sender {
+                    channel: Arc::clone(&self.channel),
+                };
+            }
+        }
+        impl<T> Drop for Sender<T> {
+            fn drop(&mut self) {
+                if self.channel.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+                    self.channel.not_empty.notify();
+                }
+            }
+        }
+        pub struct Receiver<T> {
+            channel: Arc<Channel<T>>,
+        }
+        impl<T> Receiver<T> {
+            pub fn try_recv(&self) -> Result<T, TryRecvError> {
+                let channel = &*self.channel;
+                let alive = channel.senders.load(Ordering::Acquire) > 0;
+                match channel.pop() {
+                    Some(data) => {
+                        channel.not_full.notify();
+                        return Ok(data);
+                    },
+                    None => {
+                        if alive {
+                            return Err(TryRecvError::Empty);
+                        }
+                        return Err(TryRecvError::Disconnected);
+                    }
+                }
+            }
+            pub fn recv(&self) -> Result<T, RecvError> {
+                match self.wait(None) {
+                    Ok(data) => {
+                        return Ok(data);
+                    },
+                    Err(_) => {
+                        return Err(RecvError);
+                    }
+                }
+            }
+            pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+                return self.wait(Some(Instant::now() + timeout));
+            }
+            pub fn is_disconnected(&self) -> bool {
+                return self.channel.senders.load(Ordering::Acquire) == 0;
+            }
+            pub fn size(&self) -> usize {
+                return self.channel.size();
+            }
+            fn wait(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+                loop {
+                    match self.try_recv() {
+                        Ok(data) => {
+                            return Ok(data);
+                        },
+                        Err(TryRecvError::Disconnected) => {
+                            return Err(RecvTimeoutError::Disconnected);
+                        },
+                        Err(TryRecvError::Empty) => {
+                            let channel = &*self.channel;
+                            let ready = channel.not_empty.wait_until(deadline, || {
+                                return channel.size() > 0 || channel.senders.load(Ordering::Acquire) == 0;
+                            });
+                            if !ready {
+                                return Err(RecvTimeoutError::Timeout);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        impl<T> Clone for Receiver<T> {
+            fn clone(&self) -> Self {
+                self.channel.receivers.fetch_add(1, Ordering::Relaxed);
+                return Receiver {
+                    channel: Arc::clone(&self.channel),
+                };
+            }
+        }
+        impl<T> Drop for Receiver<T> {
+            fn drop(&mut self) {
+                if self.channel.receivers.fetch_sub(1, Ordering::AcqRel) == 1 {
+                    self.channel.not_full.notify();
                 }
             }
         }
     }
-}
\ No newline at end of file
+
+    #[cfg(all(test, feature = "std"))]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn blocking_queue_rejects_zero_capacity() {
+            assert!(BlockingQueue::<u8>::with_capacity(0).is_err());
+            let queue = std::sync::Arc::new(BlockingQueue::with_capacity(1).unwrap());
+            queue.push(1).unwrap();
+            let producer = {
+                let queue = std::sync::Arc::clone(&queue);
+                std::thread::spawn(move || queue.push(2).unwrap())
+            };
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            assert_eq!(queue.size(), 1);
+            assert_eq!(queue.pop().unwrap(), 1);
+            producer.join().unwrap();
+            assert_eq!(queue.pop().unwrap(), 2);
+        }
+
+        #[test]
+        fn pop_timeout_waits_out_an_empty_queue() {
+            let queue = std::sync::Arc::new(BlockingQueue::new());
+            let start = std::time::Instant::now();
+            assert!(queue.pop_timeout(std::time::Duration::from_millis(30)).is_err());
+            assert!(start.elapsed() >= std::time::Duration::from_millis(30));
+            let producer = {
+                let queue = std::sync::Arc::clone(&queue);
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    queue.push(7).unwrap();
+                })
+            };
+            assert_eq!(queue.pop_timeout(std::time::Duration::from_secs(10)).unwrap(), 7);
+            producer.join().unwrap();
+        }
+
+        #[test]
+        fn close_wakes_blocked_consumers() {
+            let queue = std::sync::Arc::new(BlockingQueue::<u8>::new());
+            let consumers: Vec<_> = (0..3).map(|_| {
+                let queue = std::sync::Arc::clone(&queue);
+                std::thread::spawn(move || queue.pop().is_err())
+            }).collect();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            queue.close();
+            for consumer in consumers {
+                assert!(consumer.join().unwrap());
+            }
+            assert!(queue.is_closed());
+            assert!(queue.push(1).is_err());
+        }
+
+        #[test]
+        fn close_wakes_blocked_producers_and_keeps_queued_items() {
+            let queue = std::sync::Arc::new(BlockingQueue::with_capacity(2).unwrap());
+            queue.push(1).unwrap();
+            queue.push(2).unwrap();
+            let producers: Vec<_> = (3..5).map(|value| {
+                let queue = std::sync::Arc::clone(&queue);
+                std::thread::spawn(move || queue.push(value).is_err())
+            }).collect();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            assert_eq!(queue.size(), 2);
+            queue.close();
+            for producer in producers {
+                assert!(producer.join().unwrap());
+            }
+            assert_eq!(queue.pop().unwrap(), 1);
+            assert_eq!(queue.pop_timeout(std::time::Duration::from_secs(10)).unwrap(), 2);
+            assert!(queue.pop().is_err());
+            assert!(queue.try_pop().is_err());
+        }
+
+        struct Counted<'a>(usize, &'a core::cell::Cell<usize>);
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+            }
+        }
+
+        #[test]
+        fn array_queue_drops_only_initialized_slots() {
+            // Each case pops `skipped` items first so the live range starts at `skipped % 4` and may wrap.
+            for (skipped, filled) in [(0, 0), (0, 2), (0, 4), (3, 2), (3, 4), (6, 3)] {
+                let drops = core::cell::Cell::new(0);
+                {
+                    let mut queue: ArrayQueue<Counted, 4> = ArrayQueue::new();
+                    for value in 0..skipped {
+                        queue.push(Counted(value, &drops)).unwrap();
+                        assert_eq!(queue.get().unwrap().0, value);
+                    }
+                    assert_eq!(drops.get(), skipped);
+                    drops.set(0);
+                    for value in 0..filled {
+                        queue.push(Counted(value, &drops)).unwrap();
+                    }
+                    if filled == 4 {
+                        assert!(queue.push(Counted(4, &drops)).is_err());
+                        assert_eq!(drops.get(), 1);
+                        drops.set(0);
+                    }
+                    assert_eq!(*queue.size(), filled);
+                }
+                assert_eq!(drops.get(), filled);
+            }
+        }
+
+        #[test]
+        fn array_queue_is_fifo_across_the_wrap_and_rejects_overflow() {
+            let mut queue: ArrayQueue<u8, 3> = ArrayQueue::new();
+            assert!(queue.get().is_err());
+            assert!(queue.top().is_err());
+            for value in 0..3 {
+                queue.push(value).unwrap();
+            }
+            assert!(queue.push(3).is_err());
+            assert_eq!(queue.get().unwrap(), 0);
+            assert_eq!(queue.get().unwrap(), 1);
+            queue.push(3).unwrap();
+            queue.push(4).unwrap();
+            assert!(queue.push(5).is_err());
+            assert_eq!(queue.top().unwrap(), 2);
+            assert_eq!((0..3).map(|_| queue.get().unwrap()).collect::<Vec<_>>(), [2, 3, 4]);
+            let mut empty: ArrayQueue<u8, 0> = ArrayQueue::new();
+            assert!(empty.push(1).is_err());
+            assert!(empty.get().is_err());
+        }
+
+        #[test]
+        fn async_containers_reject_zero_capacity() {
+            assert!(AsyncQueue::<u8>::with_capacity(0).is_err());
+            assert!(crate::stack::AsyncStack::<u8>::with_capacity(0).is_err());
+            let queue = AsyncQueue::with_capacity(1).unwrap();
+            queue.try_push(1).unwrap();
+            assert!(queue.try_push(2).is_err());
+            let stack = crate::stack::AsyncStack::with_capacity(2).unwrap();
+            stack.try_push(1).unwrap();
+            stack.try_push(2).unwrap();
+            assert!(stack.try_push(3).is_err());
+        }
+
+        #[test]
+        fn channels_reject_zero_capacity() {
+            assert!(spsc::channel::<u8>(0).is_err());
+            assert!(mpmc::channel::<u8>(0).is_err());
+            let (sender, receiver) = spsc::channel(1).unwrap();
+            sender.try_send(1).unwrap();
+            assert!(sender.try_send(2).is_err());
+            assert_eq!(receiver.try_recv().unwrap(), 1);
+            let (sender, receiver) = mpmc::channel(1).unwrap();
+            sender.try_send(1).unwrap();
+            assert!(sender.try_send(2).is_err());
+            assert_eq!(receiver.try_recv().unwrap(), 1);
+        }
+
+        struct CountingWaker {
+            wakes: std::sync::atomic::AtomicUsize,
+            thread: std::thread::Thread,
+        }
+        impl std::task::Wake for CountingWaker {
+            fn wake(self: std::sync::Arc<Self>) {
+                self.wakes.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                self.thread.unpark();
+            }
+        }
+        fn waker() -> (std::sync::Arc<CountingWaker>, std::task::Waker) {
+            let counter = std::sync::Arc::new(CountingWaker {
+                wakes: std::sync::atomic::AtomicUsize::new(0),
+                thread: std::thread::current(),
+            });
+            return (std::sync::Arc::clone(&counter), std::task::Waker::from(counter));
+        }
+        fn wakes(counter: &CountingWaker) -> usize {
+            return counter.wakes.load(std::sync::atomic::Ordering::SeqCst);
+        }
+        fn block_on<F>(future: F) -> F::Output where F: std::future::Future {
+            let mut future = std::pin::pin!(future);
+            let (_, waker) = waker();
+            let mut cx = std::task::Context::from_waker(&waker);
+            loop {
+                match future.as_mut().poll(&mut cx) {
+                    std::task::Poll::Ready(value) => {
+                        return value;
+                    },
+                    std::task::Poll::Pending => {
+                        std::thread::park();
+                    }
+                }
+            }
+        }
+        fn poll<F>(future: &mut F, waker: &std::task::Waker) -> std::task::Poll<F::Output> where F: std::future::Future + Unpin {
+            return std::pin::Pin::new(future).poll(&mut std::task::Context::from_waker(waker));
+        }
+
+        #[test]
+        fn block_on_keeps_queue_and_stack_order() {
+            let queue = AsyncQueue::new();
+            let stack = crate::stack::AsyncStack::new();
+            for value in 0..5 {
+                block_on(queue.push(value));
+                block_on(stack.push(value));
+            }
+            assert_eq!((0..5).map(|_| block_on(queue.pop())).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+            assert_eq!((0..5).map(|_| block_on(stack.pop())).collect::<Vec<_>>(), [4, 3, 2, 1, 0]);
+        }
+
+        #[test]
+        fn each_push_wakes_a_single_pop() {
+            let queue = AsyncQueue::new();
+            let counters: Vec<_> = (0..3).map(|_| waker()).collect();
+            let mut pops: Vec<_> = (0..3).map(|_| queue.pop()).collect();
+            for (pop, (_, waker)) in pops.iter_mut().zip(&counters) {
+                assert!(poll(pop, waker).is_pending());
+            }
+            queue.try_push(1).unwrap();
+            assert_eq!(counters.iter().map(|(counter, _)| wakes(counter)).collect::<Vec<_>>(), [1, 0, 0]);
+            assert_eq!(poll(&mut pops[0], &counters[0].1), std::task::Poll::Ready(1));
+            queue.try_push(2).unwrap();
+            assert_eq!(counters.iter().map(|(counter, _)| wakes(counter)).collect::<Vec<_>>(), [1, 1, 0]);
+            // The woken waiter goes away without taking the item, so the next one is told instead.
+            drop(pops.remove(1));
+            assert_eq!(wakes(&counters[2].0), 1);
+            assert_eq!(poll(&mut pops[1], &counters[2].1), std::task::Poll::Ready(2));
+        }
+
+        #[test]
+        fn each_pop_wakes_a_single_push() {
+            let queue = AsyncQueue::with_capacity(1).unwrap();
+            queue.try_push(0).unwrap();
+            let counters: Vec<_> = (0..2).map(|_| waker()).collect();
+            let mut pushes: Vec<_> = (1..3).map(|value| queue.push(value)).collect();
+            for (push, (_, waker)) in pushes.iter_mut().zip(&counters) {
+                assert!(poll(push, waker).is_pending());
+            }
+            assert_eq!(queue.try_pop().unwrap(), 0);
+            assert_eq!(counters.iter().map(|(counter, _)| wakes(counter)).collect::<Vec<_>>(), [1, 0]);
+            assert!(poll(&mut pushes[0], &counters[0].1).is_ready());
+            assert!(poll(&mut pushes[1], &counters[1].1).is_pending());
+            assert_eq!(queue.try_pop().unwrap(), 1);
+            assert_eq!(wakes(&counters[1].0), 1);
+            assert!(poll(&mut pushes[1], &counters[1].1).is_ready());
+            assert_eq!(queue.try_pop().unwrap(), 2);
+        }
+
+        #[test]
+        fn bounded_queue_hands_every_item_to_one_consumer() {
+            let queue = std::sync::Arc::new(AsyncQueue::with_capacity(2).unwrap());
+            let producers: Vec<_> = (0..4).map(|id| {
+                let queue = std::sync::Arc::clone(&queue);
+                std::thread::spawn(move || {
+                    for value in 0..500 {
+                        block_on(queue.push(id * 500 + value));
+                    }
+                })
+            }).collect();
+            let consumers: Vec<_> = (0..4).map(|_| {
+                let queue = std::sync::Arc::clone(&queue);
+                std::thread::spawn(move || (0..500).map(|_| block_on(queue.pop())).collect::<Vec<_>>())
+            }).collect();
+            for producer in producers {
+                producer.join().unwrap();
+            }
+            let mut received: Vec<_> = consumers.into_iter().flat_map(|consumer| consumer.join().unwrap()).collect();
+            received.sort();
+            assert_eq!(received, (0..2000).collect::<Vec<_>>());
+            assert_eq!(queue.size(), 0);
+        }
+    }
+}
+pub mod linked_list {
+    use alloc::{boxed::Box, rc::{Rc, Weak}, vec::Vec};
+    use core::{cell::RefCell, marker::PhantomData, ptr::NonNull};
+    use crate::allocator::{Allocator, Global, allocate_node, deallocate_node};
+    struct Single<T: Clone + core::fmt::Display + core::fmt::Debug> {
+        data: T,
+        next: Option<NonNull<Single<T>>>,
+    }
+    pub struct SinglyLinkedList<T: Clone + core::fmt::Display + core::fmt::Debug, A: Allocator = Global> {
+        root: Option<NonNull<Single<T>>>,
+        amount: usize,
+        allocator: A,
+    }
+    impl<T> SinglyLinkedList<T> where T: Clone + core::fmt::Display + core::fmt::Debug {
+        pub fn new() -> Self {
+            return SinglyLinkedList::new_in(Global);
+        }
+    }
+    impl<T, A> SinglyLinkedList<T, A> where T: Clone + core::fmt::Display + core::fmt::Debug, A: Allocator {
+        pub fn new_in(allocator: A) -> Self {
+            return SinglyLinkedList {
+                root: None,
+                amount: 0,
+                allocator,
+            };
+        }
+        pub fn push_begin(&mut self, data: T) {
+            let node = allocate_node(&self.allocator, Single {
+                data,
+                next: self.root,
+            });
+            self.root = Some(node);
+            self.amount += 1;
+        }
+        pub fn push_back(&mut self, data: T) {
+            let node = allocate_node(&self.allocator, Single {
+                data,
+                next: None,
+            });
+            match self.amount.checked_sub(1).and_then(|index| self.node_at(index)) {
+                None => {
+                    self.root = Some(node);
+                },
+                Some(mut last) => {
+                    unsafe { last.as_mut() }.next = Some(node);
+                }
+            }
+            self.amount += 1;
+        }
+        pub fn insert(&mut self, data: T, index: usize) {
+            if index >= self.amount {
+                self.push_back(data);
+                return;
+            }
+            if index == 0 {
+                self.push_begin(data);
+                return;
+            }
+            let mut previous = self.node_at(index - 1).unwrap();
+            let node = allocate_node(&self.allocator, Single {
+                data,
+                next: unsafe { previous.as_ref() }.next,
+            });
+            unsafe { previous.as_mut() }.next = Some(node);
+            self.amount += 1;
+        }
+        pub fn pop_begin(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+            match self.root {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(value) => {
+                    let node = unsafe { deallocate_node(&self.allocator, value) };
+                    self.root = node.next;
+                    self.amount -= 1;
+                    return Ok(());
+                }
+            }
+        }
+        pub fn pop_back(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+            match self.amount {
+                0 => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                1 => {
+                    return self.pop_begin();
+                },
+                _ => {
+                    let mut previous = self.node_at(self.amount - 2).unwrap();
+                    let last = unsafe { previous.as_mut() }.next.take().unwrap();
+                    unsafe { deallocate_node(&self.allocator, last) };
+                    self.amount -= 1;
+                    return Ok(());
+                }
+            }
+        }
+        pub fn erase(&mut self, index: usize) -> Result<(), Box<dyn core::error::Error>> {
+            if index >= self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            if index == 0 {
+                return self.pop_begin();
+            }
+            let mut previous = self.node_at(index - 1).unwrap();
+            let removed = unsafe { previous.as_ref() }.next.unwrap();
+            let removed = unsafe { deallocate_node(&self.allocator, removed) };
+            unsafe { previous.as_mut() }.next = removed.next;
+            self.amount -= 1;
+            return Ok(());
+        }
+        pub fn get(&self, index: usize) -> Result<T, Box<dyn core::error::Error>> {
+            match self.refs().nth(index) {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(value) => {
+                    return Ok(value.clone());
+                }
+            }
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) {
+            println!("=====================list=begin=====================");
+            if self.amount == 0 {
+                println!("\n");
+            }
+            for value in self.refs() {
+                println!("{}", value);
+            }
+            println!("======================list=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        pub fn allocator(&self) -> &A {
+            return &self.allocator;
+        }
+        pub fn sort(&mut self) where T: Ord {
+            self.sort_by(|first, second| first.cmp(second));
+        }
+        pub fn sort_by<F>(&mut self, mut compare: F) where F: FnMut(&T, &T) -> core::cmp::Ordering {
+            let head = self.root.take();
+            self.root = sort_chain(head, self.amount, &mut compare);
+        }
+        pub fn sort_by_key<K, F>(&mut self, mut key: F) where K: Ord, F: FnMut(&T) -> K {
+            self.sort_by(|first, second| key(first).cmp(&key(second)));
+        }
+        pub fn merge_sorted(&mut self, other: &mut Self) where T: Ord, A: PartialEq {
+            let first = self.root.take();
+            let second = self.take_chain(other);
+            self.root = merge_chains(first, second, &mut |first: &T, second: &T| first.cmp(second));
+            self.amount += other.amount;
+            other.amount = 0;
+        }
+        pub fn insert_sorted(&mut self, data: T) where T: Ord {
+            let mut previous: Option<NonNull<Single<T>>> = None;
+            let mut current = self.root;
+            while let Some(node) = current {
+                let node_ref = unsafe { node.as_ref() };
+                if node_ref.data > data {
+                    break;
+                }
+                previous = Some(node);
+                current = node_ref.next;
+            }
+            match previous {
+                None => {
+                    self.push_begin(data);
+                },
+                Some(mut node) => {
+                    let created = allocate_node(&self.allocator, Single {
+                        data,
+                        next: unsafe { node.as_ref() }.next,
+                    });
+                    unsafe { node.as_mut() }.next = Some(created);
+                    self.amount += 1;
+                }
+            }
+        }
+        pub fn dedup(&mut self) where T: PartialEq {
+            self.dedup_by(|first, second| first == second);
+        }
+        pub fn dedup_by_key<K, F>(&mut self, mut key: F) where K: PartialEq, F: FnMut(&T) -> K {
+            self.dedup_by(|first, second| key(first) == key(second));
+        }
+        pub fn dedup_by<F>(&mut self, mut same: F) where F: FnMut(&T, &T) -> bool {
+            let mut current = self.root;
+            while let Some(mut node) = current {
+                let node = unsafe { node.as_mut() };
+                match node.next {
+                    None => {
+                        break;
+                    },
+                    Some(following) => {
+                        if same(&unsafe { following.as_ref() }.data, &node.data) {
+                            node.next = unsafe { deallocate_node(&self.allocator, following) }.next;
+                            self.amount -= 1;
+                        } else {
+                            current = Some(following);
+                        }
+                    }
+                }
+            }
+        }
+        pub fn is_sorted(&self) -> bool where T: PartialOrd {
+            return self.refs().is_sorted();
+        }
+        pub fn reverse(&mut self) {
+            let mut previous = None;
+            let mut current = self.root.take();
+            while let Some(mut node) = current {
+                let node_mut = unsafe { node.as_mut() };
+                current = core::mem::replace(&mut node_mut.next, previous);
+                previous = Some(node);
+            }
+            self.root = previous;
+        }
+        pub fn append(&mut self, other: &mut Self) where A: PartialEq {
+            let head = self.take_chain(other);
+            self.link_chain(head, core::mem::take(&mut other.amount));
+        }
+        // Takes `other`'s nodes. Unless the two allocators compare equal, the items are moved into
+        // fresh nodes from this list's allocator, since ours may not be able to free `other`'s.
+        fn take_chain(&self, other: &mut Self) -> Option<NonNull<Single<T>>> where A: PartialEq {
+            let head = other.root.take();
+            if self.allocator == other.allocator {
+                return head;
+            }
+            let mut created_head = None;
+            let mut last: Option<NonNull<Single<T>>> = None;
+            let mut current = head;
+            while let Some(node) = current {
+                let old = unsafe { deallocate_node(&other.allocator, node) };
+                current = old.next;
+                let created = allocate_node(&self.allocator, Single {
+                    data: old.data,
+                    next: None,
+                });
+                match last {
+                    None => {
+                        created_head = Some(created);
+                    },
+                    Some(mut value) => {
+                        unsafe { value.as_mut() }.next = Some(created);
+                    }
+                }
+                last = Some(created);
+            }
+            return created_head;
+        }
+        // `head` must start a chain of `amount` nodes that this list's allocator can free.
+        fn link_chain(&mut self, head: Option<NonNull<Single<T>>>, amount: usize) {
+            let head = match head {
+                None => {
+                    return;
+                },
+                Some(value) => {
+                    value
+                }
+            };
+            match self.amount.checked_sub(1).and_then(|index| self.node_at(index)) {
+                None => {
+                    self.root = Some(head);
+                },
+                Some(mut last) => {
+                    unsafe { last.as_mut() }.next = Some(head);
+                }
+            }
+            self.amount += amount;
+        }
+        pub fn retain<F>(&mut self, mut keep: F) where F: FnMut(&T) -> bool {
+            self.retain_mut(|data| keep(data));
+        }
+        pub fn retain_mut<F>(&mut self, mut keep: F) where F: FnMut(&mut T) -> bool {
+            let (mut removed, _) = self.unlink_where(|data| !keep(data));
+            while let Some(node) = removed {
+                removed = unsafe { deallocate_node(&self.allocator, node) }.next;
+            }
+        }
+        // Unlinks every node `remove` picks and returns them as a chain in list order, with its length.
+        fn unlink_where<F>(&mut self, mut remove: F) -> (Option<NonNull<Single<T>>>, usize) where F: FnMut(&mut T) -> bool {
+            let mut removed = None;
+            let mut last: Option<NonNull<Single<T>>> = None;
+            let mut count = 0;
+            let mut previous: Option<NonNull<Single<T>>> = None;
+            let mut current = self.root;
+            while let Some(mut node) = current {
+                let node_mut = unsafe { node.as_mut() };
+                current = node_mut.next;
+                if remove(&mut node_mut.data) {
+                    node_mut.next = None;
+                    match previous {
+                        None => {
+                            self.root = current;
+                        },
+                        Some(mut value) => {
+                            unsafe { value.as_mut() }.next = current;
+                        }
+                    }
+                    match last {
+                        None => {
+                            removed = Some(node);
+                        },
+                        Some(mut value) => {
+                            unsafe { value.as_mut() }.next = Some(node);
+                        }
+                    }
+                    last = Some(node);
+                    self.amount -= 1;
+                    count += 1;
+                } else {
+                    previous = Some(node);
+                }
+            }
+            return (removed, count);
+        }
+        pub fn contains(&self, value: &T) -> bool where T: PartialEq {
+            return self.refs().any(|data| data == value);
+        }
+        pub fn find<P>(&self, mut predicate: P) -> Option<&T> where P: FnMut(&T) -> bool {
+            return self.refs().find(|data| predicate(data));
+        }
+        pub fn position<P>(&self, predicate: P) -> Option<usize> where P: FnMut(&T) -> bool {
+            return self.refs().position(predicate);
+        }
+        pub fn find_map<B, F>(&self, f: F) -> Option<B> where F: FnMut(&T) -> Option<B> {
+            return self.refs().find_map(f);
+        }
+        pub fn count_matching<P>(&self, mut predicate: P) -> usize where P: FnMut(&T) -> bool {
+            return self.refs().filter(|data| predicate(data)).count();
+        }
+        pub fn front(&self) -> Option<&T> {
+            return self.refs().next();
+        }
+        // Like `slice::binary_search`, but see `search_sorted`: O(n) node steps on a linked list.
+        pub fn binary_search(&self, value: &T) -> Result<usize, usize> where T: Ord {
+            return self.binary_search_by(|data| data.cmp(value));
+        }
+        pub fn binary_search_by<F>(&self, compare: F) -> Result<usize, usize> where F: FnMut(&T) -> core::cmp::Ordering {
+            return search_sorted(self.refs(), self.amount, compare);
+        }
+        pub fn binary_search_by_key<K, F>(&self, key: &K, mut f: F) -> Result<usize, usize> where K: Ord, F: FnMut(&T) -> K {
+            return self.binary_search_by(|data| f(data).cmp(key));
+        }
+        pub fn back(&self) -> Option<&T> {
+            return self.refs().last();
+        }
+        pub fn first_mut(&mut self) -> Option<&mut T> {
+            let mut node = self.root?;
+            return Some(unsafe { &mut node.as_mut().data });
+        }
+        pub fn last_mut(&mut self) -> Option<&mut T> {
+            let mut node = self.node_at(self.amount.checked_sub(1)?)?;
+            return Some(unsafe { &mut node.as_mut().data });
+        }
+        fn refs(&self) -> Refs<'_, Single<T>> {
+            return Refs {
+                node: self.root,
+                marker: PhantomData,
+            };
+        }
+        fn node_at(&self, index: usize) -> Option<NonNull<Single<T>>> {
+            let mut current = self.root;
+            for _ in 0..index {
+                current = unsafe { current?.as_ref() }.next;
+            }
+            return current;
+        }
+    }
+    impl<T, A> SinglyLinkedList<T, A> where T: Clone + core::fmt::Display + core::fmt::Debug, A: Allocator + Clone {
+        pub fn split_off(&mut self, at: usize) -> Result<Self, Box<dyn core::error::Error>> {
+            if at > self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut other = SinglyLinkedList::new_in(self.allocator.clone());
+            if at == 0 {
+                other.root = self.root.take();
+            } else {
+                let mut node = self.node_at(at - 1).unwrap();
+                other.root = unsafe { node.as_mut() }.next.take();
+            }
+            other.amount = self.amount - at;
+            self.amount = at;
+            return Ok(other);
+        }
+        pub fn splice<R, I>(&mut self, range: R, items: I) -> Result<Self, Box<dyn core::error::Error>> where R: core::ops::RangeBounds<usize>, I: IntoIterator<Item = T> {
+            let (start, end) = resolve_range(range, self.amount)?;
+            let mut rest = self.split_off(end)?;
+            let removed = self.split_off(start)?;
+            let mut last = self.amount.checked_sub(1).and_then(|index| self.node_at(index));
+            for data in items {
+                let node = allocate_node(&self.allocator, Single {
+                    data,
+                    next: None,
+                });
+                match last {
+                    None => {
+                        self.root = Some(node);
+                    },
+                    Some(mut value) => {
+                        unsafe { value.as_mut() }.next = Some(node);
+                    }
+                }
+                last = Some(node);
+                self.amount += 1;
+            }
+            self.link_chain(rest.root.take(), core::mem::take(&mut rest.amount));
+            return Ok(removed);
+        }
+        pub fn drain<R>(&mut self, range: R) -> Result<Self, Box<dyn core::error::Error>> where R: core::ops::RangeBounds<usize> {
+            return self.splice(range, core::iter::empty());
+        }
+        pub fn extract_if<F>(&mut self, remove: F) -> Self where F: FnMut(&mut T) -> bool {
+            let (root, amount) = self.unlink_where(remove);
+            let mut removed = SinglyLinkedList::new_in(self.allocator.clone());
+            removed.root = root;
+            removed.amount = amount;
+            return removed;
+        }
+    }
+    impl<T, A> Drop for SinglyLinkedList<T, A> where T: Clone + core::fmt::Display + core::fmt::Debug, A: Allocator {
+        fn drop(&mut self) {
+            while self.pop_begin().is_ok() {}
+        }
+    }
+    impl<T> Link for Single<T> where T: Clone + core::fmt::Display + core::fmt::Debug {
+        type Data = T;
+        fn data(&self) -> &T {
+            return &self.data;
+        }
+        fn next(&self) -> Option<NonNull<Self>> {
+            return self.next;
+        }
+        fn set_next(&mut self, next: Option<NonNull<Self>>) {
+            self.next = next;
+        }
+    }
+    struct Double<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        data: T,
+        prev: Option<NonNull<Double<T>>>,
+        next: Option<NonNull<Double<T>>>,
+    }
+    type DoubleLink<T> = Option<NonNull<Double<T>>>;
+    impl<T> Double<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        fn new(data: T) -> Self {
+            return Double {
+                data,
+                prev: None,
+                next: None,
+            };
+        }
+    }
+    pub struct DoublyLinkedList<T: Clone + core::fmt::Debug + core::fmt::Display, A: Allocator = Global> {
+        head: Option<NonNull<Double<T>>>,
+        tail: Option<NonNull<Double<T>>>,
+        amount: usize,
+        allocator: A,
+    }
+    impl<T> DoublyLinkedList<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        pub fn new() -> Self {
+            return DoublyLinkedList::new_in(Global);
+        }
+    }
+    impl<T, A> DoublyLinkedList<T, A> where T: Clone + core::fmt::Debug + core::fmt::Display, A: Allocator {
+        pub fn new_in(allocator: A) -> Self {
+            return DoublyLinkedList {
+                head: None,
+                tail: None,
+                amount: 0,
+                allocator,
+            };
+        }
+        pub fn push_begin(&mut self, data: T) {
+            let node = allocate_node(&self.allocator, Double::new(data));
+            match self.head {
+                None => {
+                    self.push_back_node(node);
+                },
+                Some(head) => {
+                    unsafe { self.link_before(node, head) };
+                }
+            }
+        }
+        pub fn push_back(&mut self, data: T) {
+            let node = allocate_node(&self.allocator, Double::new(data));
+            self.push_back_node(node);
+        }
+        pub fn insert(&mut self, data: T, index: usize) {
+            match self.node_at(index) {
+                None => {
+                    self.push_back(data);
+                },
+                Some(anchor) => {
+                    let node = allocate_node(&self.allocator, Double::new(data));
+                    unsafe { self.link_before(node, anchor) };
+                }
+            }
+        }
+        pub fn pop_begin(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+            match self.pop_begin_node() {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(node) => {
+                    unsafe { deallocate_node(&self.allocator, node) };
+                    return Ok(());
+                }
+            }
+        }
+        pub fn pop_back(&mut self) -> Result<(), Box<dyn core::error::Error>> {
+            match self.tail {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(node) => {
+                    unsafe {
+                        self.unlink_node(node);
+                        deallocate_node(&self.allocator, node);
+                    }
+                    return Ok(());
+                }
+            }
+        }
+        pub fn erase(&mut self, index: usize) -> Result<(), Box<dyn core::error::Error>> {
+            match self.node_at(index) {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(node) => {
+                    unsafe {
+                        self.unlink_node(node);
+                        deallocate_node(&self.allocator, node);
+                    }
+                    return Ok(());
+                }
+            }
+        }
+        pub fn get(&self, index: usize) -> Result<T, Box<dyn core::error::Error>> {
+            match self.node_at(index) {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(node) => {
+                    return Ok(unsafe { node.as_ref() }.data.clone());
+                }
+            }
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) {
+            println!("=====================list=begin=====================");
+            if self.amount == 0 {
+                println!("\n");
+            }
+            for value in self.refs() {
+                println!("{}", value);
+            }
+            println!("======================list=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        pub fn allocator(&self) -> &A {
+            return &self.allocator;
+        }
+        #[cfg(feature = "std")]
+        pub fn write(&self, index: usize) {
+            match &self.get(index) {
+                Ok(value) => {
+                    println!("{}", value);
+                },
+                Err(_) => {
+                    println!("there is nothing to see");
+                }
+            }
+        }
+        pub fn sort(&mut self) where T: Ord {
+            self.sort_by(|first, second| first.cmp(second));
+        }
+        pub fn sort_by<F>(&mut self, mut compare: F) where F: FnMut(&T, &T) -> core::cmp::Ordering {
+            let head = self.head.take();
+            self.head = sort_chain(head, self.amount, &mut compare);
+            self.relink();
+        }
+        pub fn sort_by_key<K, F>(&mut self, mut key: F) where K: Ord, F: FnMut(&T) -> K {
+            self.sort_by(|first, second| key(first).cmp(&key(second)));
+        }
+        pub fn merge_sorted(&mut self, other: &mut Self) where T: Ord, A: PartialEq {
+            let first = self.head.take();
+            let (second, _) = self.take_chain(other);
+            self.head = merge_chains(first, second, &mut |first: &T, second: &T| first.cmp(second));
+            self.relink();
+            self.amount += other.amount;
+            other.amount = 0;
+        }
+        pub fn insert_sorted(&mut self, data: T) where T: Ord {
+            let mut current = self.head;
+            while let Some(node) = current {
+                let node_ref = unsafe { node.as_ref() };
+                if node_ref.data > data {
+                    let created = allocate_node(&self.allocator, Double::new(data));
+                    unsafe { self.link_before(created, node) };
+                    return;
+                }
+                current = node_ref.next;
+            }
+            self.push_back(data);
+        }
+        pub fn dedup(&mut self) where T: PartialEq {
+            self.dedup_by(|first, second| first == second);
+        }
+        pub fn dedup_by_key<K, F>(&mut self, mut key: F) where K: PartialEq, F: FnMut(&T) -> K {
+            self.dedup_by(|first, second| key(first) == key(second));
+        }
+        pub fn dedup_by<F>(&mut self, mut same: F) where F: FnMut(&T, &T) -> bool {
+            let mut current = self.head;
+            while let Some(node) = current {
+                match unsafe { node.as_ref() }.next {
+                    None => {
+                        break;
+                    },
+                    Some(following) => {
+                        if same(&unsafe { following.as_ref() }.data, &unsafe { node.as_ref() }.data) {
+                            unsafe {
+                                self.unlink_node(following);
+                                deallocate_node(&self.allocator, following);
+                            }
+                        } else {
+                            current = Some(following);
+                        }
+                    }
+                }
+            }
+        }
+        pub fn is_sorted(&self) -> bool where T: PartialOrd {
+            return self.refs().is_sorted();
+        }
+        pub fn reverse(&mut self) {
+            let mut current = self.head;
+            while let Some(mut node) = current {
+                let node_mut = unsafe { node.as_mut() };
+                current = node_mut.next;
+                core::mem::swap(&mut node_mut.prev, &mut node_mut.next);
+            }
+            core::mem::swap(&mut self.head, &mut self.tail);
+        }
+        pub fn append(&mut self, other: &mut Self) where A: PartialEq {
+            let (head, tail) = self.take_chain(other);
+            self.link_chain(head, tail, core::mem::take(&mut other.amount));
+        }
+        // Takes `other`'s nodes as `(head, tail)`. Unless the two allocators compare equal, the items are
+        // moved into fresh nodes from this list's allocator, since ours may not be able to free `other`'s.
+        fn take_chain(&self, other: &mut Self) -> (DoubleLink<T>, DoubleLink<T>) where A: PartialEq {
+            let (head, tail) = (other.head.take(), other.tail.take());
+            if self.allocator == other.allocator {
+                return (head, tail);
+            }
+            let mut created_head = None;
+            let mut last: Option<NonNull<Double<T>>> = None;
+            let mut current = head;
+            while let Some(node) = current {
+                let old = unsafe { deallocate_node(&other.allocator, node) };
+                current = old.next;
+                let mut created = allocate_node(&self.allocator, Double::new(old.data));
+                unsafe { created.as_mut() }.prev = last;
+                match last {
+                    None => {
+                        created_head = Some(created);
+                    },
+                    Some(mut value) => {
+                        unsafe { value.as_mut() }.next = Some(created);
+                    }
+                }
+                last = Some(created);
+            }
+            return (created_head, last);
+        }
+        // `head..=tail` must be a chain of `amount` nodes that this list's allocator can free.
+        fn link_chain(&mut self, head: DoubleLink<T>, tail: DoubleLink<T>, amount: usize) {
+            let head = match head {
+                None => {
+                    return;
+                },
+                Some(value) => {
+                    value
+                }
+            };
+            match self.tail {
+                None => {
+                    self.head = Some(head);
+                },
+                Some(mut last) => {
+                    unsafe {
+                        (*head.as_ptr()).prev = Some(last);
+                        last.as_mut().next = Some(head);
+                    }
+                }
+            }
+            self.tail = tail;
+            self.amount += amount;
+        }
+        pub fn retain<F>(&mut self, mut keep: F) where F: FnMut(&T) -> bool {
+            self.retain_mut(|data| keep(data));
+        }
+        pub fn retain_mut<F>(&mut self, mut keep: F) where F: FnMut(&mut T) -> bool {
+            let mut current = self.head;
+            while let Some(mut node) = current {
+                current = unsafe { node.as_ref() }.next;
+                if !keep(&mut unsafe { node.as_mut() }.data) {
+                    unsafe {
+                        self.unlink_node(node);
+                        deallocate_node(&self.allocator, node);
+                    }
+                }
+            }
+        }
+        pub fn contains(&self, value: &T) -> bool where T: PartialEq {
+            return self.refs().any(|data| data == value);
+        }
+        pub fn find<P>(&self, mut predicate: P) -> Option<&T> where P: FnMut(&T) -> bool {
+            return self.refs().find(|data| predicate(data));
+        }
+        pub fn position<P>(&self, predicate: P) -> Option<usize> where P: FnMut(&T) -> bool {
+            return self.refs().position(predicate);
+        }
+        pub fn find_map<B, F>(&self, f: F) -> Option<B> where F: FnMut(&T) -> Option<B> {
+            return self.refs().find_map(f);
+        }
+        pub fn count_matching<P>(&self, mut predicate: P) -> usize where P: FnMut(&T) -> bool {
+            return self.refs().filter(|data| predicate(data)).count();
+        }
+        pub fn front(&self) -> Option<&T> {
+            return self.refs().next();
+        }
+        // Like `slice::binary_search`, but see `search_sorted`: O(n) node steps on a linked list.
+        pub fn binary_search(&self, value: &T) -> Result<usize, usize> where T: Ord {
+            return self.binary_search_by(|data| data.cmp(value));
+        }
+        pub fn binary_search_by<F>(&self, compare: F) -> Result<usize, usize> where F: FnMut(&T) -> core::cmp::Ordering {
+            return search_sorted(self.refs(), self.amount, compare);
+        }
+        pub fn binary_search_by_key<K, F>(&self, key: &K, mut f: F) -> Result<usize, usize> where K: Ord, F: FnMut(&T) -> K {
+            return self.binary_search_by(|data| f(data).cmp(key));
+        }
+        pub fn back(&self) -> Option<&T> {
+            let node = self.tail?;
+            return Some(unsafe { &(*node.as_ptr()).data });
+        }
+        pub fn rposition<P>(&self, mut predicate: P) -> Option<usize> where P: FnMut(&T) -> bool {
+            let mut index = self.amount;
+            let mut current = self.tail;
+            while let Some(node) = current {
+                index -= 1;
+                let node = unsafe { node.as_ref() };
+                if predicate(&node.data) {
+                    return Some(index);
+                }
+                current = node.prev;
+            }
+            return None;
+        }
+        pub fn first_mut(&mut self) -> Option<&mut T> {
+            let mut node = self.head?;
+            return Some(unsafe { &mut node.as_mut().data });
+        }
+        pub fn last_mut(&mut self) -> Option<&mut T> {
+            let mut node = self.tail?;
+            return Some(unsafe { &mut node.as_mut().data });
+        }
+        fn refs(&self) -> Refs<'_, Double<T>> {
+            return Refs {
+                node: self.head,
+                marker: PhantomData,
+            };
+        }
+        fn node_at(&self, index: usize) -> Option<NonNull<Double<T>>> {
+            if index >= self.amount {
+                return None;
+            }
+            if index < self.amount / 2 {
+                let mut current = self.head;
+                for _ in 0..index {
+                    current = unsafe { current?.as_ref() }.next;
+                }
+                return current;
+            }
+            let mut current = self.tail;
+            for _ in index + 1..self.amount {
+                current = unsafe { current?.as_ref() }.prev;
+            }
+            return current;
+        }
+        fn relink(&mut self) {
+            let mut previous: Option<NonNull<Double<T>>> = None;
+            let mut current = self.head;
+            while let Some(mut node) = current {
+                let node_mut = unsafe { node.as_mut() };
+                node_mut.prev = previous;
+                current = node_mut.next;
+                previous = Some(node);
+            }
+            self.tail = previous;
+        }
+        // `node` must be unlinked and allocated by this list's allocator; the list takes ownership of it.
+        fn push_back_node(&mut self, mut node: NonNull<Double<T>>) {
+            unsafe {
+                let node_mut = node.as_mut();
+                node_mut.prev = self.tail;
+                node_mut.next = None;
+            }
+            match self.tail {
+                None => {
+                    self.head = Some(node);
+                },
+                Some(mut last) => {
+                    unsafe { last.as_mut() }.next = Some(node);
+                }
+            }
+            self.tail = Some(node);
+            self.amount += 1;
+        }
+        fn pop_begin_node(&mut self) -> Option<NonNull<Double<T>>> {
+            let node = self.head?;
+            unsafe { self.unlink_node(node) };
+            return Some(node);
+        }
+        // Safety: `node` must be linked into this list. It stays allocated; the caller now owns it.
+        unsafe fn unlink_node(&mut self, mut node: NonNull<Double<T>>) {
+            let node_mut = unsafe { node.as_mut() };
+            let prev = node_mut.prev.take();
+            let next = node_mut.next.take();
+            match prev {
+                None => {
+                    self.head = next;
+                },
+                Some(mut value) => {
+                    unsafe { value.as_mut() }.next = next;
+                }
+            }
+            match next {
+                None => {
+                    self.tail = prev;
+                },
+                Some(mut value) => {
+                    unsafe { value.as_mut() }.prev = prev;
+                }
+            }
+            self.amount -= 1;
+        }
+        // Safety: `node` must be unlinked and owned by the caller, and `anchor` linked into this list.
+        unsafe fn link_before(&mut self, mut node: NonNull<Double<T>>, mut anchor: NonNull<Double<T>>) {
+            let prev = unsafe { anchor.as_ref() }.prev;
+            unsafe {
+                let node_mut = node.as_mut();
+                node_mut.prev = prev;
+                node_mut.next = Some(anchor);
+                anchor.as_mut().prev = Some(node);
+            }
+            match prev {
+                None => {
+                    self.head = Some(node);
+                },
+                Some(mut value) => {
+                    unsafe { value.as_mut() }.next = Some(node);
+                }
+            }
+            self.amount += 1;
+        }
+    }
+    impl<T, A> DoublyLinkedList<T, A> where T: Clone + core::fmt::Debug + core::fmt::Display, A: Allocator + Clone {
+        pub fn split_off(&mut self, at: usize) -> Result<Self, Box<dyn core::error::Error>> {
+            if at > self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut other = DoublyLinkedList::new_in(self.allocator.clone());
+            if at == 0 {
+                other.head = self.head.take();
+                other.tail = self.tail.take();
+            } else if let Some(mut rest) = self.node_at(at) {
+                let mut last = unsafe { rest.as_mut() }.prev.take().unwrap();
+                unsafe { last.as_mut() }.next = None;
+                other.head = Some(rest);
+                other.tail = self.tail.replace(last);
+            }
+            other.amount = self.amount - at;
+            self.amount = at;
+            return Ok(other);
+        }
+        pub fn splice<R, I>(&mut self, range: R, items: I) -> Result<Self, Box<dyn core::error::Error>> where R: core::ops::RangeBounds<usize>, I: IntoIterator<Item = T> {
+            let (start, end) = resolve_range(range, self.amount)?;
+            let mut rest = self.split_off(end)?;
+            let removed = self.split_off(start)?;
+            for data in items {
+                self.push_back(data);
+            }
+            self.link_chain(rest.head.take(), rest.tail.take(), core::mem::take(&mut rest.amount));
+            return Ok(removed);
+        }
+        pub fn drain<R>(&mut self, range: R) -> Result<Self, Box<dyn core::error::Error>> where R: core::ops::RangeBounds<usize> {
+            return self.splice(range, core::iter::empty());
+        }
+        pub fn extract_if<F>(&mut self, mut remove: F) -> Self where F: FnMut(&mut T) -> bool {
+            let mut removed = DoublyLinkedList::new_in(self.allocator.clone());
+            let mut current = self.head;
+            while let Some(mut node) = current {
+                current = unsafe { node.as_ref() }.next;
+                if remove(&mut unsafe { node.as_mut() }.data) {
+                    unsafe { self.unlink_node(node) };
+                    removed.push_back_node(node);
+                }
+            }
+            return removed;
+        }
+    }
+    impl<T, A> Drop for DoublyLinkedList<T, A> where T: Clone + core::fmt::Debug + core::fmt::Display, A: Allocator {
+        fn drop(&mut self) {
+            while self.pop_begin().is_ok() {}
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Policy {
+        MoveToFront,
+        Transpose,
+        Count,
+    }
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct AccessStats {
+        pub lookups: u64,
+        pub hits: u64,
+        pub misses: u64,
+        pub comparisons: u64,
+        pub moves: u64,
+    }
+    impl AccessStats {
+        pub fn average_cost(&self) -> f64 {
+            if self.lookups == 0 {
+                return 0.0;
+            }
+            return self.comparisons as f64 / self.lookups as f64;
+        }
+    }
+    #[derive(Clone)]
+    struct Tracked<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        data: T,
+        hits: u64,
+    }
+    impl<T> core::fmt::Debug for Tracked<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            return core::fmt::Debug::fmt(&self.data, f);
+        }
+    }
+    impl<T> core::fmt::Display for Tracked<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            return core::fmt::Display::fmt(&self.data, f);
+        }
+    }
+    pub struct SelfOrganizingList<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        list: DoublyLinkedList<Tracked<T>>,
+        policy: Policy,
+        stats: AccessStats,
+    }
+    impl<T> SelfOrganizingList<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        pub fn new(policy: Policy) -> Self {
+            return SelfOrganizingList {
+                list: DoublyLinkedList::new(),
+                policy,
+                stats: AccessStats::default(),
+            };
+        }
+        pub fn push(&mut self, data: T) {
+            self.list.push_back(Tracked {
+                data,
+                hits: 0,
+            });
+        }
+        pub fn find<P>(&mut self, mut predicate: P) -> Option<&T> where P: FnMut(&T) -> bool {
+            self.stats.lookups += 1;
+            let mut current = self.list.head;
+            while let Some(node) = current {
+                self.stats.comparisons += 1;
+                if predicate(&unsafe { node.as_ref() }.data.data) {
+                    self.stats.hits += 1;
+                    self.reorganize(node);
+                    // The list keeps the node alive and `&mut self` stays borrowed for the result.
+                    return Some(unsafe { &(*node.as_ptr()).data.data });
+                }
+                current = unsafe { node.as_ref() }.next;
+            }
+            self.stats.misses += 1;
+            return None;
+        }
+        pub fn contains(&mut self, value: &T) -> bool where T: PartialEq {
+            return self.find(|data| data == value).is_some();
+        }
+        pub fn remove<P>(&mut self, mut predicate: P) -> Option<T> where P: FnMut(&T) -> bool {
+            // A removal scans like `find`, so it is charged to the same counters.
+            self.stats.lookups += 1;
+            let mut current = self.list.head;
+            while let Some(node) = current {
+                self.stats.comparisons += 1;
+                if predicate(&unsafe { node.as_ref() }.data.data) {
+                    self.stats.hits += 1;
+                    unsafe { self.list.unlink_node(node) };
+                    return Some(unsafe { deallocate_node(&self.list.allocator, node) }.data.data);
+                }
+                current = unsafe { node.as_ref() }.next;
+            }
+            self.stats.misses += 1;
+            return None;
+        }
+        pub fn front(&self) -> Option<&T> {
+            return self.list.front().map(|value| &value.data);
+        }
+        pub fn hits(&self, value: &T) -> Option<u64> where T: PartialEq {
+            return self.list.find(|tracked| &tracked.data == value).map(|tracked| tracked.hits);
+        }
+        pub fn policy(&self) -> Policy {
+            return self.policy;
+        }
+        pub fn set_policy(&mut self, policy: Policy) {
+            self.policy = policy;
+        }
+        pub fn stats(&self) -> &AccessStats {
+            return &self.stats;
+        }
+        pub fn reset_stats(&mut self) {
+            self.stats = AccessStats::default();
+        }
+        pub fn to_vec(&self) -> Vec<T> {
+            return self.list.refs().map(|tracked| tracked.data.clone()).collect();
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) {
+            self.list.show();
+        }
+        pub fn size(&self) -> &usize {
+            return self.list.size();
+        }
+        fn reorganize(&mut self, mut node: NonNull<Double<Tracked<T>>>) {
+            let hits = {
+                let tracked = &mut unsafe { node.as_mut() }.data;
+                tracked.hits += 1;
+                tracked.hits
+            };
+            let prev = unsafe { node.as_ref() }.prev;
+            let anchor = match self.policy {
+                Policy::MoveToFront => {
+                    prev.and(self.list.head)
+                },
+                Policy::Transpose => {
+                    prev
+                },
+                Policy::Count => {
+                    let mut anchor = None;
+                    let mut current = prev;
+                    while let Some(value) = current.filter(|value| unsafe { value.as_ref() }.data.hits < hits) {
+                        current = unsafe { value.as_ref() }.prev;
+                        anchor = Some(value);
+                    }
+                    anchor
+                }
+            };
+            if let Some(anchor) = anchor {
+                unsafe {
+                    self.list.unlink_node(node);
+                    self.list.link_before(node, anchor);
+                }
+                self.stats.moves += 1;
+            }
+        }
+    }
+    impl<T> Link for Double<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        type Data = T;
+        fn data(&self) -> &T {
+            return &self.data;
+        }
+        fn next(&self) -> Option<NonNull<Self>> {
+            return self.next;
+        }
+        fn set_next(&mut self, next: Option<NonNull<Self>>) {
+            self.next = next;
+        }
+    }
+    pub(crate) fn resolve_range<R>(range: R, amount: usize) -> Result<(usize, usize), Box<dyn core::error::Error>> where R: core::ops::RangeBounds<usize> {
+        // `usize::MAX` past an excluded start or an included end is out of range, not an overflow.
+        let start = match range.start_bound() {
+            core::ops::Bound::Included(value) => Some(*value),
+            core::ops::Bound::Excluded(value) => value.checked_add(1),
+            core::ops::Bound::Unbounded => Some(0),
+        };
+        let end = match range.end_bound() {
+            core::ops::Bound::Included(value) => value.checked_add(1),
+            core::ops::Bound::Excluded(value) => Some(*value),
+            core::ops::Bound::Unbounded => Some(amount),
+        };
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end && end <= amount => {
+                return Ok((start, end));
+            },
+            _ => {
+                return Err(Box::new(core::fmt::Error));
+            }
+        }
+    }
+    trait Link: Sized {
+        type Data;
+        fn data(&self) -> &Self::Data;
+        fn next(&self) -> Option<NonNull<Self>>;
+        fn set_next(&mut self, next: Option<NonNull<Self>>);
+    }
+    // Nodes are only relinked or freed through `&mut` on the owning list, so they outlive any
+    // shared borrow of it.
+    struct Refs<'a, N: Link> {
+        node: Option<NonNull<N>>,
+        marker: PhantomData<&'a N>,
+    }
+    impl<N> Clone for Refs<'_, N> where N: Link {
+        fn clone(&self) -> Self {
+            return Refs {
+                node: self.node,
+                marker: PhantomData,
+            };
+        }
+    }
+    impl<'a, N> Iterator for Refs<'a, N> where N: Link {
+        type Item = &'a N::Data;
+        fn next(&mut self) -> Option<&'a N::Data> {
+            let node: &'a N = unsafe { &*self.node?.as_ptr() };
+            self.node = node.next();
+            return Some(node.data());
+        }
+    }
+    // Binary search over a forward iterator: O(log n) comparisons, but every probe re-walks the
+    // list from `base`, so it still takes O(n) node steps in total.
+    fn search_sorted<'a, T, I, F>(items: I, amount: usize, mut compare: F) -> Result<usize, usize> where T: 'a, I: Iterator<Item = &'a T> + Clone, F: FnMut(&T) -> core::cmp::Ordering {
+        if amount == 0 {
+            return Err(0);
+        }
+        let mut base = items;
+        let mut index = 0;
+        let mut size = amount;
+        while size > 1 {
+            let half = size / 2;
+            let mut probe = base.clone();
+            for _ in 0..half {
+                probe.next();
+            }
+            if compare(probe.clone().next().unwrap()) != core::cmp::Ordering::Greater {
+                base = probe;
+                index += half;
+            }
+            size -= half;
+        }
+        match compare(base.next().unwrap()) {
+            core::cmp::Ordering::Equal => {
+                return Ok(index);
+            },
+            core::cmp::Ordering::Less => {
+                return Err(index + 1);
+            },
+            core::cmp::Ordering::Greater => {
+                return Err(index);
+            }
+        }
+    }
+    fn sort_chain<N, F>(head: Option<NonNull<N>>, length: usize, compare: &mut F) -> Option<NonNull<N>> where N: Link, F: FnMut(&N::Data, &N::Data) -> core::cmp::Ordering {
+        if length <= 1 {
+            return head;
+        }
+        let first = head?;
+        let middle = length / 2;
+        let mut last = first;
+        for _ in 1..middle {
+            last = unsafe { last.as_ref() }.next().unwrap();
+        }
+        let second = unsafe { last.as_ref() }.next();
+        unsafe { last.as_mut() }.set_next(None);
+        let first = sort_chain(Some(first), middle, compare);
+        let second = sort_chain(second, length - middle, compare);
+        return merge_chains(first, second, compare);
+    }
+    fn merge_chains<N, F>(first: Option<NonNull<N>>, second: Option<NonNull<N>>, compare: &mut F) -> Option<NonNull<N>> where N: Link, F: FnMut(&N::Data, &N::Data) -> core::cmp::Ordering {
+        let mut head: Option<NonNull<N>> = None;
+        let mut tail: Option<NonNull<N>> = None;
+        let mut first = first;
+        let mut second = second;
+        loop {
+            let take_first = match (first, second) {
+                (Some(left), Some(right)) => compare(unsafe { left.as_ref() }.data(), unsafe { right.as_ref() }.data()) != core::cmp::Ordering::Greater,
+                _ => {
+                    let rest = first.or(second);
+                    match tail {
+                        None => {
+                            head = rest;
+                        },
+                        Some(mut last) => {
+                            unsafe { last.as_mut() }.set_next(rest);
+                        }
+                    }
+                    return head;
+                }
+            };
+            let source = if take_first { &mut first } else { &mut second };
+            let mut node = source.take().unwrap();
+            *source = unsafe { node.as_ref() }.next();
+            unsafe { node.as_mut() }.set_next(None);
+            match tail {
+                None => {
+                    head = Some(node);
+                },
+                Some(mut last) => {
+                    unsafe { last.as_mut() }.set_next(Some(node));
+                }
+            }
+            tail = Some(node);
+        }
+    }
+    struct Unrolled<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        items: Vec<T>,
+        next: Option<Rc<RefCell<Unrolled<T>>>>,
+    }
+    pub struct UnrolledList<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        head: Option<Rc<RefCell<Unrolled<T>>>>,
+        tail: Option<Weak<RefCell<Unrolled<T>>>>,
+        node_capacity: usize,
+        amount: usize,
+    }
+    impl<T> UnrolledList<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        pub fn new() -> Self {
+            return UnrolledList::with_node_capacity(16);
+        }
+        pub fn with_node_capacity(node_capacity: usize) -> Self {
+            return UnrolledList {
+                head: None,
+                tail: None,
+                node_capacity: node_capacity.max(2),
+                amount: 0,
+            };
+        }
+        pub fn push_back(&mut self, data: T) {
+            match self.tail.as_ref().and_then(|value| value.upgrade()) {
+                None => {
+                    self.push_first(data);
+                },
+                Some(mut node) => {
+                    if node.borrow().items.len() == self.node_capacity {
+                        node = self.split(&node);
+                    }
+                    node.borrow_mut().items.push(data);
+                    self.amount += 1;
+                }
+            }
+        }
+        pub fn push_begin(&mut self, data: T) {
+            match self.head.clone() {
+                None => {
+                    self.push_first(data);
+                },
+                Some(node) => {
+                    if node.borrow().items.len() == self.node_capacity {
+                        self.split(&node);
+                    }
+                    node.borrow_mut().items.insert(0, data);
+                    self.amount += 1;
+                }
+            }
+        }
+        pub fn insert(&mut self, data: T, index: usize) {
+            if index >= self.amount {
+                self.push_back(data);
+                return;
+            }
+            let (mut node, mut offset) = self.locate(index);
+            if node.borrow().items.len() == self.node_capacity {
+                let next = self.split(&node);
+                let length = node.borrow().items.len();
+                if offset > length {
+                    offset -= length;
+                    node = next;
+                }
+            }
+            node.borrow_mut().items.insert(offset, data);
+            self.amount += 1;
+        }
+        pub fn erase(&mut self, index: usize) -> Result<(), Box<dyn core::error::Error>> {
+            if index >= self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let (node, offset) = self.locate(index);
+            node.borrow_mut().items.remove(offset);
+            self.amount -= 1;
+            if node.borrow().items.len() >= self.node_capacity / 2 {
+                return Ok(());
+            }
+            let next = node.borrow().next.clone();
+            match next {
+                None => {
+                    match self.previous(&node) {
+                        None => {
+                            if node.borrow().items.is_empty() {
+                                self.unlink(&node);
+                            }
+                        },
+                        Some(previous) => {
+                            // The tail has no successor to borrow from, so it leans on its predecessor.
+                            let total = previous.borrow().items.len() + node.borrow().items.len();
+                            if total <= self.node_capacity {
+                                let mut items = core::mem::take(&mut node.borrow_mut().items);
+                                previous.borrow_mut().items.append(&mut items);
+                                previous.borrow_mut().next = None;
+                                self.tail = Some(Rc::downgrade(&previous));
+                            } else {
+                                let moved = total / 2 - node.borrow().items.len();
+                                let start = previous.borrow().items.len() - moved;
+                                let taken: Vec<T> = previous.borrow_mut().items.drain(start..).collect();
+                                node.borrow_mut().items.splice(0..0, taken);
+                            }
+                        }
+                    }
+                },
+                Some(next) => {
+                    let total = node.borrow().items.len() + next.borrow().items.len();
+                    if total <= self.node_capacity {
+                        let mut following = next.borrow_mut();
+                        let mut current = node.borrow_mut();
+                        current.items.append(&mut following.items);
+                        current.next = following.next.take();
+                        if current.next.is_none() {
+                            self.tail = Some(Rc::downgrade(&node));
+                        }
+                    } else {
+                        let moved = total / 2 - node.borrow().items.len();
+                        let taken: Vec<T> = next.borrow_mut().items.drain(..moved).collect();
+                        node.borrow_mut().items.extend(taken);
+                    }
+                }
+            }
+            return Ok(());
+        }
+        pub fn get(&self, index: usize) -> Result<T, Box<dyn core::error::Error>> {
+            if index >= self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let (node, offset) = self.locate(index);
+            return Ok(node.borrow().items[offset].clone());
+        }
+        pub fn iter(&self) -> UnrolledIter<T> {
+            return UnrolledIter {
+                node: self.head.clone(),
+                offset: 0,
+            };
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) {
+            println!("=====================list=begin=====================");
+            if self.amount == 0 {
+                println!("\n");
+            }
+            for value in self.iter() {
+                println!("{}", value);
+            }
+            println!("======================list=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn push_first(&mut self, data: T) {
+            let mut items = Vec::with_capacity(self.node_capacity);
+            items.push(data);
+            let node = Rc::new(RefCell::new(Unrolled {
+                items,
+                next: None,
+            }));
+            self.tail = Some(Rc::downgrade(&node));
+            self.head = Some(node);
+            self.amount += 1;
+        }
+        fn locate(&self, index: usize) -> (Rc<RefCell<Unrolled<T>>>, usize) {
+            let mut current = Rc::clone(self.head.as_ref().unwrap());
+            let mut offset = index;
+            loop {
+                let length = current.borrow().items.len();
+                if offset < length {
+                    return (current, offset);
+                }
+                offset -= length;
+                let next = Rc::clone(current.borrow().next.as_ref().unwrap());
+                current = next;
+            }
+        }
+        fn split(&mut self, node: &Rc<RefCell<Unrolled<T>>>) -> Rc<RefCell<Unrolled<T>>> {
+            let mut current = node.borrow_mut();
+            let half = current.items.len() / 2;
+            let mut items = Vec::with_capacity(self.node_capacity);
+            items.extend(current.items.drain(half..));
+            let next = Rc::new(RefCell::new(Unrolled {
+                items,
+                next: current.next.take(),
+            }));
+            if next.borrow().next.is_none() {
+                self.tail = Some(Rc::downgrade(&next));
+            }
+            current.next = Some(Rc::clone(&next));
+            return next;
+        }
+        fn previous(&self, node: &Rc<RefCell<Unrolled<T>>>) -> Option<Rc<RefCell<Unrolled<T>>>> {
+            let mut previous: Option<Rc<RefCell<Unrolled<T>>>> = None;
+            let mut current = self.head.clone();
+            while let Some(value) = current {
+                if Rc::ptr_eq(&value, node) {
+                    break;
+                }
+                current = value.borrow().next.clone();
+                previous = Some(value);
+            }
+            return previous;
+        }
+        fn unlink(&mut self, node: &Rc<RefCell<Unrolled<T>>>) {
+            let next = node.borrow_mut().next.take();
+            let previous = self.previous(node);
+            match &previous {
+                None => {
+                    self.head = next.clone();
+                },
+                Some(value) => {
+                    value.borrow_mut().next = next.clone();
+                }
+            }
+            if next.is_none() {
+                self.tail = previous.as_ref().map(Rc::downgrade);
+            }
+        }
+    }
+    impl<T> Drop for UnrolledList<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        fn drop(&mut self) {
+            // Unlink node by node; a live iterator still owns the rest of the chain from its position.
+            let mut current = self.head.take();
+            while let Some(node) = current {
+                match Rc::try_unwrap(node) {
+                    Ok(cell) => {
+                        current = cell.into_inner().next;
+                    },
+                    Err(_) => {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    pub struct UnrolledIter<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        node: Option<Rc<RefCell<Unrolled<T>>>>,
+        offset: usize,
+    }
+    impl<T> Iterator for UnrolledIter<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        type Item = T;
+        fn next(&mut self) -> Option<T> {
+            loop {
+                let node = self.node.clone()?;
+                let current = node.borrow();
+                if self.offset < current.items.len() {
+                    self.offset += 1;
+                    return Some(current.items[self.offset - 1].clone());
+                }
+                self.node = current.next.clone();
+                self.offset = 0;
+            }
+        }
+    }
+    struct Ring<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        data: T,
+        next: Option<Rc<RefCell<Ring<T>>>>,
+    }
+    pub struct CircularList<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        current: Option<Rc<RefCell<Ring<T>>>>,
+        previous: Option<Rc<RefCell<Ring<T>>>>,
+        amount: usize,
+    }
+    impl<T> CircularList<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        pub fn new() -> Self {
+            return CircularList {
+                current: None,
+                previous: None,
+                amount: 0,
+            };
+        }
+        pub fn insert_after_current(&mut self, data: T) {
+            match &self.current {
+                None => {
+                    let node = Rc::new(RefCell::new(Ring {
+                        data,
+                        next: None,
+                    }));
+                    node.borrow_mut().next = Some(Rc::clone(&node));
+                    self.previous = Some(Rc::clone(&node));
+                    self.current = Some(node);
+                },
+                Some(value) => {
+                    let node = Rc::new(RefCell::new(Ring {
+                        data,
+                        next: value.borrow_mut().next.take(),
+                    }));
+                    value.borrow_mut().next = Some(Rc::clone(&node));
+                    if self.amount == 1 {
+                        self.previous = Some(node);
+                    }
+                }
+            }
+            self.amount += 1;
+        }
+        pub fn remove_current(&mut self) -> Result<T, Box<dyn core::error::Error>> {
+            let node = match self.current.take() {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(value) => {
+                    value
+                }
+            };
+            let next = node.borrow_mut().next.take();
+            if self.amount == 1 {
+                self.previous = None;
+                drop(next);
+            } else {
+                self.previous.as_ref().unwrap().borrow_mut().next = next.clone();
+                self.current = next;
+            }
+            self.amount -= 1;
+            match Rc::try_unwrap(node) {
+                Ok(cell) => {
+                    return Ok(cell.into_inner().data);
+                },
+                Err(shared) => {
+                    return Ok(shared.borrow().data.clone());
+                }
+            }
+        }
+        pub fn rotate(&mut self, steps: isize) {
+            if self.amount == 0 {
+                return;
+            }
+            for _ in 0..steps.rem_euclid(self.amount as isize) {
+                let next = self.current.as_ref().and_then(|value| value.borrow().next.clone());
+                self.previous = self.current.take();
+                self.current = next;
+            }
+        }
+        pub fn current(&self) -> Result<T, Box<dyn core::error::Error>> {
+            match &self.current {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(value) => {
+                    return Ok(value.borrow().data.clone());
+                }
+            }
+        }
+        pub fn iter(&self) -> CircularIter<T> {
+            return CircularIter {
+                node: self.current.clone(),
+                remaining: self.amount,
+            };
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) {
+            println!("=====================list=begin=====================");
+            if self.amount == 0 {
+                println!("\n");
+            }
+            for value in self.iter() {
+                println!("{}", value);
+            }
+            println!("======================list=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+    }
+    impl<T> Drop for CircularList<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        fn drop(&mut self) {
+            if let Some(previous) = self.previous.take() {
+                previous.borrow_mut().next = None;
+            }
+            let mut current = self.current.take();
+            while let Some(node) = current {
+                current = node.borrow_mut().next.take();
+            }
+        }
+    }
+    pub struct CircularIter<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        node: Option<Rc<RefCell<Ring<T>>>>,
+        remaining: usize,
+    }
+    impl<T> Iterator for CircularIter<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        type Item = T;
+        fn next(&mut self) -> Option<T> {
+            if self.remaining == 0 {
+                return None;
+            }
+            let node = self.node.take()?;
+            self.node = node.borrow().next.clone();
+            self.remaining -= 1;
+            return Some(node.borrow().data.clone());
+        }
+    }
+    struct DoubleRing<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        data: T,
+        prev: Option<Weak<RefCell<DoubleRing<T>>>>,
+        next: Option<Rc<RefCell<DoubleRing<T>>>>,
+    }
+    pub struct DoublyCircularList<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        current: Option<Rc<RefCell<DoubleRing<T>>>>,
+        amount: usize,
+    }
+    impl<T> DoublyCircularList<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        pub fn new() -> Self {
+            return DoublyCircularList {
+                current: None,
+                amount: 0,
+            };
+        }
+        pub fn insert_after_current(&mut self, data: T) {
+            match self.current.clone() {
+                None => {
+                    self.insert_first(data);
+                },
+                Some(value) => {
+                    Self::link_after(&value, data);
+                    self.amount += 1;
+                }
+            }
+        }
+        pub fn insert_before_current(&mut self, data: T) {
+            match self.current.clone() {
+                None => {
+                    self.insert_first(data);
+                },
+                Some(value) => {
+                    let previous = value.borrow().prev.as_ref().unwrap().upgrade().unwrap();
+                    Self::link_after(&previous, data);
+                    self.amount += 1;
+                }
+            }
+        }
+        pub fn remove_current(&mut self) -> Result<T, Box<dyn core::error::Error>> {
+            let node = match self.current.take() {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(value) => {
+                    value
+                }
+            };
+            let next = node.borrow_mut().next.take().unwrap();
+            let previous = node.borrow_mut().prev.take().unwrap().upgrade().unwrap();
+            if self.amount > 1 {
+                next.borrow_mut().prev = Some(Rc::downgrade(&previous));
+                previous.borrow_mut().next = Some(Rc::clone(&next));
+                self.current = Some(next);
+            } else {
+                drop(next);
+                drop(previous);
+            }
+            self.amount -= 1;
+            match Rc::try_unwrap(node) {
+                Ok(cell) => {
+                    return Ok(cell.into_inner().data);
+                },
+                Err(shared) => {
+                    return Ok(shared.borrow().data.clone());
+                }
+            }
+        }
+        pub fn rotate(&mut self, steps: isize) {
+            if self.amount == 0 {
+                return;
+            }
+            let amount = self.amount as isize;
+            let forward = steps.rem_euclid(amount);
+            if forward <= amount / 2 {
+                for _ in 0..forward {
+                    let next = self.current.as_ref().and_then(|value| value.borrow().next.clone());
+                    self.current = next;
+                }
+            } else {
+                for _ in 0..amount - forward {
+                    let previous = self.current.as_ref().and_then(|value| value.borrow().prev.as_ref().and_then(|link| link.upgrade()));
+                    self.current = previous;
+                }
+            }
+        }
+        pub fn current(&self) -> Result<T, Box<dyn core::error::Error>> {
+            match &self.current {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(value) => {
+                    return Ok(value.borrow().data.clone());
+                }
+            }
+        }
+        pub fn iter(&self) -> DoublyCircularIter<T> {
+            return DoublyCircularIter {
+                node: self.current.clone(),
+                remaining: self.amount,
+            };
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) {
+            println!("=====================list=begin=====================");
+            if self.amount == 0 {
+                println!("\n");
+            }
+            for value in self.iter() {
+                println!("{}", value);
+            }
+            println!("======================list=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn insert_first(&mut self, data: T) {
+            let node = Rc::new(RefCell::new(DoubleRing {
+                data,
+                prev: None,
+                next: None,
+            }));
+            node.borrow_mut().prev = Some(Rc::downgrade(&node));
+            node.borrow_mut().next = Some(Rc::clone(&node));
+            self.current = Some(node);
+            self.amount += 1;
+        }
+        fn link_after(previous: &Rc<RefCell<DoubleRing<T>>>, data: T) {
+            let next = previous.borrow_mut().next.take().unwrap();
+            let node = Rc::new(RefCell::new(DoubleRing {
+                data,
+                prev: Some(Rc::downgrade(previous)),
+                next: Some(Rc::clone(&next)),
+            }));
+            next.borrow_mut().prev = Some(Rc::downgrade(&node));
+            previous.borrow_mut().next = Some(node);
+        }
+    }
+    impl<T> Drop for DoublyCircularList<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        fn drop(&mut self) {
+            let mut current = self.current.take();
+            if let Some(previous) = current.as_ref().and_then(|value| value.borrow().prev.as_ref().and_then(|link| link.upgrade())) {
+                previous.borrow_mut().next = None;
+            }
+            while let Some(node) = current {
+                current = node.borrow_mut().next.take();
+            }
+        }
+    }
+    pub struct DoublyCircularIter<T: Clone + core::fmt::Debug + core::fmt::Display> {
+        node: Option<Rc<RefCell<DoubleRing<T>>>>,
+        remaining: usize,
+    }
+    impl<T> Iterator for DoublyCircularIter<T> where T: Clone + core::fmt::Debug + core::fmt::Display {
+        type Item = T;
+        fn next(&mut self) -> Option<T> {
+            if self.remaining == 0 {
+                return None;
+            }
+            let node = self.node.take()?;
+            self.node = node.borrow().next.clone();
+            self.remaining -= 1;
+            return Some(node.borrow().data.clone());
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use core::{alloc::Layout, cell::Cell};
+        use crate::Rng;
+
+        #[derive(Clone)]
+        struct Counting {
+            live: Rc<Cell<usize>>,
+        }
+        impl PartialEq for Counting {
+            fn eq(&self, other: &Self) -> bool {
+                return Rc::ptr_eq(&self.live, &other.live);
+            }
+        }
+        unsafe impl Allocator for Counting {
+            fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+                self.live.set(self.live.get() + 1);
+                return Global.allocate(layout);
+            }
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.live.set(self.live.get() - 1);
+                unsafe { Global.deallocate(ptr, layout) };
+            }
+        }
+
+        #[test]
+        fn append_and_merge_copy_items_between_unequal_allocators() {
+            let (left, right) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(0)));
+            {
+                let mut singly = SinglyLinkedList::new_in(Counting { live: Rc::clone(&left) });
+                let mut doubly = DoublyLinkedList::new_in(Counting { live: Rc::clone(&left) });
+                let mut other_singly = SinglyLinkedList::new_in(Counting { live: Rc::clone(&right) });
+                let mut other_doubly = DoublyLinkedList::new_in(Counting { live: Rc::clone(&right) });
+                for value in 0..3 {
+                    singly.push_back(value * 2);
+                    doubly.push_back(value * 2);
+                    other_singly.push_back(value * 2 + 1);
+                    other_doubly.push_back(value * 2 + 1);
+                }
+                singly.merge_sorted(&mut other_singly);
+                doubly.append(&mut other_doubly);
+                assert_eq!((left.get(), right.get()), (12, 0));
+                assert_eq!(singly.refs().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+                assert_eq!(doubly.refs().copied().collect::<Vec<_>>(), [0, 2, 4, 1, 3, 5]);
+                assert_eq!(doubly.back(), Some(&5));
+                assert_eq!((*other_singly.size(), *other_doubly.size()), (0, 0));
+                // Equal allocators relink, so no node is allocated or freed.
+                let mut shared = DoublyLinkedList::new_in(Counting { live: Rc::clone(&left) });
+                shared.push_back(6);
+                doubly.merge_sorted(&mut shared);
+                let mut tail = doubly.split_off(3).unwrap();
+                tail.append(&mut doubly);
+                let mut extra = SinglyLinkedList::new_in(Counting { live: Rc::clone(&left) });
+                extra.push_back(7);
+                singly.append(&mut extra);
+                assert_eq!(left.get(), 14);
+                assert_eq!(singly.refs().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5, 7]);
+                assert_eq!(tail.refs().copied().collect::<Vec<_>>(), [1, 3, 5, 6, 0, 2, 4]);
+                assert_eq!((tail.front(), tail.back()), (Some(&1), Some(&4)));
+            }
+            assert_eq!((left.get(), right.get()), (0, 0));
+        }
+
+        #[test]
+        fn append_between_node_pools_returns_nodes_to_their_own_pool() {
+            let (first_pool, second_pool) = (crate::allocator::NodePool::new(), crate::allocator::NodePool::new());
+            let mut first = DoublyLinkedList::new_in(&first_pool);
+            let mut second = DoublyLinkedList::new_in(&second_pool);
+            for value in 0..4 {
+                first.push_back(value);
+                second.push_back(value + 4);
+            }
+            first.append(&mut second);
+            assert_eq!((first_pool.cached(), second_pool.cached()), (0, 4));
+            second.push_back(8);
+            assert_eq!(second_pool.cached(), 3);
+            drop(first);
+            assert_eq!(first_pool.cached(), 8);
+        }
+
+        #[test]
+        fn singly_nodes_go_through_the_allocator() {
+            let live = Rc::new(Cell::new(0));
+            {
+                let mut list = SinglyLinkedList::new_in(Counting { live: Rc::clone(&live) });
+                for value in 0..100 {
+                    list.push_back(value);
+                }
+                assert_eq!(live.get(), 100);
+                list.erase(10).unwrap();
+                list.pop_back().unwrap();
+                list.pop_begin().unwrap();
+                list.retain(|value| value % 3 != 0);
+                assert_eq!(live.get(), *list.size());
+                let odd = list.extract_if(|value| *value % 2 == 1);
+                let tail = list.split_off(*list.size() / 2).unwrap();
+                assert_eq!(live.get(), *list.size() + *odd.size() + *tail.size());
+            }
+            assert_eq!(live.get(), 0);
+        }
+
+        #[test]
+        fn doubly_nodes_go_through_the_allocator() {
+            let live = Rc::new(Cell::new(0));
+            {
+                let mut list = DoublyLinkedList::new_in(Counting { live: Rc::clone(&live) });
+                for value in (0..100).rev() {
+                    list.push_begin(value);
+                    list.insert(value, 1);
+                }
+                assert_eq!(live.get(), 200);
+                list.dedup();
+                list.erase(5).unwrap();
+                list.pop_back().unwrap();
+                assert_eq!(live.get(), *list.size());
+                let drained = list.drain(10..20).unwrap();
+                let mut other = list.splice(..5, [7, 8, 9]).unwrap();
+                other.append(&mut list.split_off(3).unwrap());
+                assert_eq!(live.get(), *list.size() + *drained.size() + *other.size());
+            }
+            assert_eq!(live.get(), 0);
+        }
+
+        trait Contents<T> {
+            fn contents(&self) -> Vec<T>;
+        }
+        impl<T, A> Contents<T> for SinglyLinkedList<T, A> where T: Clone + core::fmt::Display + core::fmt::Debug, A: Allocator {
+            fn contents(&self) -> Vec<T> {
+                return self.refs().cloned().collect();
+            }
+        }
+        impl<T, A> Contents<T> for DoublyLinkedList<T, A> where T: Clone + core::fmt::Debug + core::fmt::Display, A: Allocator {
+            fn contents(&self) -> Vec<T> {
+                return self.refs().cloned().collect();
+            }
+        }
+        fn filled(items: &[i32]) -> (SinglyLinkedList<i32>, DoublyLinkedList<i32>) {
+            let mut singly = SinglyLinkedList::new();
+            let mut doubly = DoublyLinkedList::new();
+            for item in items {
+                singly.push_back(*item);
+                doubly.push_back(*item);
+            }
+            return (singly, doubly);
+        }
+
+        #[test]
+        fn split_off_drain_and_splice_match_vec() {
+            for length in 0..7 {
+                let items: Vec<i32> = (0..length).collect();
+                for at in 0..=length as usize {
+                    let (mut singly, mut doubly) = filled(&items);
+                    let mut model = items.clone();
+                    let rest = model.split_off(at);
+                    assert_eq!(singly.split_off(at).unwrap().contents(), rest);
+                    assert_eq!(doubly.split_off(at).unwrap().contents(), rest);
+                    assert_eq!((singly.contents(), doubly.contents()), (model.clone(), model));
+                }
+                for start in 0..=length as usize {
+                    for end in start..=length as usize {
+                        let (mut singly, mut doubly) = filled(&items);
+                        let mut model = items.clone();
+                        let drained: Vec<i32> = model.drain(start..end).collect();
+                        assert_eq!(singly.drain(start..end).unwrap().contents(), drained);
+                        assert_eq!(doubly.drain(start..end).unwrap().contents(), drained);
+                        assert_eq!((singly.contents(), doubly.contents()), (model.clone(), model.clone()));
+                        let removed: Vec<i32> = model.splice(start.., [100, 101]).collect();
+                        assert_eq!(singly.splice(start.., [100, 101]).unwrap().contents(), removed);
+                        assert_eq!(doubly.splice(start.., [100, 101]).unwrap().contents(), removed);
+                        assert_eq!((singly.contents(), doubly.contents()), (model.clone(), model.clone()));
+                        assert_eq!((*singly.size(), *doubly.size()), (model.len(), model.len()));
+                        assert_eq!(doubly.back(), model.last());
+                    }
+                }
+                let (mut singly, mut doubly) = filled(&items);
+                assert_eq!(singly.drain(..).unwrap().contents(), items);
+                if length > 0 {
+                    assert_eq!(doubly.drain(..=length as usize - 1).unwrap().contents(), items);
+                }
+                assert_eq!((*singly.size(), *doubly.size()), (0, 0));
+            }
+        }
+
+        #[test]
+        fn range_edits_reject_bad_bounds_and_leave_the_list_alone() {
+            use core::ops::Bound::{Excluded, Included, Unbounded};
+            let items = [1, 2, 3, 4];
+            let (mut singly, mut doubly) = filled(&items);
+            assert!(singly.split_off(5).is_err());
+            assert!(doubly.split_off(5).is_err());
+            for range in [(Included(3), Excluded(1)), (Included(0), Excluded(5)), (Included(0), Included(4)), (Excluded(usize::MAX), Unbounded), (Unbounded, Included(usize::MAX)), (Included(5), Unbounded)] {
+                assert!(singly.drain(range).is_err());
+                assert!(doubly.drain(range).is_err());
+                assert!(singly.splice(range, [9]).is_err());
+                assert!(doubly.splice(range, [9]).is_err());
+            }
+            assert_eq!((singly.contents(), doubly.contents()), (items.to_vec(), items.to_vec()));
+            assert_eq!(singly.drain((Excluded(3), Unbounded)).unwrap().contents(), []);
+            assert_eq!(doubly.splice(4.., [5]).unwrap().contents(), []);
+            assert_eq!(doubly.contents(), [1, 2, 3, 4, 5]);
+        }
+
+        #[test]
+        fn retain_and_extract_if_match_vec() {
+            let mut rng = Rng(17);
+            for length in [0, 1, 2, 5, 40] {
+                let items: Vec<i32> = (0..length).map(|_| rng.next(10) as i32).collect();
+                for divisor in 1..4 {
+                    let (mut singly, mut doubly) = filled(&items);
+                    let mut model = items.clone();
+                    model.retain(|item| item % divisor == 0);
+                    singly.retain(|item| item % divisor == 0);
+                    doubly.retain_mut(|item| *item % divisor == 0);
+                    assert_eq!((singly.contents(), doubly.contents()), (model.clone(), model));
+                    let (mut singly, mut doubly) = filled(&items);
+                    let mut model = items.clone();
+                    let extracted: Vec<i32> = model.extract_if(.., |item| *item % divisor == 0).collect();
+                    assert_eq!(singly.extract_if(|item| *item % divisor == 0).contents(), extracted);
+                    assert_eq!(doubly.extract_if(|item| *item % divisor == 0).contents(), extracted);
+                    assert_eq!((singly.contents(), doubly.contents()), (model.clone(), model.clone()));
+                    assert_eq!(doubly.back(), model.last());
+                }
+            }
+        }
+
+        #[derive(Clone, Debug)]
+        struct Shared(Rc<()>);
+        impl core::fmt::Display for Shared {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                return write!(f, "shared");
+            }
+        }
+
+        #[test]
+        fn half_consumed_drain_and_extract_if_drop_every_item() {
+            let token = Rc::new(());
+            {
+                let mut singly = SinglyLinkedList::new();
+                let mut doubly = DoublyLinkedList::new();
+                for _ in 0..10 {
+                    singly.push_back(Shared(Rc::clone(&token)));
+                    doubly.push_back(Shared(Rc::clone(&token)));
+                }
+                let mut drained = singly.drain(2..8).unwrap();
+                drained.pop_begin().unwrap();
+                drained.pop_back().unwrap();
+                assert_eq!(Rc::strong_count(&token), 19);
+                drop(drained);
+                assert_eq!(Rc::strong_count(&token), 15);
+                let mut flag = false;
+                let mut extracted = doubly.extract_if(|_| {
+                    flag = !flag;
+                    return flag;
+                });
+                assert_eq!((*extracted.size(), *doubly.size()), (5, 5));
+                extracted.pop_begin().unwrap();
+                extracted.pop_begin().unwrap();
+                drop(extracted);
+                assert_eq!(Rc::strong_count(&token), 10);
+                assert!(Rc::ptr_eq(&doubly.front().unwrap().0, &token));
+                let spliced = doubly.splice(1..3, [Shared(Rc::clone(&token))]).unwrap();
+                drop(spliced);
+                assert_eq!(Rc::strong_count(&token), 9);
+            }
+            assert_eq!(Rc::strong_count(&token), 1);
+        }
+
+        #[test]
+        fn circular_lists_match_a_deque_model() {
+            let mut rng = Rng(37);
+            let mut singly = CircularList::new();
+            let mut doubly = DoublyCircularList::new();
+            // The front of the model is the current item; the ring continues towards the back.
+            let mut model: alloc::collections::VecDeque<u32> = alloc::collections::VecDeque::new();
+            for step in 0..5000 {
+                let value = step as u32;
+                match rng.next(7) {
+                    0 | 1 => {
+                        singly.insert_after_current(value);
+                        doubly.insert_after_current(value);
+                        model.insert(model.len().min(1), value);
+                    },
+                    2 => {
+                        // The singly list mirrors this by inserting after the last item and rotating back.
+                        singly.rotate(-1);
+                        singly.insert_after_current(value);
+                        singly.rotate(2);
+                        doubly.insert_before_current(value);
+                        model.push_back(value);
+                    },
+                    3 | 4 => {
+                        let removed = model.pop_front();
+                        assert_eq!(singly.remove_current().ok(), removed);
+                        assert_eq!(doubly.remove_current().ok(), removed);
+                    },
+                    _ => {
+                        let steps = [-7, -3, -1, 0, 1, 2, 5, 13, isize::MIN, isize::MAX][rng.next(10) as usize];
+                        singly.rotate(steps);
+                        doubly.rotate(steps);
+                        if !model.is_empty() {
+                            let length = model.len() as isize;
+                            model.rotate_left(steps.rem_euclid(length) as usize);
+                        }
+                    }
+                }
+                assert_eq!((*singly.size(), *doubly.size()), (model.len(), model.len()));
+                assert_eq!((singly.current().ok(), doubly.current().ok()), (model.front().copied(), model.front().copied()));
+                if step % 50 == 0 || model.len() < 3 {
+                    assert!(singly.iter().eq(model.iter().copied()));
+                    assert!(doubly.iter().eq(model.iter().copied()));
+                }
+            }
+        }
+
+        #[test]
+        fn circular_lists_drop_every_item() {
+            let token = Rc::new(());
+            for length in [0, 1, 2, 3, 10] {
+                let mut singly = CircularList::new();
+                let mut doubly = DoublyCircularList::new();
+                for index in 0..length {
+                    singly.insert_after_current(Shared(Rc::clone(&token)));
+                    if index % 2 == 0 {
+                        doubly.insert_after_current(Shared(Rc::clone(&token)));
+                    } else {
+                        doubly.insert_before_current(Shared(Rc::clone(&token)));
+                    }
+                }
+                singly.rotate(-1);
+                doubly.rotate(length as isize + 1);
+                assert_eq!(Rc::strong_count(&token), 1 + 2 * length);
+                drop(singly);
+                drop(doubly);
+                assert_eq!(Rc::strong_count(&token), 1);
+            }
+        }
+
+        #[test]
+        fn remove_current_down_to_empty_drops_every_item() {
+            let token = Rc::new(());
+            let mut singly = CircularList::new();
+            let mut doubly = DoublyCircularList::new();
+            for _ in 0..5 {
+                singly.insert_after_current(Shared(Rc::clone(&token)));
+                doubly.insert_before_current(Shared(Rc::clone(&token)));
+            }
+            for left in (0..5).rev() {
+                drop(singly.remove_current().unwrap());
+                drop(doubly.remove_current().unwrap());
+                assert_eq!(Rc::strong_count(&token), 1 + 2 * left);
+                singly.rotate(1);
+                doubly.rotate(-1);
+            }
+            assert!(singly.remove_current().is_err());
+            assert!(doubly.remove_current().is_err());
+            singly.insert_after_current(Shared(Rc::clone(&token)));
+            doubly.insert_after_current(Shared(Rc::clone(&token)));
+            assert!(Rc::ptr_eq(&singly.current().unwrap().0, &token));
+            drop((singly, doubly));
+            assert_eq!(Rc::strong_count(&token), 1);
+        }
+
+        #[test]
+        fn circular_iterators_may_outlive_their_list() {
+            let token = Rc::new(());
+            let mut singly = CircularList::new();
+            let mut doubly = DoublyCircularList::new();
+            for _ in 0..4 {
+                singly.insert_after_current(Shared(Rc::clone(&token)));
+                doubly.insert_after_current(Shared(Rc::clone(&token)));
+            }
+            let mut singly_iter = singly.iter();
+            let mut doubly_iter = doubly.iter();
+            drop(singly_iter.next());
+            drop(doubly_iter.next());
+            // Removing the node an iterator stands on must still hand back the item.
+            drop(singly.remove_current().unwrap());
+            drop(doubly.remove_current().unwrap());
+            drop((singly, doubly));
+            assert!(singly_iter.count() <= 3);
+            assert!(doubly_iter.count() <= 3);
+            assert_eq!(Rc::strong_count(&token), 1);
+        }
+
+        #[test]
+        fn binary_search_matches_slices() {
+            for length in 0..40 {
+                // Every value appears up to three times, with gaps between values.
+                let items: Vec<i32> = (0..length).map(|index| index / 3 * 2).collect();
+                let mut singly = SinglyLinkedList::new();
+                let mut doubly = DoublyLinkedList::new();
+                for item in &items {
+                    singly.push_back(*item);
+                    doubly.push_back(*item);
+                }
+                for value in -1..=length / 3 * 2 + 2 {
+                    let expected = items.binary_search(&value);
+                    for found in [singly.binary_search(&value), doubly.binary_search(&value), singly.binary_search_by_key(&(value * 10), |item| item * 10), doubly.binary_search_by(|item| item.cmp(&value))] {
+                        match (found, expected) {
+                            (Ok(index), Ok(_)) => {
+                                assert_eq!(items[index], value);
+                            },
+                            (found, expected) => {
+                                assert_eq!(found, expected);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn searches_match_iterator_adapters() {
+            let items = [4, 8, 15, 16, 23, 42, 8, 15];
+            let mut singly = SinglyLinkedList::new();
+            let mut doubly = DoublyLinkedList::new();
+            for item in items {
+                singly.push_back(item);
+                doubly.push_back(item);
+            }
+            for target in [0, 4, 8, 15, 42, 99] {
+                assert_eq!(singly.contains(&target), items.contains(&target));
+                assert_eq!(doubly.contains(&target), items.contains(&target));
+                assert_eq!(singly.position(|item| *item == target), items.iter().position(|item| *item == target));
+                assert_eq!(doubly.position(|item| *item == target), items.iter().position(|item| *item == target));
+                assert_eq!(doubly.rposition(|item| *item == target), items.iter().rposition(|item| *item == target));
+                assert_eq!(singly.count_matching(|item| *item == target), items.iter().filter(|item| **item == target).count());
+                assert_eq!(doubly.count_matching(|item| *item >= target), items.iter().filter(|item| **item >= target).count());
+            }
+            assert_eq!(singly.find(|item| item % 2 == 1), Some(&15));
+            assert_eq!(doubly.find(|item| *item > 100), None);
+            assert_eq!(singly.find_map(|item| (*item > 20).then_some(item * 2)), Some(46));
+            assert_eq!(doubly.find_map(|item| (*item > 100).then_some(*item)), None);
+            assert_eq!((singly.front(), singly.back()), (Some(&4), Some(&15)));
+            assert_eq!((doubly.front(), doubly.back()), (Some(&4), Some(&15)));
+            let empty: DoublyLinkedList<i32> = DoublyLinkedList::new();
+            assert_eq!((empty.front(), empty.back(), empty.rposition(|_| true)), (None, None, None));
+            assert_eq!(empty.binary_search(&1), Err(0));
+        }
+
+        #[test]
+        fn singly_edits_at_every_position_keep_the_count() {
+            let mut list = SinglyLinkedList::new();
+            for value in 0..6 {
+                list.push_back(value);
+                list.insert(value + 10, value);
+            }
+            list.insert(99, 100);
+            assert_eq!(*list.size(), 13);
+            assert_eq!(list.refs().cloned().collect::<Vec<_>>(), [10, 11, 12, 13, 14, 15, 0, 1, 2, 3, 4, 5, 99]);
+            list.erase(12).unwrap();
+            list.erase(6).unwrap();
+            list.pop_back().unwrap();
+            assert_eq!(*list.size(), 10);
+            assert_eq!(list.back(), Some(&4));
+            while list.pop_back().is_ok() {}
+            assert_eq!(*list.size(), 0);
+            assert!(list.erase(0).is_err());
+            list.push_back(7);
+            assert_eq!(list.get(0).unwrap(), 7);
+        }
+
+        #[test]
+        fn doubly_erase_of_the_tail_moves_the_tail_back() {
+            let mut list = DoublyLinkedList::new();
+            for value in 0..5 {
+                list.push_back(value);
+            }
+            list.insert(9, 2);
+            list.erase(5).unwrap();
+            assert_eq!(list.back(), Some(&3));
+            list.push_back(8);
+            assert_eq!(list.refs().cloned().collect::<Vec<_>>(), [0, 1, 9, 2, 3, 8]);
+            list.erase(0).unwrap();
+            list.erase(4).unwrap();
+            list.pop_back().unwrap();
+            assert_eq!(list.refs().cloned().collect::<Vec<_>>(), [1, 9, 2]);
+            assert_eq!(list.rposition(|value| *value == 1), Some(0));
+        }
+
+        #[test]
+        fn dropping_long_lists_does_not_recurse() {
+            let mut singly = SinglyLinkedList::new();
+            let mut doubly = DoublyLinkedList::new();
+            for value in 0..1_000_000u32 {
+                singly.push_begin(value);
+                doubly.push_back(value);
+            }
+            drop(singly);
+            drop(doubly);
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Keyed(u64, usize);
+        impl core::fmt::Display for Keyed {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                return write!(f, "{}#{}", self.0, self.1);
+            }
+        }
+        fn keyed(length: usize, keys: u64, seed: u64) -> Vec<Keyed> {
+            let mut rng = Rng(seed);
+            return (0..length).map(|index| Keyed(rng.next(keys), index)).collect();
+        }
+
+        #[test]
+        fn sort_by_key_is_stable() {
+            for length in [0, 1, 2, 3, 10, 257, 1000] {
+                let items = keyed(length, 10, 5 + length as u64);
+                let mut singly = SinglyLinkedList::new();
+                let mut doubly = DoublyLinkedList::new();
+                for item in &items {
+                    singly.push_begin(item.clone());
+                    doubly.push_back(item.clone());
+                }
+                singly.reverse();
+                let mut expected = items.clone();
+                expected.sort_by_key(|item| item.0);
+                singly.sort_by_key(|item| item.0);
+                doubly.sort_by_key(|item| item.0);
+                assert_eq!(singly.refs().cloned().collect::<Vec<_>>(), expected);
+                assert_eq!(doubly.refs().cloned().collect::<Vec<_>>(), expected);
+                assert_eq!(doubly.back(), expected.last());
+                assert!(singly.is_sorted() && doubly.is_sorted());
+            }
+        }
+
+        #[test]
+        fn large_inputs_sort_dedup_and_reverse() {
+            let items: Vec<u64> = keyed(200_000, 1000, 8).into_iter().map(|item| item.0).collect();
+            let mut singly = SinglyLinkedList::new();
+            let mut doubly = DoublyLinkedList::new();
+            for item in &items {
+                singly.push_begin(*item);
+                doubly.push_begin(*item);
+            }
+            let mut expected = items.clone();
+            expected.sort();
+            singly.sort();
+            doubly.sort();
+            assert!(singly.is_sorted() && doubly.is_sorted());
+            assert_eq!(doubly.refs().cloned().collect::<Vec<_>>(), expected);
+            expected.dedup();
+            singly.dedup();
+            doubly.dedup();
+            assert_eq!(singly.refs().cloned().collect::<Vec<_>>(), expected);
+            assert_eq!(*doubly.size(), expected.len());
+            expected.reverse();
+            singly.reverse();
+            doubly.reverse();
+            assert_eq!(singly.refs().cloned().collect::<Vec<_>>(), expected);
+            assert_eq!(doubly.refs().cloned().collect::<Vec<_>>(), expected);
+            assert_eq!(doubly.rposition(|value| *value == 999), Some(0));
+            doubly.push_back(1000);
+            assert_eq!(doubly.back(), Some(&1000));
+        }
+
+        #[test]
+        fn merge_sorted_keeps_ties_from_the_receiver_first() {
+            let mut items = keyed(300, 20, 11);
+            items.sort_by_key(|item| item.0);
+            let (left, right): (Vec<_>, Vec<_>) = items.iter().cloned().partition(|item| item.1 % 3 == 0);
+            let mut first = DoublyLinkedList::new();
+            let mut second = DoublyLinkedList::new();
+            let mut singly = SinglyLinkedList::new();
+            let mut other = SinglyLinkedList::new();
+            for item in &left {
+                first.push_back(Keyed(item.0, 0));
+                singly.push_back(Keyed(item.0, 0));
+            }
+            for item in &right {
+                second.push_back(Keyed(item.0, 1));
+                other.push_back(Keyed(item.0, 1));
+            }
+            let mut expected: Vec<_> = left.iter().map(|item| Keyed(item.0, 0)).chain(right.iter().map(|item| Keyed(item.0, 1))).collect();
+            expected.sort_by_key(|item| item.0);
+            first.merge_sorted(&mut second);
+            singly.merge_sorted(&mut other);
+            assert_eq!(first.refs().cloned().collect::<Vec<_>>(), expected);
+            assert_eq!(singly.refs().cloned().collect::<Vec<_>>(), expected);
+            assert_eq!((*second.size(), *other.size()), (0, 0));
+            assert_eq!(first.back(), expected.last());
+            second.push_back(Keyed(0, 2));
+            assert_eq!(second.front(), Some(&Keyed(0, 2)));
+        }
+
+        #[test]
+        fn dedup_keeps_the_first_of_each_run() {
+            let mut list = DoublyLinkedList::new();
+            for item in [Keyed(1, 0), Keyed(1, 1), Keyed(2, 2), Keyed(2, 3), Keyed(2, 4), Keyed(1, 5)] {
+                list.push_back(item);
+            }
+            list.dedup_by_key(|item| item.0);
+            assert_eq!(list.refs().cloned().collect::<Vec<_>>(), [Keyed(1, 0), Keyed(2, 2), Keyed(1, 5)]);
+            let mut singly = SinglyLinkedList::new();
+            for value in [0, 1, 2, 3, 4, 5, 9] {
+                singly.push_back(value);
+            }
+            singly.dedup_by_key(|value| value / 3);
+            assert_eq!(singly.refs().cloned().collect::<Vec<_>>(), [0, 3, 9]);
+            let mut repeated = DoublyLinkedList::new();
+            for _ in 0..3 {
+                repeated.push_back(1);
+            }
+            repeated.dedup();
+            repeated.push_back(2);
+            assert_eq!(repeated.refs().cloned().collect::<Vec<_>>(), [1, 2]);
+        }
+
+        #[test]
+        fn self_organizing_remove_counts_its_scan() {
+            let mut list = SelfOrganizingList::new(Policy::MoveToFront);
+            for value in 0..10 {
+                list.push(value);
+            }
+            assert_eq!(list.remove(|value| *value == 4), Some(4));
+            assert_eq!(list.remove(|value| *value == 40), None);
+            assert_eq!(*list.stats(), AccessStats {
+                lookups: 2,
+                hits: 1,
+                misses: 1,
+                comparisons: 5 + 9,
+                moves: 0,
+            });
+            assert_eq!(list.find(|value| *value == 9), Some(&9));
+            assert_eq!(list.stats().comparisons, 14 + 9);
+            assert_eq!(list.stats().average_cost(), 23.0 / 3.0);
+        }
+
+        fn nodes<T>(list: &UnrolledList<T>) -> Vec<usize> where T: Clone + core::fmt::Debug + core::fmt::Display {
+            let mut lengths = Vec::new();
+            let mut current = list.head.clone();
+            while let Some(node) = current {
+                lengths.push(node.borrow().items.len());
+                current = node.borrow().next.clone();
+            }
+            return lengths;
+        }
+
+        #[test]
+        fn unrolled_matches_a_vec_and_keeps_nodes_half_full() {
+            let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+            let mut next = move |bound: usize| rng.next(bound as u64) as usize;
+            for capacity in [2, 3, 4, 5, 8, 16] {
+                let mut list = UnrolledList::with_node_capacity(capacity);
+                let mut model: Vec<usize> = Vec::new();
+                for step in 0..6000 {
+                    let value = next(1000);
+                    match next(10) {
+                        0..=2 => {
+                            list.push_back(value);
+                            model.push(value);
+                        },
+                        3 => {
+                            list.push_begin(value);
+                            model.insert(0, value);
+                        },
+                        4 | 5 => {
+                            let index = next(model.len() + 2);
+                            list.insert(value, index);
+                            model.insert(index.min(model.len()), value);
+                        },
+                        _ => {
+                            let index = next(model.len() + 1);
+                            match list.erase(index) {
+                                Ok(()) => {
+                                    model.remove(index);
+                                },
+                                Err(_) => {
+                                    assert_eq!(index, model.len());
+                                }
+                            }
+                        }
+                    }
+                    let lengths = nodes(&list);
+                    assert_eq!(lengths.iter().sum::<usize>(), model.len());
+                    if lengths.len() > 1 {
+                        assert!(lengths.iter().all(|length| *length >= capacity / 2 && *length <= capacity), "{:?}", lengths);
+                    }
+                    if step % 500 == 0 {
+                        assert_eq!(list.iter().collect::<Vec<_>>(), model);
+                    }
+                }
+                assert_eq!(list.iter().collect::<Vec<_>>(), model);
+            }
+        }
+
+        #[test]
+        fn unrolled_erase_at_the_back_merges_the_tail() {
+            let mut list = UnrolledList::with_node_capacity(4);
+            for value in 0..12 {
+                list.push_back(value);
+            }
+            while *list.size() > 2 {
+                list.erase(*list.size() - 1).unwrap();
+                let lengths = nodes(&list);
+                assert!(lengths.len() == 1 || lengths.iter().all(|length| *length >= 2), "{:?}", lengths);
+            }
+            assert_eq!(nodes(&list), [2]);
+            list.push_back(2);
+            assert_eq!(list.iter().collect::<Vec<_>>(), [0, 1, 2]);
+        }
+
+        #[test]
+        fn dropping_a_long_unrolled_list_does_not_recurse() {
+            let mut list = UnrolledList::new();
+            for value in 0..4_000_000u32 {
+                list.push_back(value);
+            }
+            drop(list);
+            let mut list = UnrolledList::with_node_capacity(2);
+            for value in 0..100 {
+                list.push_back(value);
+            }
+            let iter = list.iter().skip(90);
+            drop(list);
+            assert_eq!(iter.collect::<Vec<_>>(), (90..100).collect::<Vec<_>>());
+        }
+    }
+}
+pub mod timer {
+    use alloc::{boxed::Box, rc::{Rc, Weak}, vec::Vec};
+    use core::ptr::NonNull;
+    use crate::allocator::{Global, allocate_node, deallocate_node};
+
+    const SLOT_BITS: usize = 6;
+    const SLOTS: usize = 1 << SLOT_BITS;
+    const LEVELS: usize = 6;
+    const MAX_SPAN: u64 = 1 << (SLOT_BITS * LEVELS);
+    static NEXT_WHEEL: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+
+    struct Entry<T> {
+        item: T,
+        deadline: u64,
+        level: usize,
+        slot: usize,
+        // Handles hold a `Weak` to this token, so they can tell whether the node is still queued.
+        alive: Rc<()>,
+    }
+    struct Timer<T> {
+        data: Entry<T>,
+        prev: Option<NonNull<Timer<T>>>,
+        next: Option<NonNull<Timer<T>>>,
+    }
+    // Intrusive list of the timers in one slot. Cascading moves nodes between slots without
+    // reallocating them, so handles stay valid.
+    struct Slot<T> {
+        head: Option<NonNull<Timer<T>>>,
+        tail: Option<NonNull<Timer<T>>>,
+        amount: usize,
+    }
+    impl<T> Slot<T> {
+        fn new() -> Self {
+            return Slot {
+                head: None,
+                tail: None,
+                amount: 0,
+            };
+        }
+        // `node` must be unlinked; the slot takes ownership of it.
+        fn push_back(&mut self, mut node: NonNull<Timer<T>>) {
+            unsafe {
+                let node_mut = node.as_mut();
+                node_mut.prev = self.tail;
+                node_mut.next = None;
+            }
+            match self.tail {
+                None => {
+                    self.head = Some(node);
+                },
+                Some(mut last) => {
+                    unsafe { last.as_mut() }.next = Some(node);
+                }
+            }
+            self.tail = Some(node);
+            self.amount += 1;
+        }
+        fn pop_begin(&mut self) -> Option<NonNull<Timer<T>>> {
+            let node = self.head?;
+            unsafe { self.unlink(node) };
+            return Some(node);
+        }
+        // Safety: `node` must be linked into this slot. It stays allocated; the caller now owns it.
+        unsafe fn unlink(&mut self, mut node: NonNull<Timer<T>>) {
+            let node_mut = unsafe { node.as_mut() };
+            let prev = node_mut.prev.take();
+            let next = node_mut.next.take();
+            match prev {
+                None => {
+                    self.head = next;
+                },
+                Some(mut value) => {
+                    unsafe { value.as_mut() }.next = next;
+                }
+            }
+            match next {
+                None => {
+                    self.tail = prev;
+                },
+                Some(mut value) => {
+                    unsafe { value.as_mut() }.prev = prev;
+                }
+            }
+            self.amount -= 1;
+        }
+    }
+    impl<T> Drop for Slot<T> {
+        fn drop(&mut self) {
+            while let Some(node) = self.pop_begin() {
+                drop(unsafe { deallocate_node(&Global, node) });
+            }
+        }
+    }
+
+    pub struct TimerHandle<T> {
+        node: NonNull<Timer<T>>,
+        alive: Weak<()>,
+        wheel: usize,
+    }
+    impl<T> Clone for TimerHandle<T> {
+        fn clone(&self) -> Self {
+            return TimerHandle {
+                node: self.node,
+                alive: Weak::clone(&self.alive),
+                wheel: self.wheel,
+            };
+        }
+    }
+
+    pub struct TimingWheel<T> {
+        id: usize,
+        slots: Vec<Slot<T>>,
+        occupied: [u64; LEVELS],
+        overflow: Slot<T>,
+        elapsed: u64,
+        amount: usize,
+    }
+    impl<T> TimingWheel<T> {
+        pub fn new() -> Self {
+            return TimingWheel {
+                id: NEXT_WHEEL.fetch_add(1, core::sync::atomic::Ordering::Relaxed),
+                slots: (0..LEVELS * SLOTS).map(|_| Slot::new()).collect(),
+                occupied: [0; LEVELS],
+                overflow: Slot::new(),
+                elapsed: 0,
+                amount: 0,
+            };
+        }
+        pub fn schedule(&mut self, delay: u64, item: T) -> TimerHandle<T> {
+            let alive = Rc::new(());
+            let token = Rc::downgrade(&alive);
+            let node = allocate_node(&Global, Timer {
+                data: Entry {
+                    item,
+                    deadline: self.elapsed.saturating_add(delay),
+                    level: 0,
+                    slot: 0,
+                    alive,
+                },
+                prev: None,
+                next: None,
+            });
+            let handle = TimerHandle {
+                node,
+                alive: token,
+                wheel: self.id,
+            };
+            self.insert_node(node);
+            self.amount += 1;
+            return handle;
+        }
+        pub fn cancel(&mut self, handle: &TimerHandle<T>) -> Result<T, Box<dyn core::error::Error>> {
+            // A handle from another wheel points into that wheel's slots, not ours.
+            if handle.wheel != self.id {
+                return Err(Box::new(core::fmt::Error));
+            }
+            match handle.alive.upgrade() {
+                None => {
+                    return Err(Box::new(core::fmt::Error));
+                },
+                Some(token) => {
+                    let node = handle.node;
+                    let (level, slot) = {
+                        let entry = &unsafe { node.as_ref() }.data;
+                        debug_assert!(Rc::ptr_eq(&token, &entry.alive));
+                        (entry.level, entry.slot)
+                    };
+                    // The token is only alive while the node is linked into one of our lists.
+                    if level == LEVELS {
+                        unsafe { self.overflow.unlink(node) };
+                    } else {
+                        let list = &mut self.slots[level * SLOTS + slot];
+                        unsafe { list.unlink(node) };
+                        if list.amount == 0 {
+                            self.occupied[level] &= !(1 << slot);
+                        }
+                    }
+                    self.amount -= 1;
+                    return Ok(Self::into_item(node));
+                }
+            }
+        }
+        pub fn advance(&mut self, now: u64) -> Vec<T> {
+            let mut expired = Vec::new();
+            loop {
+                match self.next_expiration() {
+                    Some((level, slot, deadline)) if deadline <= now => {
+                        self.elapsed = deadline;
+                        self.process_slot(level, slot, &mut expired);
+                    },
+                    _ => {
+                        // Jump straight to the epoch of the earliest overflow timer, or of `now` if
+                        // that comes first, so the overflow list is re-inserted once per jump.
+                        if let Some(earliest) = self.overflow_deadline() {
+                            let epoch = earliest.min(now) & !(MAX_SPAN - 1);
+                            if epoch > self.elapsed {
+                                self.elapsed = epoch;
+                                let mut overflow = core::mem::replace(&mut self.overflow, Slot::new());
+                                while let Some(node) = overflow.pop_begin() {
+                                    self.insert_node(node);
+                                }
+                                continue;
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+            if now > self.elapsed {
+                self.elapsed = now;
+            }
+            self.amount -= expired.len();
+            return expired;
+        }
+        pub fn now(&self) -> u64 {
+            return self.elapsed;
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn insert_node(&mut self, mut node: NonNull<Timer<T>>) {
+            let deadline = unsafe { node.as_ref() }.data.deadline;
+            let masked = (self.elapsed ^ deadline) | (SLOTS as u64 - 1);
+            if masked >= MAX_SPAN {
+                unsafe { node.as_mut() }.data.level = LEVELS;
+                self.overflow.push_back(node);
+                return;
+            }
+            let level = (63 - masked.leading_zeros() as usize) / SLOT_BITS;
+            let slot = ((deadline >> (level * SLOT_BITS)) as usize) & (SLOTS - 1);
+            {
+                let entry = &mut unsafe { node.as_mut() }.data;
+                entry.level = level;
+                entry.slot = slot;
+            }
+            self.slots[level * SLOTS + slot].push_back(node);
+            self.occupied[level] |= 1 << slot;
+        }
+        fn overflow_deadline(&self) -> Option<u64> {
+            let mut earliest = None;
+            let mut current = self.overflow.head;
+            while let Some(node) = current {
+                let node = unsafe { node.as_ref() };
+                earliest = Some(earliest.map_or(node.data.deadline, |value: u64| value.min(node.data.deadline)));
+                current = node.next;
+            }
+            return earliest;
+        }
+        fn next_expiration(&self) -> Option<(usize, usize, u64)> {
+            for level in 0..LEVELS {
+                let occupied = self.occupied[level];
+                if occupied == 0 {
+                    continue;
+                }
+                let shift = level * SLOT_BITS;
+                let now_slot = ((self.elapsed >> shift) as usize) & (SLOTS - 1);
+                let offset = occupied.rotate_right(now_slot as u32).trailing_zeros() as usize;
+                let slot = (now_slot + offset) & (SLOTS - 1);
+                let slot_range = 1u64 << shift;
+                let level_range = slot_range << SLOT_BITS;
+                let level_start = self.elapsed & !(level_range - 1);
+                let mut deadline = level_start + slot as u64 * slot_range;
+                if deadline < self.elapsed {
+                    deadline += level_range;
+                }
+                return Some((level, slot, deadline));
+            }
+            return None;
+        }
+        fn process_slot(&mut self, level: usize, slot: usize, expired: &mut Vec<T>) {
+            self.occupied[level] &= !(1 << slot);
+            let mut list = core::mem::replace(&mut self.slots[level * SLOTS + slot], Slot::new());
+            while let Some(node) = list.pop_begin() {
+                if unsafe { node.as_ref() }.data.deadline <= self.elapsed {
+                    expired.push(Self::into_item(node));
+                } else {
+                    self.insert_node(node);
+                }
+            }
+        }
+        // Frees an unlinked node; dropping its token invalidates every handle to it.
+        fn into_item(node: NonNull<Timer<T>>) -> T {
+            return unsafe { deallocate_node(&Global, node) }.data.item;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::Rng;
+
+        #[test]
+        fn zero_delay_fires_on_the_current_tick() {
+            let mut wheel = TimingWheel::new();
+            wheel.advance(10);
+            wheel.schedule(0, 'a');
+            assert_eq!(*wheel.size(), 1);
+            assert_eq!(wheel.advance(10), ['a']);
+            assert_eq!(*wheel.size(), 0);
+        }
+
+        #[test]
+        fn timers_cascade_down_to_their_exact_deadline() {
+            let delays = [1, 63, 64, 65, 4095, 4096, 4097, 262_143, 262_144, 1 << 30, MAX_SPAN - 1];
+            let mut wheel = TimingWheel::new();
+            wheel.advance(5);
+            for delay in delays {
+                wheel.schedule(delay, delay);
+            }
+            for (fired, delay) in delays.into_iter().enumerate() {
+                assert_eq!(*wheel.size(), delays.len() - fired);
+                assert!(wheel.advance(5 + delay - 1).is_empty());
+                assert_eq!(wheel.advance(5 + delay), [delay]);
+            }
+            assert_eq!(*wheel.size(), 0);
+        }
+
+        #[test]
+        fn timers_beyond_the_span_wait_in_overflow() {
+            let mut wheel = TimingWheel::new();
+            wheel.advance(3);
+            for delay in [MAX_SPAN, MAX_SPAN + 7, 3 * MAX_SPAN + 1] {
+                wheel.schedule(delay, delay);
+            }
+            assert!(wheel.advance(MAX_SPAN + 2).is_empty());
+            assert_eq!(wheel.advance(MAX_SPAN + 3), [MAX_SPAN]);
+            assert!(wheel.advance(MAX_SPAN + 9).is_empty());
+            assert_eq!(wheel.advance(MAX_SPAN + 10), [MAX_SPAN + 7]);
+            assert_eq!(*wheel.size(), 1);
+            assert!(wheel.advance(3 * MAX_SPAN + 3).is_empty());
+            assert_eq!(wheel.advance(u64::MAX), [3 * MAX_SPAN + 1]);
+            assert_eq!(wheel.now(), u64::MAX);
+        }
+
+        #[test]
+        fn cancel_only_succeeds_while_the_timer_is_pending() {
+            let mut wheel = TimingWheel::new();
+            let fired = wheel.schedule(5, 1);
+            let cancelled = wheel.schedule(50, 2);
+            let pending = wheel.schedule(500, 3);
+            assert_eq!(wheel.cancel(&cancelled).unwrap(), 2);
+            assert!(wheel.cancel(&cancelled).is_err());
+            assert_eq!(*wheel.size(), 2);
+            assert_eq!(wheel.advance(100), [1]);
+            assert!(wheel.cancel(&fired).is_err());
+            assert!(wheel.cancel(&fired.clone()).is_err());
+            assert_eq!(*wheel.size(), 1);
+            assert_eq!(wheel.cancel(&pending.clone()).unwrap(), 3);
+            assert_eq!(*wheel.size(), 0);
+            assert!(wheel.advance(1000).is_empty());
+        }
+
+        #[test]
+        fn cancel_rejects_handles_from_another_wheel() {
+            let mut first = TimingWheel::new();
+            let mut second = TimingWheel::new();
+            let handle = first.schedule(10, 'a');
+            second.schedule(10, 'b');
+            assert!(second.cancel(&handle).is_err());
+            assert_eq!(*second.size(), 1);
+            assert_eq!(second.advance(10), ['b']);
+            assert_eq!(first.cancel(&handle).unwrap(), 'a');
+        }
+
+        #[test]
+        fn far_overflow_timers_are_reached_in_one_jump() {
+            let mut wheel = TimingWheel::new();
+            wheel.schedule(u64::MAX, 'z');
+            wheel.schedule(3 * MAX_SPAN + 7, 'c');
+            wheel.schedule(MAX_SPAN + 1, 'a');
+            wheel.schedule(1 << 60, 'y');
+            assert_eq!(wheel.advance(MAX_SPAN), []);
+            assert_eq!(wheel.advance(3 * MAX_SPAN + 6), ['a']);
+            assert_eq!(wheel.advance(u64::MAX - 1), ['c', 'y']);
+            assert_eq!(wheel.now(), u64::MAX - 1);
+            assert_eq!(wheel.advance(u64::MAX), ['z']);
+            assert_eq!(*wheel.size(), 0);
+        }
+
+        #[test]
+        fn items_need_not_be_clone_and_are_dropped_with_the_wheel() {
+            struct Task(Rc<()>);
+            let shared = Rc::new(());
+            let mut wheel = TimingWheel::new();
+            let first = wheel.schedule(5, Task(Rc::clone(&shared)));
+            wheel.schedule(100, Task(Rc::clone(&shared)));
+            wheel.schedule(1 << 40, Task(Rc::clone(&shared)));
+            assert_eq!(Rc::strong_count(&shared), 4);
+            let task = wheel.cancel(&first).unwrap();
+            assert!(Rc::ptr_eq(&task.0, &shared));
+            drop(task);
+            assert_eq!(wheel.advance(100).len(), 1);
+            assert_eq!(Rc::strong_count(&shared), 2);
+            drop(wheel);
+            assert_eq!(Rc::strong_count(&shared), 1);
+        }
+
+        #[test]
+        fn random_schedule_matches_a_sorted_model() {
+            let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+            let mut wheel = TimingWheel::new();
+            let mut model: Vec<(u64, usize, bool)> = Vec::new();
+            let mut handles = Vec::new();
+            let mut now = 0u64;
+            for round in 0..500 {
+                for _ in 0..4 {
+                    let scale = [10u64, 1000, 100_000, 1 << 30, 1 << 40][rng.next(5) as usize];
+                    let delay = rng.next(scale);
+                    handles.push(wheel.schedule(delay, model.len()));
+                    model.push((now + delay, model.len(), false));
+                }
+                if round % 3 == 0 {
+                    let index = rng.next(model.len() as u64) as usize;
+                    match wheel.cancel(&handles[index]) {
+                        Ok(id) => {
+                            assert!(!model[index].2);
+                            assert_eq!(id, index);
+                            model[index].2 = true;
+                        },
+                        Err(_) => {
+                            assert!(model[index].2);
+                        }
+                    }
+                }
+                let step = [1u64, 50, 5000, 1 << 20, 1 << 37][rng.next(5) as usize];
+                now += rng.next(step);
+                let mut fired = wheel.advance(now);
+                fired.sort();
+                let expected: Vec<usize> = model.iter().filter(|entry| !entry.2 && entry.0 <= now).map(|entry| entry.1).collect();
+                assert_eq!(fired, expected);
+                for entry in model.iter_mut().filter(|entry| entry.0 <= now) {
+                    entry.2 = true;
+                }
+                assert_eq!(*wheel.size(), model.iter().filter(|entry| !entry.2).count());
+            }
+        }
+    }
+}
+#[cfg(feature = "std")]
+pub mod deque {
+    use std::{cell::{Cell, UnsafeCell}, marker::PhantomData, mem::MaybeUninit, ptr, sync::{Arc, Mutex, atomic::{self, AtomicIsize, AtomicPtr, Ordering}}};
+
+    const MIN_CAPACITY: usize = 16;
+
+    struct Buffer<T> {
+        slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+        mask: usize,
+    }
+    impl<T> Buffer<T> {
+        fn alloc(capacity: usize) -> *mut Buffer<T> {
+            let slots = (0..capacity).map(|_| UnsafeCell::new(MaybeUninit::uninit())).collect();
+            return Box::into_raw(Box::new(Buffer {
+                slots,
+                mask: capacity - 1,
+            }));
+        }
+        fn capacity(&self) -> usize {
+            return self.mask + 1;
+        }
+        unsafe fn read(&self, index: isize) -> MaybeUninit<T> {
+            let slot = self.slots[index as usize & self.mask].get();
+            return unsafe { ptr::read_volatile(slot) };
+        }
+        unsafe fn write(&self, index: isize, data: T) {
+            let slot = self.slots[index as usize & self.mask].get();
+            unsafe { ptr::write_volatile(slot, MaybeUninit::new(data)) };
+        }
+    }
+
+    pub enum Steal<T> {
+        Empty,
+        Success(T),
+        Retry,
+    }
+
+    pub struct WorkStealing<T> {
+        top: AtomicIsize,
+        bottom: AtomicIsize,
+        buffer: AtomicPtr<Buffer<T>>,
+        retired: Mutex<Vec<*mut Buffer<T>>>,
+    }
+    unsafe impl<T: Send> Send for WorkStealing<T> {}
+    unsafe impl<T: Send> Sync for WorkStealing<T> {}
+    impl<T> WorkStealing<T> {
+        pub fn split() -> (Worker<T>, Stealer<T>) {
+            return WorkStealing::split_with_capacity(MIN_CAPACITY);
+        }
+        pub fn split_with_capacity(capacity: usize) -> (Worker<T>, Stealer<T>) {
+            let deque = Arc::new(WorkStealing {
+                top: AtomicIsize::new(0),
+                bottom: AtomicIsize::new(0),
+                buffer: AtomicPtr::new(Buffer::alloc(capacity.max(MIN_CAPACITY).next_power_of_two())),
+                retired: Mutex::new(Vec::new()),
+            });
+            let stealer = Stealer {
+                deque: Arc::clone(&deque),
+            };
+            let worker = Worker {
+                deque,
+                owner: PhantomData,
+            };
+            return (worker, stealer);
+        }
+        fn size(&self) -> usize {
+            let bottom = self.bottom.load(Ordering::Relaxed);
+            let top = self.top.load(Ordering::Relaxed);
+            return (bottom - top).max(0) as usize;
+        }
+    }
+    impl<T> Drop for WorkStealing<T> {
+        fn drop(&mut self) {
+            let buffer = *self.buffer.get_mut();
+            let bottom = *self.bottom.get_mut();
+            let mut top = *self.top.get_mut();
+            unsafe {
+                while top < bottom {
+                    (*buffer).read(top).assume_init_drop();
+                    top += 1;
+                }
+                drop(Box::from_raw(buffer));
+                for old in self.retired.get_mut().unwrap().drain(..) {
+                    drop(Box::from_raw(old));
+                }
+            }
+        }
+    }
+
+    pub struct Worker<T> {
+        deque: Arc<WorkStealing<T>>,
+        owner: PhantomData<Cell<()>>,
+    }
+    impl<T> Worker<T> {
+        pub fn push(&self, data: T) {
+            let deque = &*self.deque;
+            let bottom = deque.bottom.load(Ordering::Relaxed);
+            let top = deque.top.load(Ordering::Acquire);
+            let mut buffer = deque.buffer.load(Ordering::Relaxed);
+            if bottom - top >= unsafe { (*buffer).capacity() } as isize {
+                buffer = self.grow(buffer, top, bottom);
+            }
+            unsafe { (*buffer).write(bottom, data) };
+            atomic::fence(Ordering::Release);
+            deque.bottom.store(bottom + 1, Ordering::Relaxed);
+        }
+        pub fn pop(&self) -> Result<T, Box<dyn core::error::Error>> {
+            let deque = &*self.deque;
+            let bottom = deque.bottom.load(Ordering::Relaxed) - 1;
+            let buffer = deque.buffer.load(Ordering::Relaxed);
+            deque.bottom.store(bottom, Ordering::Relaxed);
+            atomic::fence(Ordering::SeqCst);
+            let top = deque.top.load(Ordering::Relaxed);
+            if top > bottom {
+                deque.bottom.store(bottom + 1, Ordering::Relaxed);
+                return Err(Box::new(core::fmt::Error));
+            }
+            let data = unsafe { (*buffer).read(bottom) };
+            if top == bottom {
+                let won = deque.top.compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed).is_ok();
+                deque.bottom.store(bottom + 1, Ordering::Relaxed);
+                if !won {
+                    return Err(Box::new(core::fmt::Error));
+                }
+            }
+            return Ok(unsafe { data.assume_init() });
+        }
+        pub fn stealer(&self) -> Stealer<T> {
+            return Stealer {
+                deque: Arc::clone(&self.deque),
+            };
+        }
+        pub fn size(&self) -> usize {
+            return self.deque.size();
+        }
+        fn grow(&self, old: *mut Buffer<T>, top: isize, bottom: isize) -> *mut Buffer<T> {
+            let deque = &*self.deque;
+            unsafe {
+                let buffer = Buffer::alloc((*old).capacity() * 2);
+                let mut index = top;
+                while index < bottom {
+                    (*buffer).write(index, (*old).read(index).assume_init());
+                    index += 1;
+                }
+                deque.buffer.store(buffer, Ordering::Release);
+                deque.retired.lock().unwrap().push(old);
+                return buffer;
+            }
+        }
+    }
+
+    pub struct Stealer<T> {
+        deque: Arc<WorkStealing<T>>,
+    }
+    impl<T> Clone for Stealer<T> {
+        fn clone(&self) -> Self {
+            return Stealer {
+                deque: Arc::clone(&self.deque),
+            };
+        }
+    }
+    impl<T> Stealer<T> {
+        pub fn steal(&self) -> Steal<T> {
+            let deque = &*self.deque;
+            let top = deque.top.load(Ordering::Acquire);
+            atomic::fence(Ordering::SeqCst);
+            let bottom = deque.bottom.load(Ordering::Acquire);
+            if top >= bottom {
+                return Steal::Empty;
+            }
+            let buffer = deque.buffer.load(Ordering::Acquire);
+            let data = unsafe { (*buffer).read(top) };
+            if deque.top.compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed).is_err() {
+                return Steal::Retry;
+            }
+            return Steal::Success(unsafe { data.assume_init() });
+        }
+        pub fn size(&self) -> usize {
+            return self.deque.size();
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::sync::atomic::{AtomicBool, AtomicUsize};
+
+        fn capacity<T>(worker: &Worker<T>) -> usize {
+            return unsafe { (*worker.deque.buffer.load(Ordering::Relaxed)).capacity() };
+        }
+
+        #[test]
+        fn every_task_runs_exactly_once_while_the_buffer_grows() {
+            const TASKS: usize = 60_000;
+            for _ in 0..5 {
+                let (worker, stealer) = WorkStealing::<usize>::split_with_capacity(2);
+                assert_eq!(capacity(&worker), MIN_CAPACITY);
+                let counts: Arc<Vec<AtomicUsize>> = Arc::new((0..TASKS).map(|_| AtomicUsize::new(0)).collect());
+                let done = Arc::new(AtomicBool::new(false));
+                let stealers: Vec<_> = (0..4).map(|_| {
+                    let stealer = stealer.clone();
+                    let counts = Arc::clone(&counts);
+                    let done = Arc::clone(&done);
+                    std::thread::spawn(move || {
+                        loop {
+                            match stealer.steal() {
+                                Steal::Success(task) => {
+                                    counts[task].fetch_add(1, Ordering::Relaxed);
+                                },
+                                Steal::Retry => {},
+                                Steal::Empty => {
+                                    if done.load(Ordering::Acquire) {
+                                        break;
+                                    }
+                                }
+                            }
+                        }
+                    })
+                }).collect();
+                // Bursts outrun the stealers, so the buffer has to grow several times under contention.
+                for burst in (0..TASKS).collect::<Vec<_>>().chunks(3000) {
+                    for &task in burst {
+                        worker.push(task);
+                    }
+                    for _ in 0..burst.len() / 2 {
+                        if let Ok(task) = worker.pop() {
+                            counts[task].fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                }
+                assert!(capacity(&worker) > MIN_CAPACITY);
+                while let Ok(task) = worker.pop() {
+                    counts[task].fetch_add(1, Ordering::Relaxed);
+                }
+                done.store(true, Ordering::Release);
+                for handle in stealers {
+                    handle.join().unwrap();
+                }
+                assert!(counts.iter().all(|count| count.load(Ordering::Relaxed) == 1));
+                assert_eq!(worker.size(), 0);
+            }
+        }
+
+        #[test]
+        fn dropping_the_deque_drops_queued_tasks() {
+            let task = Arc::new(());
+            {
+                let (worker, stealer) = WorkStealing::split_with_capacity(2);
+                for _ in 0..100 {
+                    worker.push(Arc::clone(&task));
+                }
+                drop(worker.pop().unwrap());
+                assert!(matches!(stealer.steal(), Steal::Success(_)));
+                assert_eq!(Arc::strong_count(&task), 99);
+            }
+            assert_eq!(Arc::strong_count(&task), 1);
+        }
+    }
+}
+pub mod skip_list {
+    use alloc::{boxed::Box, vec::Vec};
+    use core::{marker::PhantomData, ops::{Bound, RangeBounds}, ptr::{self, NonNull}, sync::atomic::{AtomicPtr, AtomicUsize, Ordering}};
+
+    const MAX_LEVEL: usize = 32;
+    const DEFAULT_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+    // `width` is the number of positions the link skips; a missing `next` points one past the end.
+    struct Link<K, V> {
+        next: Option<NonNull<Tower<K, V>>>,
+        width: usize,
+    }
+    impl<K, V> Clone for Link<K, V> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+    impl<K, V> Copy for Link<K, V> {}
+    type Path<K, V> = ([Option<NonNull<Tower<K, V>>>; MAX_LEVEL], [usize; MAX_LEVEL]);
+    struct Tower<K, V> {
+        key: K,
+        value: V,
+        links: Vec<Link<K, V>>,
+    }
+    pub struct SkipMap<K: Ord, V> {
+        head: Vec<Link<K, V>>,
+        amount: usize,
+        state: u64,
+    }
+    impl<K, V> SkipMap<K, V> where K: Ord {
+        pub fn new() -> Self {
+            return SkipMap::with_seed(DEFAULT_SEED);
+        }
+        pub fn with_seed(seed: u64) -> Self {
+            return SkipMap {
+                head: alloc::vec![Link { next: None, width: 1 }; MAX_LEVEL],
+                amount: 0,
+                state: if seed == 0 { DEFAULT_SEED } else { seed },
+            };
+        }
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            let (update, rank) = self.path(&key);
+            if let Some(next) = self.links(update[0])[0].next {
+                let tower = unsafe { &mut *next.as_ptr() };
+                if tower.key == key {
+                    return Some(core::mem::replace(&mut tower.value, value));
+                }
+            }
+            let height = self.random_level();
+            let position = rank[0] + 1;
+            let tower = NonNull::from(Box::leak(Box::new(Tower {
+                key,
+                value,
+                links: Vec::with_capacity(height),
+            })));
+            for level in 0..MAX_LEVEL {
+                let previous = &mut self.links_mut(update[level])[level];
+                if level < height {
+                    let link = Link {
+                        next: previous.next,
+                        width: rank[level] + previous.width + 1 - position,
+                    };
+                    *previous = Link {
+                        next: Some(tower),
+                        width: position - rank[level],
+                    };
+                    unsafe { (*tower.as_ptr()).links.push(link) };
+                } else {
+                    previous.width += 1;
+                }
+            }
+            self.amount += 1;
+            return None;
+        }
+        pub fn remove(&mut self, key: &K) -> Option<V> {
+            let (update, _) = self.path(key);
+            let target = self.links(update[0])[0].next.filter(|value| unsafe { &value.as_ref().key } == key)?;
+            for (level, node) in update.into_iter().enumerate() {
+                let previous = &mut self.links_mut(node)[level];
+                if previous.next == Some(target) {
+                    let link = unsafe { target.as_ref().links[level] };
+                    *previous = Link {
+                        next: link.next,
+                        width: previous.width + link.width - 1,
+                    };
+                } else {
+                    previous.width -= 1;
+                }
+            }
+            self.amount -= 1;
+            let tower = unsafe { Box::from_raw(target.as_ptr()) };
+            return Some(tower.value);
+        }
+        pub fn get(&self, key: &K) -> Option<&V> {
+            let (node, _) = self.seek(key, true);
+            return node.map(|value| unsafe { &*value.as_ptr() }).filter(|tower| &tower.key == key).map(|tower| &tower.value);
+        }
+        pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            let (node, _) = self.seek(key, true);
+            return node.map(|value| unsafe { &mut *value.as_ptr() }).filter(|tower| &tower.key == key).map(|tower| &mut tower.value);
+        }
+        pub fn contains_key(&self, key: &K) -> bool {
+            return self.get(key).is_some();
+        }
+        pub fn first(&self) -> Option<(&K, &V)> {
+            return self.head[0].next.map(|value| Self::entry(value));
+        }
+        pub fn last(&self) -> Option<(&K, &V)> {
+            let mut node = None;
+            for level in (0..MAX_LEVEL).rev() {
+                while let Some(next) = self.links(node)[level].next {
+                    node = Some(next);
+                }
+            }
+            return node.map(|value| Self::entry(value));
+        }
+        pub fn nth(&self, index: usize) -> Option<(&K, &V)> {
+            if index >= self.amount {
+                return None;
+            }
+            let mut node = None;
+            let mut position = 0;
+            for level in (0..MAX_LEVEL).rev() {
+                loop {
+                    let link = self.links(node)[level];
+                    if link.next.is_none() || position + link.width > index + 1 {
+                        break;
+                    }
+                    position += link.width;
+                    node = link.next;
+                }
+            }
+            return node.map(|value| Self::entry(value));
+        }
+        pub fn rank_of(&self, key: &K) -> Option<usize> {
+            let (node, rank) = self.seek(key, true);
+            return node.filter(|value| unsafe { &value.as_ref().key } == key).map(|_| rank);
+        }
+        pub fn range<R>(&self, range: R) -> Range<'_, K, V> where R: RangeBounds<K> {
+            let (node, start) = match range.start_bound() {
+                Bound::Included(key) => self.seek(key, true),
+                Bound::Excluded(key) => self.seek(key, false),
+                Bound::Unbounded => (self.head[0].next, 0),
+            };
+            let end = match range.end_bound() {
+                Bound::Included(key) => self.seek(key, false).1,
+                Bound::Excluded(key) => self.seek(key, true).1,
+                Bound::Unbounded => self.amount,
+            };
+            return Range {
+                node,
+                remaining: end.saturating_sub(start),
+                marker: PhantomData,
+            };
+        }
+        pub fn iter(&self) -> Range<'_, K, V> {
+            return self.range(..);
+        }
+        pub fn keys(&self) -> impl Iterator<Item = &K> {
+            return self.iter().map(|(key, _)| key);
+        }
+        pub fn values(&self) -> impl Iterator<Item = &V> {
+            return self.iter().map(|(_, value)| value);
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where K: core::fmt::Display, V: core::fmt::Display {
+            println!("=====================map=begin======================");
+            for (key, value) in self.iter() {
+                println!("{}: {}", key, value);
+            }
+            println!("======================map=end=======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn entry<'a>(node: NonNull<Tower<K, V>>) -> (&'a K, &'a V) {
+            let tower = unsafe { &*node.as_ptr() };
+            return (&tower.key, &tower.value);
+        }
+        fn links(&self, node: Option<NonNull<Tower<K, V>>>) -> &[Link<K, V>] {
+            match node {
+                None => {
+                    return &self.head;
+                },
+                Some(value) => {
+                    return unsafe { &(*value.as_ptr()).links };
+                }
+            }
+        }
+        fn links_mut(&mut self, node: Option<NonNull<Tower<K, V>>>) -> &mut [Link<K, V>] {
+            match node {
+                None => {
+                    return &mut self.head;
+                },
+                Some(value) => {
+                    return unsafe { &mut (*value.as_ptr()).links };
+                }
+            }
+        }
+        // Predecessor and its position (head is 0) at every level for the last key below `key`.
+        fn path(&self, key: &K) -> Path<K, V> {
+            let mut update = [None; MAX_LEVEL];
+            let mut rank = [0; MAX_LEVEL];
+            let mut node = None;
+            let mut position = 0;
+            for level in (0..MAX_LEVEL).rev() {
+                while let Some(next) = self.links(node)[level].next.filter(|value| unsafe { &value.as_ref().key } < key) {
+                    position += self.links(node)[level].width;
+                    node = Some(next);
+                }
+                update[level] = node;
+                rank[level] = position;
+            }
+            return (update, rank);
+        }
+        // First node whose key is not below `key` (or above it, when not inclusive) and its index.
+        fn seek(&self, key: &K, inclusive: bool) -> (Option<NonNull<Tower<K, V>>>, usize) {
+            let mut node = None;
+            let mut position = 0;
+            for level in (0..MAX_LEVEL).rev() {
+                while let Some(next) = self.links(node)[level].next {
+                    let other = unsafe { &next.as_ref().key };
+                    if other > key || (inclusive && other == key) {
+                        break;
+                    }
+                    position += self.links(node)[level].width;
+                    node = Some(next);
+                }
+            }
+            return (self.links(node)[0].next, position);
+        }
+        fn random_level(&mut self) -> usize {
+            self.state ^= self.state >> 12;
+            self.state ^= self.state << 25;
+            self.state ^= self.state >> 27;
+            let bits = self.state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+            return (bits.trailing_ones() as usize).min(MAX_LEVEL - 1) + 1;
+        }
+    }
+    impl<K, V> Drop for SkipMap<K, V> where K: Ord {
+        fn drop(&mut self) {
+            let mut current = self.head[0].next.take();
+            while let Some(node) = current {
+                let tower = unsafe { Box::from_raw(node.as_ptr()) };
+                current = tower.links[0].next;
+            }
+        }
+    }
+    pub struct Range<'a, K, V> {
+        node: Option<NonNull<Tower<K, V>>>,
+        remaining: usize,
+        marker: PhantomData<&'a Tower<K, V>>,
+    }
+    impl<'a, K, V> Iterator for Range<'a, K, V> {
+        type Item = (&'a K, &'a V);
+        fn next(&mut self) -> Option<(&'a K, &'a V)> {
+            if self.remaining == 0 {
+                return None;
+            }
+            let tower = unsafe { &*self.node?.as_ptr() };
+            self.node = tower.links[0].next;
+            self.remaining -= 1;
+            return Some((&tower.key, &tower.value));
+        }
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            return (self.remaining, Some(self.remaining));
+        }
+    }
+    impl<K, V> ExactSizeIterator for Range<'_, K, V> {}
+
+    pub struct SkipSet<K: Ord> {
+        map: SkipMap<K, ()>,
+    }
+    impl<K> SkipSet<K> where K: Ord {
+        pub fn new() -> Self {
+            return SkipSet {
+                map: SkipMap::new(),
+            };
+        }
+        pub fn with_seed(seed: u64) -> Self {
+            return SkipSet {
+                map: SkipMap::with_seed(seed),
+            };
+        }
+        pub fn insert(&mut self, key: K) -> bool {
+            return self.map.insert(key, ()).is_none();
+        }
+        pub fn remove(&mut self, key: &K) -> bool {
+            return self.map.remove(key).is_some();
+        }
+        pub fn contains(&self, key: &K) -> bool {
+            return self.map.contains_key(key);
+        }
+        pub fn first(&self) -> Option<&K> {
+            return self.map.first().map(|(key, _)| key);
+        }
+        pub fn last(&self) -> Option<&K> {
+            return self.map.last().map(|(key, _)| key);
+        }
+        pub fn nth(&self, index: usize) -> Option<&K> {
+            return self.map.nth(index).map(|(key, _)| key);
+        }
+        pub fn rank_of(&self, key: &K) -> Option<usize> {
+            return self.map.rank_of(key);
+        }
+        pub fn range<R>(&self, range: R) -> impl Iterator<Item = &K> where R: RangeBounds<K> {
+            return self.map.range(range).map(|(key, _)| key);
+        }
+        pub fn iter(&self) -> impl Iterator<Item = &K> {
+            return self.map.keys();
+        }
+        pub fn size(&self) -> &usize {
+            return self.map.size();
+        }
+    }
+
+    struct Shared<K, V> {
+        key: K,
+        value: V,
+        links: Box<[AtomicPtr<Shared<K, V>>]>,
+        // The inserter and the remover each hold one reference; the last to finish retires the node.
+        refs: AtomicUsize,
+        retired: AtomicUsize,
+        garbage: AtomicPtr<Shared<K, V>>,
+    }
+    fn marked<T>(pointer: *mut T) -> bool {
+        return pointer.addr() & 1 == 1;
+    }
+    fn mark<T>(pointer: *mut T) -> *mut T {
+        return pointer.map_addr(|addr| addr | 1);
+    }
+    fn unmark<T>(pointer: *mut T) -> *mut T {
+        return pointer.map_addr(|addr| addr & !1);
+    }
+    // Epoch based reclamation: a node retired at epoch `e` is freed once the epoch reaches `e + 2`,
+    // because by then every operation that could have observed it has finished.
+    struct Collector<K, V> {
+        epoch: AtomicUsize,
+        pinned: [AtomicUsize; 3],
+        garbage: AtomicPtr<Shared<K, V>>,
+    }
+    struct Guard<'a, K, V> {
+        collector: &'a Collector<K, V>,
+        epoch: usize,
+    }
+    impl<K, V> Collector<K, V> {
+        fn pin(&self) -> Guard<'_, K, V> {
+            loop {
+                let epoch = self.epoch.load(Ordering::SeqCst);
+                self.pinned[epoch % 3].fetch_add(1, Ordering::SeqCst);
+                if self.epoch.load(Ordering::SeqCst) == epoch {
+                    return Guard {
+                        collector: self,
+                        epoch,
+                    };
+                }
+                self.pinned[epoch % 3].fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+        fn retire(&self, node: *mut Shared<K, V>) {
+            unsafe { (*node).retired.store(self.epoch.load(Ordering::SeqCst), Ordering::Relaxed) };
+            self.push(node);
+        }
+        fn push(&self, node: *mut Shared<K, V>) {
+            let mut head = self.garbage.load(Ordering::Relaxed);
+            loop {
+                unsafe { (*node).garbage.store(head, Ordering::Relaxed) };
+                match self.garbage.compare_exchange_weak(head, node, Ordering::Release, Ordering::Relaxed) {
+                    Ok(_) => {
+                        return;
+                    },
+                    Err(current) => {
+                        head = current;
+                    }
+                }
+            }
+        }
+        fn try_advance(&self) {
+            let epoch = self.epoch.load(Ordering::SeqCst);
+            if self.pinned[(epoch + 2) % 3].load(Ordering::SeqCst) != 0 {
+                return;
+            }
+            if self.epoch.compare_exchange(epoch, epoch + 1, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+                return;
+            }
+            let mut current = self.garbage.swap(ptr::null_mut(), Ordering::Acquire);
+            while !current.is_null() {
+                let node = current;
+                current = unsafe { (*node).garbage.load(Ordering::Relaxed) };
+                if unsafe { (*node).retired.load(Ordering::Relaxed) } < epoch {
+                    drop(unsafe { Box::from_raw(node) });
+                } else {
+                    self.push(node);
+                }
+            }
+        }
+    }
+    impl<K, V> Drop for Guard<'_, K, V> {
+        fn drop(&mut self) {
+            self.collector.pinned[self.epoch % 3].fetch_sub(1, Ordering::SeqCst);
+            if !self.collector.garbage.load(Ordering::Relaxed).is_null() {
+                self.collector.try_advance();
+            }
+        }
+    }
+    pub struct ConcurrentSkipMap<K: Ord, V> {
+        head: Box<[AtomicPtr<Shared<K, V>>]>,
+        amount: AtomicUsize,
+        seed: u64,
+        counter: AtomicUsize,
+        collector: Collector<K, V>,
+    }
+    unsafe impl<K, V> Send for ConcurrentSkipMap<K, V> where K: Ord + Send + Sync, V: Send + Sync {}
+    unsafe impl<K, V> Sync for ConcurrentSkipMap<K, V> where K: Ord + Send + Sync, V: Send + Sync {}
+    impl<K, V> ConcurrentSkipMap<K, V> where K: Ord {
+        pub fn new() -> Self {
+            return ConcurrentSkipMap::with_seed(DEFAULT_SEED);
+        }
+        pub fn with_seed(seed: u64) -> Self {
+            return ConcurrentSkipMap {
+                head: (0..MAX_LEVEL).map(|_| AtomicPtr::new(ptr::null_mut())).collect(),
+                amount: AtomicUsize::new(0),
+                seed,
+                counter: AtomicUsize::new(0),
+                collector: Collector {
+                    epoch: AtomicUsize::new(0),
+                    pinned: [AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0)],
+                    garbage: AtomicPtr::new(ptr::null_mut()),
+                },
+            };
+        }
+        pub fn insert(&self, key: K, value: V) -> bool {
+            let _guard = self.collector.pin();
+            let height = self.random_level();
+            let node = Box::into_raw(Box::new(Shared {
+                key,
+                value,
+                links: (0..height).map(|_| AtomicPtr::new(ptr::null_mut())).collect(),
+                refs: AtomicUsize::new(2),
+                retired: AtomicUsize::new(0),
+                garbage: AtomicPtr::new(ptr::null_mut()),
+            }));
+            let tower = unsafe { &*node };
+            let mut preds = [ptr::null_mut(); MAX_LEVEL];
+            let mut succs = [ptr::null_mut(); MAX_LEVEL];
+            loop {
+                if self.find(&tower.key, &mut preds, &mut succs) {
+                    drop(unsafe { Box::from_raw(node) });
+                    return false;
+                }
+                for (level, link) in tower.links.iter().enumerate() {
+                    link.store(succs[level], Ordering::Relaxed);
+                }
+                if self.link(preds[0], 0).compare_exchange(succs[0], node, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                    break;
+                }
+            }
+            self.amount.fetch_add(1, Ordering::Relaxed);
+            'levels: for level in 1..height {
+                loop {
+                    let current = tower.links[level].load(Ordering::SeqCst);
+                    if marked(current) {
+                        break 'levels;
+                    }
+                    if current != succs[level] && tower.links[level].compare_exchange(current, succs[level], Ordering::SeqCst, Ordering::SeqCst).is_err() {
+                        break 'levels;
+                    }
+                    if self.link(preds[level], level).compare_exchange(succs[level], node, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                        break;
+                    }
+                    self.find(&tower.key, &mut preds, &mut succs);
+                    if marked(tower.links[0].load(Ordering::SeqCst)) {
+                        break 'levels;
+                    }
+                }
+            }
+            if marked(tower.links[0].load(Ordering::SeqCst)) {
+                self.find(&tower.key, &mut preds, &mut succs);
+            }
+            self.release(node);
+            return true;
+        }
+        pub fn remove(&self, key: &K) -> Option<V> where V: Clone {
+            let _guard = self.collector.pin();
+            let mut preds = [ptr::null_mut(); MAX_LEVEL];
+            let mut succs = [ptr::null_mut(); MAX_LEVEL];
+            if !self.find(key, &mut preds, &mut succs) {
+                return None;
+            }
+            let node = succs[0];
+            let tower = unsafe { &*node };
+            for link in tower.links.iter().skip(1).rev() {
+                let mut current = link.load(Ordering::SeqCst);
+                while !marked(current) {
+                    match link.compare_exchange_weak(current, mark(current), Ordering::SeqCst, Ordering::SeqCst) {
+                        Ok(_) => {
+                            break;
+                        },
+                        Err(value) => {
+                            current = value;
+                        }
+                    }
+                }
+            }
+            let mut current = tower.links[0].load(Ordering::SeqCst);
+            loop {
+                if marked(current) {
+                    return None;
+                }
+                match tower.links[0].compare_exchange_weak(current, mark(current), Ordering::SeqCst, Ordering::SeqCst) {
+                    Ok(_) => {
+                        break;
+                    },
+                    Err(value) => {
+                        current = value;
+                    }
+                }
+            }
+            self.amount.fetch_sub(1, Ordering::Relaxed);
+            let value = tower.value.clone();
+            self.find(key, &mut preds, &mut succs);
+            self.release(node);
+            return Some(value);
+        }
+        pub fn get(&self, key: &K) -> Option<V> where V: Clone {
+            let _guard = self.collector.pin();
+            let node = self.seek(key, true);
+            if node.is_null() {
+                return None;
+            }
+            let tower = unsafe { &*node };
+            if &tower.key != key || marked(tower.links[0].load(Ordering::SeqCst)) {
+                return None;
+            }
+            return Some(tower.value.clone());
+        }
+        pub fn contains_key(&self, key: &K) -> bool {
+            let _guard = self.collector.pin();
+            let node = self.seek(key, true);
+            return !node.is_null() && unsafe { &(*node).key } == key && !marked(unsafe { (*node).links[0].load(Ordering::SeqCst) });
+        }
+        pub fn range<R>(&self, range: R) -> ConcurrentRange<'_, K, V> where K: Clone, R: RangeBounds<K> {
+            let guard = self.collector.pin();
+            let node = match range.start_bound() {
+                Bound::Included(key) => self.seek(key, true),
+                Bound::Excluded(key) => self.seek(key, false),
+                Bound::Unbounded => unmark(self.head[0].load(Ordering::SeqCst)),
+            };
+            return ConcurrentRange {
+                _guard: guard,
+                node,
+                end: range.end_bound().cloned(),
+            };
+        }
+        pub fn iter(&self) -> ConcurrentRange<'_, K, V> where K: Clone {
+            return self.range(..);
+        }
+        pub fn size(&self) -> usize {
+            return self.amount.load(Ordering::Relaxed);
+        }
+        fn link(&self, node: *mut Shared<K, V>, level: usize) -> &AtomicPtr<Shared<K, V>> {
+            if node.is_null() {
+                return &self.head[level];
+            }
+            return unsafe { &(*node).links[level] };
+        }
+        // Fills in the neighbours of `key` on every level, unlinking marked nodes on the way.
+        fn find(&self, key: &K, preds: &mut [*mut Shared<K, V>; MAX_LEVEL], succs: &mut [*mut Shared<K, V>; MAX_LEVEL]) -> bool {
+            'retry: loop {
+                let mut pred = ptr::null_mut();
+                for level in (0..MAX_LEVEL).rev() {
+                    let mut current = unmark(self.link(pred, level).load(Ordering::SeqCst));
+                    while !current.is_null() {
+                        let succ = unsafe { (*current).links[level].load(Ordering::SeqCst) };
+                        if marked(succ) {
+                            if self.link(pred, level).compare_exchange(current, unmark(succ), Ordering::SeqCst, Ordering::SeqCst).is_err() {
+                                continue 'retry;
+                            }
+                            current = unmark(succ);
+                            continue;
+                        }
+                        if unsafe { &(*current).key } >= key {
+                            break;
+                        }
+                        pred = current;
+                        current = succ;
+                    }
+                    preds[level] = pred;
+                    succs[level] = current;
+                }
+                return !succs[0].is_null() && unsafe { &(*succs[0]).key } == key;
+            }
+        }
+        // First live node at or after `key` (strictly after when not inclusive), without helping unlink.
+        fn seek(&self, key: &K, inclusive: bool) -> *mut Shared<K, V> {
+            let mut pred = ptr::null_mut();
+            let mut current = ptr::null_mut();
+            for level in (0..MAX_LEVEL).rev() {
+                current = unmark(self.link(pred, level).load(Ordering::SeqCst));
+                while !current.is_null() {
+                    let succ = unsafe { (*current).links[level].load(Ordering::SeqCst) };
+                    let other = unsafe { &(*current).key };
+                    if !marked(succ) && (other > key || (inclusive && other == key)) {
+                        break;
+                    }
+                    if !marked(succ) {
+                        pred = current;
+                    }
+                    current = unmark(succ);
+                }
+            }
+            return current;
+        }
+        fn release(&self, node: *mut Shared<K, V>) {
+            if unsafe { (*node).refs.fetch_sub(1, Ordering::AcqRel) } == 1 {
+                self.collector.retire(node);
+            }
+        }
+        fn random_level(&self) -> usize {
+            let mut bits = self.seed.wrapping_add((self.counter.fetch_add(1, Ordering::Relaxed) as u64).wrapping_mul(DEFAULT_SEED));
+            bits = (bits ^ (bits >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            bits = (bits ^ (bits >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            bits ^= bits >> 31;
+            return (bits.trailing_ones() as usize).min(MAX_LEVEL - 1) + 1;
+        }
+    }
+    impl<K, V> Drop for ConcurrentSkipMap<K, V> where K: Ord {
+        fn drop(&mut self) {
+            let mut current = unmark(*self.head[0].get_mut());
+            while !current.is_null() {
+                let node = unsafe { Box::from_raw(current) };
+                current = unmark(node.links[0].load(Ordering::Relaxed));
+            }
+            let mut current = *self.collector.garbage.get_mut();
+            while !current.is_null() {
+                let node = unsafe { Box::from_raw(current) };
+                current = node.garbage.load(Ordering::Relaxed);
+            }
+        }
+    }
+    pub struct ConcurrentRange<'a, K, V> {
+        _guard: Guard<'a, K, V>,
+        node: *mut Shared<K, V>,
+        end: Bound<K>,
+    }
+    impl<K, V> Iterator for ConcurrentRange<'_, K, V> where K: Ord + Clone, V: Clone {
+        type Item = (K, V);
+        fn next(&mut self) -> Option<(K, V)> {
+            while !self.node.is_null() {
+                let tower = unsafe { &*self.node };
+                let succ = tower.links[0].load(Ordering::SeqCst);
+                self.node = unmark(succ);
+                if marked(succ) {
+                    continue;
+                }
+                let past = match &self.end {
+                    Bound::Included(key) => &tower.key > key,
+                    Bound::Excluded(key) => &tower.key >= key,
+                    Bound::Unbounded => false,
+                };
+                if past {
+                    self.node = ptr::null_mut();
+                    return None;
+                }
+                return Some((tower.key.clone(), tower.value.clone()));
+            }
+            return None;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use alloc::collections::BTreeMap;
+        use crate::Rng;
+
+        fn heights<K, V>(map: &SkipMap<K, V>) -> Vec<usize> where K: Ord {
+            let mut heights = Vec::new();
+            let mut current = map.head[0].next;
+            while let Some(tower) = current {
+                let tower = unsafe { tower.as_ref() };
+                heights.push(tower.links.len());
+                current = tower.links[0].next;
+            }
+            return heights;
+        }
+
+        #[test]
+        fn seeds_make_tower_heights_reproducible() {
+            let build = |seed: u64| {
+                let mut map = SkipMap::with_seed(seed);
+                for key in 0..300 {
+                    map.insert(key, ());
+                }
+                return heights(&map);
+            };
+            assert_eq!(build(7), build(7));
+            assert_ne!(build(7), build(8));
+            assert_eq!(build(0), build(DEFAULT_SEED));
+        }
+
+        #[test]
+        fn seeded_maps_match_btree_map_on_nth_rank_and_range() {
+            for seed in [0, 1, 7, 0xDEAD_BEEF] {
+                let mut rng = Rng(12345 + seed);
+                let mut map = SkipMap::with_seed(seed);
+                let mut model = BTreeMap::new();
+                for step in 0..8000 {
+                    let key = rng.next(500) as i64;
+                    match rng.next(4) {
+                        0 | 1 => {
+                            assert_eq!(map.insert(key, step), model.insert(key, step));
+                        },
+                        2 => {
+                            assert_eq!(map.remove(&key), model.remove(&key));
+                        },
+                        _ => {
+                            assert_eq!(map.get(&key), model.get(&key));
+                            assert_eq!(map.rank_of(&key), model.get(&key).map(|_| model.range(..key).count()));
+                        }
+                    }
+                    assert_eq!(*map.size(), model.len());
+                    if step % 250 == 0 {
+                        for index in 0..model.len() + 2 {
+                            assert_eq!(map.nth(index), model.iter().nth(index));
+                        }
+                        let low = rng.next(520) as i64 - 10;
+                        let high = rng.next(520) as i64 - 10;
+                        if low <= high {
+                            assert!(map.range(low..high).eq(model.range(low..high)));
+                            assert!(map.range(low..=high).eq(model.range(low..=high)));
+                        }
+                        assert!(map.range(high..).eq(model.range(high..)));
+                        assert!(map.range(..=low).eq(model.range(..=low)));
+                        assert!(map.range((Bound::Excluded(low), Bound::Unbounded)).eq(model.range((Bound::Excluded(low), Bound::Unbounded))));
+                        assert_eq!(map.first(), model.iter().next());
+                        assert_eq!(map.last(), model.iter().next_back());
+                    }
+                }
+            }
+        }
+
+        // Counts live values so a leak leaves it above zero and a double free drives it below.
+        #[cfg(feature = "std")]
+        static LIVE: std::sync::atomic::AtomicIsize = std::sync::atomic::AtomicIsize::new(0);
+        #[cfg(feature = "std")]
+        struct Counted(u64);
+        #[cfg(feature = "std")]
+        impl Counted {
+            fn new(key: u64) -> Self {
+                LIVE.fetch_add(1, Ordering::SeqCst);
+                return Counted(key);
+            }
+        }
+        #[cfg(feature = "std")]
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                return Counted::new(self.0);
+            }
+        }
+        #[cfg(feature = "std")]
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                LIVE.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        #[cfg(feature = "std")]
+        #[test]
+        fn concurrent_map_survives_mixed_threads() {
+            const SHARED: u64 = 256;
+            {
+                let map = std::sync::Arc::new(ConcurrentSkipMap::with_seed(1));
+                // Per shared key: successful inserts minus successful removes, summed over all threads.
+                let owners: std::sync::Arc<Vec<std::sync::atomic::AtomicIsize>> = std::sync::Arc::new((0..SHARED).map(|_| std::sync::atomic::AtomicIsize::new(0)).collect());
+                let threads: Vec<_> = (0..8u64).map(|thread| {
+                    let map = std::sync::Arc::clone(&map);
+                    let owners = std::sync::Arc::clone(&owners);
+                    std::thread::spawn(move || {
+                        let mut rng = Rng(thread * 7919 + 1);
+                        let mut own = BTreeMap::new();
+                        let low = 1000 + thread * 1000;
+                        for _ in 0..30_000 {
+                            let shared = rng.next(SHARED);
+                            let private = low + rng.next(200);
+                            match rng.next(6) {
+                                0 => {
+                                    if map.insert(shared, Counted::new(shared)) {
+                                        owners[shared as usize].fetch_add(1, Ordering::SeqCst);
+                                    }
+                                },
+                                1 => {
+                                    if let Some(value) = map.remove(&shared) {
+                                        assert_eq!(value.0, shared);
+                                        owners[shared as usize].fetch_sub(1, Ordering::SeqCst);
+                                    }
+                                },
+                                2 => {
+                                    if let Some(value) = map.get(&shared) {
+                                        assert_eq!(value.0, shared);
+                                    }
+                                },
+                                3 => {
+                                    assert_eq!(map.insert(private, Counted::new(private)), own.insert(private, ()).is_none());
+                                },
+                                4 => {
+                                    assert_eq!(map.remove(&private).map(|value| value.0), own.remove(&private).map(|_| private));
+                                },
+                                _ => {
+                                    assert_eq!(map.contains_key(&private), own.contains_key(&private));
+                                    let keys: Vec<u64> = map.range(low..low + 200).map(|(key, value)| {
+                                        assert_eq!(key, value.0);
+                                        return key;
+                                    }).collect();
+                                    assert!(keys.iter().eq(own.keys()));
+                                }
+                            }
+                        }
+                        return own.len();
+                    })
+                }).collect();
+                let private: usize = threads.into_iter().map(|thread| thread.join().unwrap()).sum();
+                let keys: Vec<u64> = map.iter().map(|(key, _)| key).collect();
+                assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+                assert_eq!(keys.len(), map.size());
+                assert_eq!(keys.iter().filter(|key| **key >= 1000).count(), private);
+                for (key, owner) in owners.iter().enumerate() {
+                    assert_eq!(owner.load(Ordering::SeqCst), map.contains_key(&(key as u64)) as isize);
+                }
+            }
+            assert_eq!(LIVE.load(Ordering::SeqCst), 0);
+        }
+
+        #[test]
+        fn concurrent_map_reclaims_removed_values() {
+            struct Tracked<'a>(&'a AtomicUsize);
+            impl Clone for Tracked<'_> {
+                fn clone(&self) -> Self {
+                    self.0.fetch_add(1, Ordering::SeqCst);
+                    return Tracked(self.0);
+                }
+            }
+            impl Drop for Tracked<'_> {
+                fn drop(&mut self) {
+                    self.0.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+            let live = AtomicUsize::new(0);
+            let map = ConcurrentSkipMap::new();
+            for key in 0..1000 {
+                live.fetch_add(1, Ordering::SeqCst);
+                assert!(map.insert(key, Tracked(&live)));
+                if key % 4 != 0 {
+                    assert!(map.remove(&key).is_some());
+                }
+            }
+            assert_eq!(map.size(), 250);
+            assert!(live.load(Ordering::SeqCst) < 300);
+            drop(map);
+            assert_eq!(live.load(Ordering::SeqCst), 0);
+        }
+    }
+}
+pub mod tree {
+    use alloc::{boxed::Box, vec::Vec};
+    use core::{cmp::Ordering, marker::PhantomData, ops::{Bound, RangeBounds}, ptr::NonNull};
+    use crate::queue::Queue;
+    use crate::stack::Stack;
+
+    type Link<K, V> = Option<Box<Node<K, V>>>;
+    // `tag` is the subtree height in an AVL tree and the node colour in a red-black tree; splay trees leave it unused.
+    struct Node<K, V> {
+        key: K,
+        value: V,
+        tag: usize,
+        left: Link<K, V>,
+        right: Link<K, V>,
+    }
+    impl<K, V> Node<K, V> {
+        fn new(key: K, value: V, tag: usize) -> Box<Self> {
+            return Box::new(Node {
+                key,
+                value,
+                tag,
+                left: None,
+                right: None,
+            });
+        }
+    }
+    fn find<'a, K, V>(mut node: Option<&'a Node<K, V>>, key: &K) -> Option<&'a Node<K, V>> where K: Ord {
+        while let Some(current) = node {
+            match key.cmp(&current.key) {
+                Ordering::Less => {
+                    node = current.left.as_deref();
+                },
+                Ordering::Greater => {
+                    node = current.right.as_deref();
+                },
+                Ordering::Equal => {
+                    return Some(current);
+                }
+            }
+        }
+        return None;
+    }
+    fn find_mut<'a, K, V>(mut node: Option<&'a mut Node<K, V>>, key: &K) -> Option<&'a mut V> where K: Ord {
+        while let Some(current) = node {
+            match key.cmp(&current.key) {
+                Ordering::Less => {
+                    node = current.left.as_deref_mut();
+                },
+                Ordering::Greater => {
+                    node = current.right.as_deref_mut();
+                },
+                Ordering::Equal => {
+                    return Some(&mut current.value);
+                }
+            }
+        }
+        return None;
+    }
+    // Greatest node below `key` (or equal to it when inclusive).
+    fn below<'a, K, V>(mut node: Option<&'a Node<K, V>>, key: &K, inclusive: bool) -> Option<&'a Node<K, V>> where K: Ord {
+        let mut found = None;
+        while let Some(current) = node {
+            if current.key < *key || (inclusive && current.key == *key) {
+                found = Some(current);
+                node = current.right.as_deref();
+            } else {
+                node = current.left.as_deref();
+            }
+        }
+        return found;
+    }
+    // Least node above `key` (or equal to it when inclusive).
+    fn above<'a, K, V>(mut node: Option<&'a Node<K, V>>, key: &K, inclusive: bool) -> Option<&'a Node<K, V>> where K: Ord {
+        let mut found = None;
+        while let Some(current) = node {
+            if current.key > *key || (inclusive && current.key == *key) {
+                found = Some(current);
+                node = current.left.as_deref();
+            } else {
+                node = current.right.as_deref();
+            }
+        }
+        return found;
+    }
+    fn first<K, V>(mut node: Option<&Node<K, V>>) -> Option<&Node<K, V>> {
+        while let Some(left) = node.and_then(|value| value.left.as_deref()) {
+            node = Some(left);
+        }
+        return node;
+    }
+    fn last<K, V>(mut node: Option<&Node<K, V>>) -> Option<&Node<K, V>> {
+        while let Some(right) = node.and_then(|value| value.right.as_deref()) {
+            node = Some(right);
+        }
+        return node;
+    }
+    fn entry<K, V>(node: &Node<K, V>) -> (&K, &V) {
+        return (&node.key, &node.value);
+    }
+    fn range<'a, K, V, R>(root: Option<&'a Node<K, V>>, range: R) -> Range<'a, K, V> where K: Ord, R: RangeBounds<K> {
+        let mut stack = Stack::new();
+        let mut node = root;
+        while let Some(current) = node {
+            let inside = match range.start_bound() {
+                Bound::Included(key) => current.key >= *key,
+                Bound::Excluded(key) => current.key > *key,
+                Bound::Unbounded => true,
+            };
+            if inside {
+                stack.push(current);
+                node = current.left.as_deref();
+            } else {
+                node = current.right.as_deref();
+            }
+        }
+        let mut end = match range.end_bound() {
+            Bound::Included(key) => below(root, key, true),
+            Bound::Excluded(key) => below(root, key, false),
+            Bound::Unbounded => last(root),
+        };
+        if let (Ok(start), Some(stop)) = (stack.top(), end) && start.key > stop.key {
+            end = None;
+        }
+        return Range {
+            stack,
+            end,
+        };
+    }
+    fn push_left<'a, K, V>(stack: &mut Stack<&'a Node<K, V>>, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+    fn count<K, V>(node: Option<&Node<K, V>>) -> usize {
+        match node {
+            None => {
+                return 0;
+            },
+            Some(value) => {
+                return 1 + count(value.left.as_deref()) + count(value.right.as_deref());
+            }
+        }
+    }
+    fn depth<K, V>(node: Option<&Node<K, V>>) -> usize {
+        match node {
+            None => {
+                return 0;
+            },
+            Some(value) => {
+                return 1 + depth(value.left.as_deref()).max(depth(value.right.as_deref()));
+            }
+        }
+    }
+    fn ordered<K, V>(node: Option<&Node<K, V>>) -> bool where K: Ord {
+        let mut previous: Option<&K> = None;
+        for (key, _) in InOrder::new(node) {
+            if previous.is_some_and(|value| value >= key) {
+                return false;
+            }
+            previous = Some(key);
+        }
+        return true;
+    }
+
+    pub struct InOrder<'a, K, V> {
+        stack: Stack<&'a Node<K, V>>,
+    }
+    impl<'a, K, V> InOrder<'a, K, V> {
+        fn new(root: Option<&'a Node<K, V>>) -> Self {
+            let mut stack = Stack::new();
+            push_left(&mut stack, root);
+            return InOrder {
+                stack,
+            };
+        }
+    }
+    impl<'a, K, V> Iterator for InOrder<'a, K, V> {
+        type Item = (&'a K, &'a V);
+        fn next(&mut self) -> Option<(&'a K, &'a V)> {
+            let node = self.stack.get().ok()?;
+            push_left(&mut self.stack, node.right.as_deref());
+            return Some(entry(node));
+        }
+    }
+    pub struct PreOrder<'a, K, V> {
+        stack: Stack<&'a Node<K, V>>,
+    }
+    impl<'a, K, V> PreOrder<'a, K, V> {
+        fn new(root: Option<&'a Node<K, V>>) -> Self {
+            let mut stack = Stack::new();
+            if let Some(node) = root {
+                stack.push(node);
+            }
+            return PreOrder {
+                stack,
+            };
+        }
+    }
+    impl<'a, K, V> Iterator for PreOrder<'a, K, V> {
+        type Item = (&'a K, &'a V);
+        fn next(&mut self) -> Option<(&'a K, &'a V)> {
+            let node = self.stack.get().ok()?;
+            if let Some(right) = node.right.as_deref() {
+                self.stack.push(right);
+            }
+            if let Some(left) = node.left.as_deref() {
+                self.stack.push(left);
+            }
+            return Some(entry(node));
+        }
+    }
+    pub struct PostOrder<'a, K, V> {
+        stack: Stack<(&'a Node<K, V>, bool)>,
+    }
+    impl<'a, K, V> PostOrder<'a, K, V> {
+        fn new(root: Option<&'a Node<K, V>>) -> Self {
+            let mut stack = Stack::new();
+            if let Some(node) = root {
+                stack.push((node, false));
+            }
+            return PostOrder {
+                stack,
+            };
+        }
+    }
+    impl<'a, K, V> Iterator for PostOrder<'a, K, V> {
+        type Item = (&'a K, &'a V);
+        fn next(&mut self) -> Option<(&'a K, &'a V)> {
+            loop {
+                let (node, expanded) = self.stack.get().ok()?;
+                if expanded {
+                    return Some(entry(node));
+                }
+                self.stack.push((node, true));
+                if let Some(right) = node.right.as_deref() {
+                    self.stack.push((right, false));
+                }
+                if let Some(left) = node.left.as_deref() {
+                    self.stack.push((left, false));
+                }
+            }
+        }
+    }
+    pub struct LevelOrder<'a, K, V> {
+        queue: Queue<&'a Node<K, V>>,
+    }
+    impl<'a, K, V> LevelOrder<'a, K, V> {
+        fn new(root: Option<&'a Node<K, V>>) -> Self {
+            let mut queue = Queue::new();
+            if let Some(node) = root {
+                queue.push(node);
+            }
+            return LevelOrder {
+                queue,
+            };
+        }
+    }
+    impl<'a, K, V> Iterator for LevelOrder<'a, K, V> {
+        type Item = (&'a K, &'a V);
+        fn next(&mut self) -> Option<(&'a K, &'a V)> {
+            let node = self.queue.get().ok()?;
+            if let Some(left) = node.left.as_deref() {
+                self.queue.push(left);
+            }
+            if let Some(right) = node.right.as_deref() {
+                self.queue.push(right);
+            }
+            return Some(entry(node));
+        }
+    }
+    pub struct Range<'a, K, V> {
+        stack: Stack<&'a Node<K, V>>,
+        end: Option<&'a Node<K, V>>,
+    }
+    impl<'a, K, V> Iterator for Range<'a, K, V> {
+        type Item = (&'a K, &'a V);
+        fn next(&mut self) -> Option<(&'a K, &'a V)> {
+            let end = self.end?;
+            let node = self.stack.get().ok()?;
+            push_left(&mut self.stack, node.right.as_deref());
+            if core::ptr::eq(node, end) {
+                self.end = None;
+            }
+            return Some(entry(node));
+        }
+    }
+
+    pub struct AvlTree<K: Ord, V> {
+        root: Link<K, V>,
+        amount: usize,
+    }
+    impl<K, V> AvlTree<K, V> where K: Ord {
+        pub fn new() -> Self {
+            return AvlTree {
+                root: None,
+                amount: 0,
+            };
+        }
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            let mut old = None;
+            self.root = Some(Self::insert_node(self.root.take(), key, value, &mut old));
+            if old.is_none() {
+                self.amount += 1;
+            }
+            return old;
+        }
+        pub fn remove(&mut self, key: &K) -> Option<V> {
+            let mut removed = None;
+            self.root = Self::remove_node(self.root.take(), key, &mut removed);
+            if removed.is_some() {
+                self.amount -= 1;
+            }
+            return removed;
+        }
+        pub fn get(&self, key: &K) -> Option<&V> {
+            return find(self.root.as_deref(), key).map(|node| &node.value);
+        }
+        pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            return find_mut(self.root.as_deref_mut(), key);
+        }
+        pub fn contains_key(&self, key: &K) -> bool {
+            return self.get(key).is_some();
+        }
+        pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+            return below(self.root.as_deref(), key, true).map(entry);
+        }
+        pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+            return above(self.root.as_deref(), key, true).map(entry);
+        }
+        pub fn first(&self) -> Option<(&K, &V)> {
+            return first(self.root.as_deref()).map(entry);
+        }
+        pub fn last(&self) -> Option<(&K, &V)> {
+            return last(self.root.as_deref()).map(entry);
+        }
+        pub fn range<R>(&self, range: R) -> Range<'_, K, V> where R: RangeBounds<K> {
+            return self::range(self.root.as_deref(), range);
+        }
+        pub fn iter(&self) -> InOrder<'_, K, V> {
+            return InOrder::new(self.root.as_deref());
+        }
+        pub fn pre_order(&self) -> PreOrder<'_, K, V> {
+            return PreOrder::new(self.root.as_deref());
+        }
+        pub fn post_order(&self) -> PostOrder<'_, K, V> {
+            return PostOrder::new(self.root.as_deref());
+        }
+        pub fn level_order(&self) -> LevelOrder<'_, K, V> {
+            return LevelOrder::new(self.root.as_deref());
+        }
+        pub fn height(&self) -> usize {
+            return Self::height_of(&self.root);
+        }
+        pub fn validate(&self) -> Result<(), Box<dyn core::error::Error>> {
+            if !ordered(self.root.as_deref()) || count(self.root.as_deref()) != self.amount || Self::check(self.root.as_deref()).is_none() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where K: core::fmt::Display, V: core::fmt::Display {
+            println!("=====================tree=begin=====================");
+            for (key, value) in self.iter() {
+                println!("{}: {}", key, value);
+            }
+            println!("======================tree=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn height_of(node: &Link<K, V>) -> usize {
+            return node.as_ref().map_or(0, |value| value.tag);
+        }
+        fn update(node: &mut Node<K, V>) {
+            node.tag = 1 + Self::height_of(&node.left).max(Self::height_of(&node.right));
+        }
+        fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+            let mut right = node.right.take().unwrap();
+            node.right = right.left.take();
+            Self::update(&mut node);
+            right.left = Some(node);
+            Self::update(&mut right);
+            return right;
+        }
+        fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+            let mut left = node.left.take().unwrap();
+            node.left = left.right.take();
+            Self::update(&mut node);
+            left.right = Some(node);
+            Self::update(&mut left);
+            return left;
+        }
+        fn rebalance(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+            Self::update(&mut node);
+            let left = Self::height_of(&node.left);
+            let right = Self::height_of(&node.right);
+            if left > right + 1 {
+                let child = node.left.take().unwrap();
+                if Self::height_of(&child.left) < Self::height_of(&child.right) {
+                    node.left = Some(Self::rotate_left(child));
+                } else {
+                    node.left = Some(child);
+                }
+                return Self::rotate_right(node);
+            }
+            if right > left + 1 {
+                let child = node.right.take().unwrap();
+                if Self::height_of(&child.right) < Self::height_of(&child.left) {
+                    node.right = Some(Self::rotate_right(child));
+                } else {
+                    node.right = Some(child);
+                }
+                return Self::rotate_left(node);
+            }
+            return node;
+        }
+        fn insert_node(node: Link<K, V>, key: K, value: V, old: &mut Option<V>) -> Box<Node<K, V>> {
+            match node {
+                None => {
+                    return Node::new(key, value, 1);
+                },
+                Some(mut current) => {
+                    match key.cmp(&current.key) {
+                        Ordering::Less => {
+                            current.left = Some(Self::insert_node(current.left.take(), key, value, old));
+                        },
+                        Ordering::Greater => {
+                            current.right = Some(Self::insert_node(current.right.take(), key, value, old));
+                        },
+                        Ordering::Equal => {
+                            *old = Some(core::mem::replace(&mut current.value, value));
+                            return current;
+                        }
+                    }
+                    return Self::rebalance(current);
+                }
+            }
+        }
+        fn remove_node(node: Link<K, V>, key: &K, removed: &mut Option<V>) -> Link<K, V> {
+            let mut current = node?;
+            match key.cmp(&current.key) {
+                Ordering::Less => {
+                    current.left = Self::remove_node(current.left.take(), key, removed);
+                },
+                Ordering::Greater => {
+                    current.right = Self::remove_node(current.right.take(), key, removed);
+                },
+                Ordering::Equal => {
+                    let left = current.left.take();
+                    let right = current.right.take();
+                    *removed = Some(current.value);
+                    match (left, right) {
+                        (None, right) => {
+                            return right;
+                        },
+                        (left, None) => {
+                            return left;
+                        },
+                        (Some(left), Some(right)) => {
+                            let (rest, mut successor) = Self::remove_min(right);
+                            successor.left = Some(left);
+                            successor.right = rest;
+                            return Some(Self::rebalance(successor));
+                        }
+                    }
+                }
+            }
+            return Some(Self::rebalance(current));
+        }
+        fn remove_min(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+            match node.left.take() {
+                None => {
+                    let rest = node.right.take();
+                    return (rest, node);
+                },
+                Some(left) => {
+                    let (rest, min) = Self::remove_min(left);
+                    node.left = rest;
+                    return (Some(Self::rebalance(node)), min);
+                }
+            }
+        }
+        // Returns the subtree height when every stored height and balance factor is consistent.
+        fn check(node: Option<&Node<K, V>>) -> Option<usize> {
+            match node {
+                None => {
+                    return Some(0);
+                },
+                Some(value) => {
+                    let left = Self::check(value.left.as_deref())?;
+                    let right = Self::check(value.right.as_deref())?;
+                    let height = 1 + left.max(right);
+                    if left.abs_diff(right) > 1 || value.tag != height {
+                        return None;
+                    }
+                    return Some(height);
+                }
+            }
+        }
+    }
+
+    const RED: usize = 0;
+    const BLACK: usize = 1;
+    type Removed<K, V> = (Link<K, V>, Box<Node<K, V>>, bool);
+    pub struct RbTree<K: Ord, V> {
+        root: Link<K, V>,
+        amount: usize,
+    }
+    impl<K, V> RbTree<K, V> where K: Ord {
+        pub fn new() -> Self {
+            return RbTree {
+                root: None,
+                amount: 0,
+            };
+        }
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            let mut old = None;
+            let mut root = Self::insert_node(self.root.take(), key, value, &mut old);
+            root.tag = BLACK;
+            self.root = Some(root);
+            if old.is_none() {
+                self.amount += 1;
+            }
+            return old;
+        }
+        pub fn remove(&mut self, key: &K) -> Option<V> {
+            let mut removed = None;
+            self.root = Self::remove_node(self.root.take(), key, &mut removed).0;
+            if let Some(root) = self.root.as_mut() {
+                root.tag = BLACK;
+            }
+            if removed.is_some() {
+                self.amount -= 1;
+            }
+            return removed;
+        }
+        pub fn get(&self, key: &K) -> Option<&V> {
+            return find(self.root.as_deref(), key).map(|node| &node.value);
+        }
+        pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            return find_mut(self.root.as_deref_mut(), key);
+        }
+        pub fn contains_key(&self, key: &K) -> bool {
+            return self.get(key).is_some();
+        }
+        pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+            return below(self.root.as_deref(), key, true).map(entry);
+        }
+        pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+            return above(self.root.as_deref(), key, true).map(entry);
+        }
+        pub fn first(&self) -> Option<(&K, &V)> {
+            return first(self.root.as_deref()).map(entry);
+        }
+        pub fn last(&self) -> Option<(&K, &V)> {
+            return last(self.root.as_deref()).map(entry);
+        }
+        pub fn range<R>(&self, range: R) -> Range<'_, K, V> where R: RangeBounds<K> {
+            return self::range(self.root.as_deref(), range);
+        }
+        pub fn iter(&self) -> InOrder<'_, K, V> {
+            return InOrder::new(self.root.as_deref());
+        }
+        pub fn pre_order(&self) -> PreOrder<'_, K, V> {
+            return PreOrder::new(self.root.as_deref());
+        }
+        pub fn post_order(&self) -> PostOrder<'_, K, V> {
+            return PostOrder::new(self.root.as_deref());
+        }
+        pub fn level_order(&self) -> LevelOrder<'_, K, V> {
+            return LevelOrder::new(self.root.as_deref());
+        }
+        pub fn height(&self) -> usize {
+            return depth(self.root.as_deref());
+        }
+        pub fn validate(&self) -> Result<(), Box<dyn core::error::Error>> {
+            if Self::is_red(&self.root) || !ordered(self.root.as_deref()) || count(self.root.as_deref()) != self.amount || Self::check(self.root.as_deref()).is_none() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where K: core::fmt::Display, V: core::fmt::Display {
+            println!("=====================tree=begin=====================");
+            for (key, value) in self.iter() {
+                println!("{}: {}", key, value);
+            }
+            println!("======================tree=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn is_red(node: &Link<K, V>) -> bool {
+            return node.as_ref().is_some_and(|value| value.tag == RED);
+        }
+        fn paint(node: &mut Link<K, V>, colour: usize) {
+            if let Some(value) = node.as_mut() {
+                value.tag = colour;
+            }
+        }
+        fn rotate_left(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+            let mut right = node.right.take().unwrap();
+            node.right = right.left.take();
+            right.left = Some(node);
+            return right;
+        }
+        fn rotate_right(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+            let mut left = node.left.take().unwrap();
+            node.left = left.right.take();
+            left.right = Some(node);
+            return left;
+        }
+        fn insert_node(node: Link<K, V>, key: K, value: V, old: &mut Option<V>) -> Box<Node<K, V>> {
+            match node {
+                None => {
+                    return Node::new(key, value, RED);
+                },
+                Some(mut current) => {
+                    match key.cmp(&current.key) {
+                        Ordering::Less => {
+                            current.left = Some(Self::insert_node(current.left.take(), key, value, old));
+                        },
+                        Ordering::Greater => {
+                            current.right = Some(Self::insert_node(current.right.take(), key, value, old));
+                        },
+                        Ordering::Equal => {
+                            *old = Some(core::mem::replace(&mut current.value, value));
+                            return current;
+                        }
+                    }
+                    return Self::fix_insert(current);
+                }
+            }
+        }
+        // Repairs a red child with a red grandchild below `node`, recolouring when the uncle is red
+        // and rotating otherwise.
+        fn fix_insert(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+            let left = node.left.as_ref().is_some_and(|child| child.tag == RED && (Self::is_red(&child.left) || Self::is_red(&child.right)));
+            let right = node.right.as_ref().is_some_and(|child| child.tag == RED && (Self::is_red(&child.left) || Self::is_red(&child.right)));
+            if !left && !right {
+                return node;
+            }
+            if Self::is_red(&node.left) && Self::is_red(&node.right) {
+                node.tag = RED;
+                Self::paint(&mut node.left, BLACK);
+                Self::paint(&mut node.right, BLACK);
+                return node;
+            }
+            if left {
+                if Self::is_red(&node.left.as_ref().unwrap().right) {
+                    node.left = Some(Self::rotate_left(node.left.take().unwrap()));
+                }
+                node = Self::rotate_right(node);
+                Self::paint(&mut node.right, RED);
+            } else {
+                if Self::is_red(&node.right.as_ref().unwrap().left) {
+                    node.right = Some(Self::rotate_right(node.right.take().unwrap()));
+                }
+                node = Self::rotate_left(node);
+                Self::paint(&mut node.left, RED);
+            }
+            node.tag = BLACK;
+            return node;
+        }
+        // The returned flag is set when the subtree lost one unit of black height.
+        fn remove_node(node: Link<K, V>, key: &K, removed: &mut Option<V>) -> (Link<K, V>, bool) {
+            let mut current = match node {
+                None => {
+                    return (None, false);
+                },
+                Some(value) => {
+                    value
+                }
+            };
+            match key.cmp(&current.key) {
+                Ordering::Less => {
+                    let (left, shorter) = Self::remove_node(current.left.take(), key, removed);
+                    current.left = left;
+                    if shorter {
+                        return Self::fix_left(current);
+                    }
+                    return (Some(current), false);
+                },
+                Ordering::Greater => {
+                    let (right, shorter) = Self::remove_node(current.right.take(), key, removed);
+                    current.right = right;
+                    if shorter {
+                        return Self::fix_right(current);
+                    }
+                    return (Some(current), false);
+                },
+                Ordering::Equal => {
+                    let left = current.left.take();
+                    let right = current.right.take();
+                    let colour = current.tag;
+                    *removed = Some(current.value);
+                    match (left, right) {
+                        (None, None) => {
+                            return (None, colour == BLACK);
+                        },
+                        (Some(mut child), None) | (None, Some(mut child)) => {
+                            child.tag = BLACK;
+                            return (Some(child), false);
+                        },
+                        (Some(left), Some(right)) => {
+                            let (rest, mut successor, shorter) = Self::remove_min(right);
+                            successor.tag = colour;
+                            successor.left = Some(left);
+                            successor.right = rest;
+                            if shorter {
+                                return Self::fix_right(successor);
+                            }
+                            return (Some(successor), false);
+                        }
+                    }
+                }
+            }
+        }
+        fn remove_min(mut node: Box<Node<K, V>>) -> Removed<K, V> {
+            match node.left.take() {
+                None => {
+                    match node.right.take() {
+                        None => {
+                            let shorter = node.tag == BLACK;
+                            return (None, node, shorter);
+                        },
+                        Some(mut child) => {
+                            child.tag = BLACK;
+                            return (Some(child), node, false);
+                        }
+                    }
+                },
+                Some(left) => {
+                    let (rest, min, shorter) = Self::remove_min(left);
+                    node.left = rest;
+                    if shorter {
+                        let (node, shorter) = Self::fix_left(node);
+                        return (node, min, shorter);
+                    }
+                    return (Some(node), min, false);
+                }
+            }
+        }
+        fn fix_left(mut node: Box<Node<K, V>>) -> (Link<K, V>, bool) {
+            let mut sibling = node.right.take().unwrap();
+            if sibling.tag == RED {
+                sibling.tag = BLACK;
+                node.tag = RED;
+                node.right = sibling.left.take();
+                sibling.left = Self::fix_left(node).0;
+                return (Some(sibling), false);
+            }
+            if Self::is_red(&sibling.left) || Self::is_red(&sibling.right) {
+                if !Self::is_red(&sibling.right) {
+                    sibling = Self::rotate_right(sibling);
+                }
+                let colour = node.tag;
+                node.right = Some(sibling);
+                let mut top = Self::rotate_left(node);
+                top.tag = colour;
+                Self::paint(&mut top.left, BLACK);
+                Self::paint(&mut top.right, BLACK);
+                return (Some(top), false);
+            }
+            sibling.tag = RED;
+            node.right = Some(sibling);
+            if node.tag == RED {
+                node.tag = BLACK;
+                return (Some(node), false);
+            }
+            return (Some(node), true);
+        }
+        fn fix_right(mut node: Box<Node<K, V>>) -> (Link<K, V>, bool) {
+            let mut sibling = node.left.take().unwrap();
+            if sibling.tag == RED {
+                sibling.tag = BLACK;
+                node.tag = RED;
+                node.left = sibling.right.take();
+                sibling.right = Self::fix_right(node).0;
+                return (Some(sibling), false);
+            }
+            if Self::is_red(&sibling.left) || Self::is_red(&sibling.right) {
+                if !Self::is_red(&sibling.left) {
+                    sibling = Self::rotate_left(sibling);
+                }
+                let colour = node.tag;
+                node.left = Some(sibling);
+                let mut top = Self::rotate_right(node);
+                top.tag = colour;
+                Self::paint(&mut top.left, BLACK);
+                Self::paint(&mut top.right, BLACK);
+                return (Some(top), false);
+            }
+            sibling.tag = RED;
+            node.left = Some(sibling);
+            if node.tag == RED {
+                node.tag = BLACK;
+                return (Some(node), false);
+            }
+            return (Some(node), true);
+        }
+        // Returns the black height when no red node has a red child and all paths agree.
+        fn check(node: Option<&Node<K, V>>) -> Option<usize> {
+            match node {
+                None => {
+                    return Some(1);
+                },
+                Some(value) => {
+                    if value.tag == RED && (Self::is_red(&value.left) || Self::is_red(&value.right)) {
+                        return None;
+                    }
+                    let left = Self::check(value.left.as_deref())?;
+                    let right = Self::check(value.right.as_deref())?;
+                    if left != right {
+                        return None;
+                    }
+                    return Some(left + value.tag);
+                }
+            }
+        }
+    }
+
+    struct BNode<K, V> {
+        keys: Vec<K>,
+        values: Vec<V>,
+        children: Vec<BNode<K, V>>,
+    }
+    impl<K, V> BNode<K, V> {
+        fn leaf() -> Self {
+            return BNode {
+                keys: Vec::new(),
+                values: Vec::new(),
+                children: Vec::new(),
+            };
+        }
+        fn is_leaf(&self) -> bool {
+            return self.children.is_empty();
+        }
+    }
+    // A subtree root with its height, counting a leaf as 1.
+    type Rooted<N> = (N, usize);
+    type Halves<N> = (Rooted<N>, Rooted<N>);
+    enum Insertion<K, V, N> {
+        Replaced(V),
+        Fitted,
+        Split(K, Option<V>, N),
+    }
+    // Every node but the root keeps between `B - 1` and `2 * B - 1` keys.
+    pub struct BTreeMap<K: Ord, V, const B: usize> {
+        root: BNode<K, V>,
+        amount: usize,
+    }
+    impl<K, V, const B: usize> BTreeMap<K, V, B> where K: Ord {
+        pub fn new() -> Self {
+            const { assert!(B >= 2) };
+            return BTreeMap {
+                root: BNode::leaf(),
+                amount: 0,
+            };
+        }
+        pub fn from_sorted<I>(items: I) -> Result<Self, Box<dyn core::error::Error>> where I: IntoIterator<Item = (K, V)> {
+            let entries: Vec<(K, V)> = items.into_iter().collect();
+            if entries.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut tree = BTreeMap::new();
+            tree.rebuild(entries);
+            return Ok(tree);
+        }
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            match Self::insert_into(&mut self.root, key, value) {
+                Insertion::Replaced(old) => {
+                    return Some(old);
+                },
+                Insertion::Fitted => {},
+                Insertion::Split(key, value, right) => {
+                    let left = core::mem::replace(&mut self.root, BNode::leaf());
+                    self.root.keys.push(key);
+                    self.root.values.extend(value);
+                    self.root.children = alloc::vec![left, right];
+                }
+            }
+            self.amount += 1;
+            return None;
+        }
+        pub fn remove(&mut self, key: &K) -> Option<V> {
+            let removed = Self::remove_from(&mut self.root, key);
+            if self.root.keys.is_empty() && !self.root.is_leaf() {
+                self.root = self.root.children.pop().unwrap();
+            }
+            if removed.is_some() {
+                self.amount -= 1;
+            }
+            return removed;
+        }
+        pub fn get(&self, key: &K) -> Option<&V> {
+            let mut node = &self.root;
+            loop {
+                match node.keys.binary_search(key) {
+                    Ok(index) => {
+                        return Some(&node.values[index]);
+                    },
+                    Err(index) => {
+                        node = node.children.get(index)?;
+                    }
+                }
+            }
+        }
+        pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            let mut node = &mut self.root;
+            loop {
+                match node.keys.binary_search(key) {
+                    Ok(index) => {
+                        return Some(&mut node.values[index]);
+                    },
+                    Err(index) => {
+                        node = node.children.get_mut(index)?;
+                    }
+                }
+            }
+        }
+        pub fn contains_key(&self, key: &K) -> bool {
+            return self.get(key).is_some();
+        }
+        pub fn first(&self) -> Option<(&K, &V)> {
+            let mut node = &self.root;
+            while let Some(child) = node.children.first() {
+                node = child;
+            }
+            return node.keys.first().map(|key| (key, &node.values[0]));
+        }
+        pub fn last(&self) -> Option<(&K, &V)> {
+            let mut node = &self.root;
+            while let Some(child) = node.children.last() {
+                node = child;
+            }
+            return node.keys.last().map(|key| (key, &node.values[node.values.len() - 1]));
+        }
+        pub fn range<R>(&self, range: R) -> BTreeRange<'_, K, V> where R: RangeBounds<K> {
+            let mut stack = Stack::new();
+            let mut node = Some(&self.root);
+            while let Some(current) = node {
+                let index = match range.start_bound() {
+                    Bound::Included(key) => current.keys.partition_point(|value| value < key),
+                    Bound::Excluded(key) => current.keys.partition_point(|value| value <= key),
+                    Bound::Unbounded => 0,
+                };
+                stack.push((current, index));
+                node = current.children.get(index);
+            }
+            let start = match range.start_bound() {
+                Bound::Included(key) => Self::above(&self.root, key, true),
+                Bound::Excluded(key) => Self::above(&self.root, key, false),
+                Bound::Unbounded => self.first().map(|(key, _)| key),
+            };
+            let end = match range.end_bound() {
+                Bound::Included(key) => Self::below(&self.root, key, true),
+                Bound::Excluded(key) => Self::below(&self.root, key, false),
+                Bound::Unbounded => self.last().map(|(key, _)| key),
+            };
+            return BTreeRange {
+                stack,
+                end: end.filter(|stop| start.is_some_and(|value| value <= *stop)),
+            };
+        }
+        pub fn iter(&self) -> BTreeRange<'_, K, V> {
+            return self.range(..);
+        }
+        // Splits along the key's path and joins the pieces back together by height; only counting
+        // the smaller half walks entries.
+        pub fn split_off(&mut self, key: &K) -> Self {
+            let total = self.amount;
+            let height = Self::height(&self.root);
+            let root = core::mem::replace(&mut self.root, BNode::leaf());
+            let ((left, _), (right, _)) = Self::split(root, height, key);
+            self.root = left;
+            let mut other = BTreeMap {
+                root: right,
+                amount: 0,
+            };
+            other.amount = count_split(other.iter(), self.iter(), total);
+            self.amount = total - other.amount;
+            return other;
+        }
+        // Joins by height when every key of one tree sorts below the other's; overlapping trees
+        // are merged entry by entry and rebuilt in O(n + m).
+        pub fn append(&mut self, other: &mut Self) {
+            let below = self.last().zip(other.first()).is_none_or(|((last, _), (first, _))| last < first);
+            let above = other.last().zip(self.first()).is_some_and(|((last, _), (first, _))| last < first);
+            if !below && !above {
+                let entries = merge_sorted(self.take_entries(), other.take_entries());
+                self.rebuild(entries);
+                return;
+            }
+            let amount = self.amount + other.amount;
+            let (lower, upper) = (core::mem::replace(&mut self.root, BNode::leaf()), core::mem::replace(&mut other.root, BNode::leaf()));
+            let (mut lower, upper) = if above { (upper, lower) } else { (lower, upper) };
+            self.root = if lower.keys.is_empty() {
+                upper
+            } else {
+                let middle = Self::pop_last(&mut lower);
+                let heights = (Self::height(&lower), Self::height(&upper));
+                Self::join(Self::collapse(lower, heights.0), middle, (upper, heights.1)).0
+            };
+            self.amount = amount;
+            other.amount = 0;
+        }
+        pub fn validate(&self) -> Result<(), Box<dyn core::error::Error>> {
+            if Self::check(&self.root, true, None, None).is_none() || self.iter().count() != self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where K: core::fmt::Display, V: core::fmt::Display {
+            println!("=====================tree=begin=====================");
+            for (key, value) in self.iter() {
+                println!("{}: {}", key, value);
+            }
+            println!("======================tree=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn insert_into(node: &mut BNode<K, V>, key: K, value: V) -> Insertion<K, V, BNode<K, V>> {
+            let index = match node.keys.binary_search(&key) {
+                Ok(index) => {
+                    return Insertion::Replaced(core::mem::replace(&mut node.values[index], value));
+                },
+                Err(index) => {
+                    index
+                }
+            };
+            if node.is_leaf() {
+                node.keys.insert(index, key);
+                node.values.insert(index, value);
+            } else {
+                match Self::insert_into(&mut node.children[index], key, value) {
+                    Insertion::Split(key, value, right) => {
+                        node.keys.insert(index, key);
+                        node.values.insert(index, value.unwrap());
+                        node.children.insert(index + 1, right);
+                    },
+                    other => {
+                        return other;
+                    }
+                }
+            }
+            return Self::split_full(node);
+        }
+        // Splits a node holding `2 * B` or more keys around its middle key, handing the right half
+        // up to the parent.
+        fn split_full(node: &mut BNode<K, V>) -> Insertion<K, V, BNode<K, V>> {
+            if node.keys.len() < 2 * B {
+                return Insertion::Fitted;
+            }
+            let half = node.keys.len() / 2;
+            let keys = node.keys.split_off(half + 1);
+            let values = node.values.split_off(half + 1);
+            let children = if node.is_leaf() { Vec::new() } else { node.children.split_off(half + 1) };
+            let value = node.values.pop();
+            return Insertion::Split(node.keys.pop().unwrap(), value, BNode {
+                keys,
+                values,
+                children,
+            });
+        }
+        fn remove_from(node: &mut BNode<K, V>, key: &K) -> Option<V> {
+            match node.keys.binary_search(key) {
+                Ok(index) => {
+                    if node.is_leaf() {
+                        node.keys.remove(index);
+                        return Some(node.values.remove(index));
+                    }
+                    let (key, value) = Self::pop_last(&mut node.children[index]);
+                    node.keys[index] = key;
+                    let old = core::mem::replace(&mut node.values[index], value);
+                    Self::fix(node, index);
+                    return Some(old);
+                },
+                Err(index) => {
+                    if node.is_leaf() {
+                        return None;
+                    }
+                    let removed = Self::remove_from(&mut node.children[index], key);
+                    if removed.is_some() {
+                        Self::fix(node, index);
+                    }
+                    return removed;
+                }
+            }
+        }
+        fn pop_last(node: &mut BNode<K, V>) -> (K, V) {
+            if node.is_leaf() {
+                return (node.keys.pop().unwrap(), node.values.pop().unwrap());
+            }
+            let index = node.children.len() - 1;
+            let entry = Self::pop_last(&mut node.children[index]);
+            Self::fix(node, index);
+            return entry;
+        }
+        // Refills child `index` after it dropped below `B - 1` keys, borrowing through the parent or merging.
+        fn fix(node: &mut BNode<K, V>, index: usize) {
+            if node.children[index].keys.len() + 1 >= B {
+                return;
+            }
+            if index > 0 && node.children[index - 1].keys.len() >= B {
+                let (left, right) = node.children.split_at_mut(index);
+                let (sibling, child) = (&mut left[index - 1], &mut right[0]);
+                child.keys.insert(0, core::mem::replace(&mut node.keys[index - 1], sibling.keys.pop().unwrap()));
+                child.values.insert(0, core::mem::replace(&mut node.values[index - 1], sibling.values.pop().unwrap()));
+                if let Some(last) = sibling.children.pop() {
+                    child.children.insert(0, last);
+                }
+            } else if index + 1 < node.children.len() && node.children[index + 1].keys.len() >= B {
+                let (left, right) = node.children.split_at_mut(index + 1);
+                let (child, sibling) = (&mut left[index], &mut right[0]);
+                child.keys.push(core::mem::replace(&mut node.keys[index], sibling.keys.remove(0)));
+                child.values.push(core::mem::replace(&mut node.values[index], sibling.values.remove(0)));
+                if !sibling.children.is_empty() {
+                    child.children.push(sibling.children.remove(0));
+                }
+            } else {
+                Self::merge(node, index.saturating_sub(1));
+            }
+        }
+        fn merge(node: &mut BNode<K, V>, at: usize) {
+            let right = node.children.remove(at + 1);
+            let left = &mut node.children[at];
+            left.keys.push(node.keys.remove(at));
+            left.values.push(node.values.remove(at));
+            left.keys.extend(right.keys);
+            left.values.extend(right.values);
+            left.children.extend(right.children);
+        }
+        // Evens out children `at` and `at + 1` when either may be short: merges them, then splits
+        // the result again if it overflows.
+        fn balance(node: &mut BNode<K, V>, at: usize) {
+            Self::merge(node, at);
+            if let Insertion::Split(key, value, right) = Self::split_full(&mut node.children[at]) {
+                node.keys.insert(at, key);
+                node.values.insert(at, value.unwrap());
+                node.children.insert(at + 1, right);
+            }
+        }
+        fn height(mut node: &BNode<K, V>) -> usize {
+            let mut height = 1;
+            while let Some(child) = node.children.first() {
+                node = child;
+                height += 1;
+            }
+            return height;
+        }
+        // Drops keyless roots left behind by merges.
+        fn collapse(mut node: BNode<K, V>, mut height: usize) -> Rooted<BNode<K, V>> {
+            while node.keys.is_empty() && !node.is_leaf() {
+                node = node.children.pop().unwrap();
+                height -= 1;
+            }
+            return (node, height);
+        }
+        // Splits a subtree into the entries below `key` and the rest. The keys left over on either
+        // side of the path at each level form trees of their own, joined onto the halves coming up
+        // from below.
+        fn split(node: BNode<K, V>, height: usize, key: &K) -> Halves<BNode<K, V>> {
+            let BNode { mut keys, mut values, mut children } = node;
+            let index = keys.partition_point(|value| value < key);
+            let mut right_keys = keys.split_off(index);
+            let mut right_values = values.split_off(index);
+            if children.is_empty() {
+                let right = BNode {
+                    keys: right_keys,
+                    values: right_values,
+                    children: Vec::new(),
+                };
+                return ((BNode { keys, values, children }, 1), (right, 1));
+            }
+            let right_children = children.split_off(index + 1);
+            let (low, high) = Self::split(children.pop().unwrap(), height - 1, key);
+            let left = match (keys.pop(), values.pop()) {
+                (Some(key), Some(value)) => Self::join(Self::collapse(BNode { keys, values, children }, height), (key, value), low),
+                _ => low,
+            };
+            if right_keys.is_empty() {
+                return (left, high);
+            }
+            let middle = (right_keys.remove(0), right_values.remove(0));
+            let rest = BNode {
+                keys: right_keys,
+                values: right_values,
+                children: right_children,
+            };
+            return (left, Self::join(high, middle, Self::collapse(rest, height)));
+        }
+        // Joins two trees around `middle`, whose key sorts between theirs, by hanging the shorter
+        // tree off the facing spine of the taller one.
+        fn join(left: Rooted<BNode<K, V>>, middle: (K, V), right: Rooted<BNode<K, V>>) -> Rooted<BNode<K, V>> {
+            let ((mut left, left_height), (mut right, right_height)) = (left, right);
+            let (mut root, height, grown) = match left_height.cmp(&right_height) {
+                Ordering::Equal => {
+                    let mut root = BNode {
+                        keys: alloc::vec![middle.0],
+                        values: alloc::vec![middle.1],
+                        children: alloc::vec![left, right],
+                    };
+                    Self::balance(&mut root, 0);
+                    return Self::collapse(root, left_height + 1);
+                },
+                Ordering::Greater => {
+                    let grown = Self::graft(&mut left, left_height - right_height - 1, middle, right, true);
+                    (left, left_height, grown)
+                },
+                Ordering::Less => {
+                    let grown = Self::graft(&mut right, right_height - left_height - 1, middle, left, false);
+                    (right, right_height, grown)
+                }
+            };
+            if let Insertion::Split(key, value, right) = grown {
+                let left = core::mem::replace(&mut root, BNode::leaf());
+                root.keys.push(key);
+                root.values.extend(value);
+                root.children = alloc::vec![left, right];
+                return (root, height + 1);
+            }
+            return (root, height);
+        }
+        // Hangs `tree` off the rightmost (or leftmost) node `depth` levels down, splitting any node
+        // that overflows on the way back up.
+        fn graft(node: &mut BNode<K, V>, depth: usize, middle: (K, V), tree: BNode<K, V>, rightmost: bool) -> Insertion<K, V, BNode<K, V>> {
+            if depth == 0 {
+                if rightmost {
+                    node.keys.push(middle.0);
+                    node.values.push(middle.1);
+                    node.children.push(tree);
+                    Self::balance(node, node.keys.len() - 1);
+                } else {
+                    node.keys.insert(0, middle.0);
+                    node.values.insert(0, middle.1);
+                    node.children.insert(0, tree);
+                    Self::balance(node, 0);
+                }
+            } else {
+                let index = if rightmost { node.children.len() - 1 } else { 0 };
+                match Self::graft(&mut node.children[index], depth - 1, middle, tree, rightmost) {
+                    Insertion::Split(key, value, right) => {
+                        node.keys.insert(index, key);
+                        node.values.insert(index, value.unwrap());
+                        node.children.insert(index + 1, right);
+                    },
+                    other => {
+                        return other;
+                    }
+                }
+            }
+            return Self::split_full(node);
+        }
+        fn above<'a>(mut node: &'a BNode<K, V>, key: &K, inclusive: bool) -> Option<&'a K> {
+            let mut found = None;
+            loop {
+                let index = node.keys.partition_point(|value| value < key || (!inclusive && value == key));
+                if index < node.keys.len() {
+                    found = Some(&node.keys[index]);
+                }
+                match node.children.get(index) {
+                    None => {
+                        return found;
+                    },
+                    Some(child) => {
+                        node = child;
+                    }
+                }
+            }
+        }
+        fn below<'a>(mut node: &'a BNode<K, V>, key: &K, inclusive: bool) -> Option<&'a K> {
+            let mut found = None;
+            loop {
+                let index = node.keys.partition_point(|value| value < key || (inclusive && value == key));
+                if index > 0 {
+                    found = Some(&node.keys[index - 1]);
+                }
+                match node.children.get(index) {
+                    None => {
+                        return found;
+                    },
+                    Some(child) => {
+                        node = child;
+                    }
+                }
+            }
+        }
+        fn take_entries(&mut self) -> Vec<(K, V)> {
+            let mut entries = Vec::with_capacity(self.amount);
+            Self::drain(core::mem::replace(&mut self.root, BNode::leaf()), &mut entries);
+            self.amount = 0;
+            return entries;
+        }
+        fn drain(node: BNode<K, V>, entries: &mut Vec<(K, V)>) {
+            let BNode { keys, values, children } = node;
+            let mut children = children.into_iter();
+            for entry in keys.into_iter().zip(values) {
+                if let Some(child) = children.next() {
+                    Self::drain(child, entries);
+                }
+                entries.push(entry);
+            }
+            if let Some(child) = children.next() {
+                Self::drain(child, entries);
+            }
+        }
+        fn rebuild(&mut self, entries: Vec<(K, V)>) {
+            self.amount = entries.len();
+            self.root = Self::build(entries, Vec::new());
+        }
+        // Packs one level into as few nodes as the order allows, spreading keys evenly, then builds
+        // the level above from the separators.
+        fn build(entries: Vec<(K, V)>, children: Vec<BNode<K, V>>) -> BNode<K, V> {
+            let groups = (entries.len() + 1).div_ceil(2 * B);
+            let leaf = children.is_empty();
+            if groups <= 1 {
+                let (keys, values) = entries.into_iter().unzip();
+                return BNode {
+                    keys,
+                    values,
+                    children,
+                };
+            }
+            let total = entries.len() + 1 - groups;
+            let mut entries = entries.into_iter();
+            let mut children = children.into_iter();
+            let mut nodes = Vec::with_capacity(groups);
+            let mut separators = Vec::with_capacity(groups - 1);
+            for group in 0..groups {
+                let take = total / groups + usize::from(group < total % groups);
+                let (keys, values) = entries.by_ref().take(take).unzip();
+                nodes.push(BNode {
+                    keys,
+                    values,
+                    children: if leaf { Vec::new() } else { children.by_ref().take(take + 1).collect() },
+                });
+                if group + 1 < groups {
+                    separators.push(entries.next().unwrap());
+                }
+            }
+            return Self::build(separators, nodes);
+        }
+        // Returns the depth of the subtree when key order, fill bounds and leaf depth all hold.
+        fn check(node: &BNode<K, V>, root: bool, lower: Option<&K>, upper: Option<&K>) -> Option<usize> {
+            let sorted = node.keys.windows(2).all(|pair| pair[0] < pair[1]);
+            let bounded = node.keys.first().is_none_or(|key| lower.is_none_or(|value| value < key)) && node.keys.last().is_none_or(|key| upper.is_none_or(|value| key < value));
+            let filled = node.keys.len() < 2 * B && (root || node.keys.len() + 1 >= B);
+            if !sorted || !bounded || !filled || node.values.len() != node.keys.len() {
+                return None;
+            }
+            if node.is_leaf() {
+                return Some(1);
+            }
+            if node.children.len() != node.keys.len() + 1 {
+                return None;
+            }
+            let mut depth = None;
+            for (index, child) in node.children.iter().enumerate() {
+                let lower = if index == 0 { lower } else { Some(&node.keys[index - 1]) };
+                let upper = node.keys.get(index).or(upper);
+                let value = Self::check(child, false, lower, upper)?;
+                if depth.is_some_and(|other| other != value) {
+                    return None;
+                }
+                depth = Some(value);
+            }
+            return depth.map(|value| value + 1);
+        }
+    }
+    // Counts the items of `first` out of `total` in O(min(first, second)) steps by walking both.
+    fn count_split<I, J>(mut first: I, mut second: J, total: usize) -> usize where I: Iterator, J: Iterator {
+        let mut steps = 0;
+        loop {
+            if first.next().is_none() {
+                return steps;
+            }
+            if second.next().is_none() {
+                return total - steps;
+            }
+            steps += 1;
+        }
+    }
+    fn merge_sorted<K, V>(left: Vec<(K, V)>, right: Vec<(K, V)>) -> Vec<(K, V)> where K: Ord {
+        let mut merged = Vec::with_capacity(left.len() + right.len());
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+        loop {
+            let order = match (left.peek(), right.peek()) {
+                (None, None) => {
+                    return merged;
+                },
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(first), Some(second)) => first.0.cmp(&second.0),
+            };
+            match order {
+                Ordering::Less => {
+                    merged.extend(left.next());
+                },
+                Ordering::Greater => {
+                    merged.extend(right.next());
+                },
+                Ordering::Equal => {
+                    left.next();
+                    merged.extend(right.next());
+                }
+            }
+        }
+    }
+    pub struct BTreeRange<'a, K, V> {
+        stack: Stack<(&'a BNode<K, V>, usize)>,
+        end: Option<&'a K>,
+    }
+    impl<'a, K, V> Iterator for BTreeRange<'a, K, V> {
+        type Item = (&'a K, &'a V);
+        fn next(&mut self) -> Option<(&'a K, &'a V)> {
+            let end = self.end?;
+            loop {
+                let (node, index) = self.stack.get().ok()?;
+                if index < node.keys.len() {
+                    self.stack.push((node, index + 1));
+                    let mut child = node.children.get(index + 1);
+                    while let Some(current) = child {
+                        self.stack.push((current, 0));
+                        child = current.children.first();
+                    }
+                    if core::ptr::eq(&node.keys[index], end) {
+                        self.end = None;
+                    }
+                    return Some((&node.keys[index], &node.values[index]));
+                }
+            }
+        }
+    }
+
+    // Leaves hold every entry and are chained left to right; inner nodes only hold separator copies.
+    struct PlusNode<K, V> {
+        keys: Vec<K>,
+        values: Vec<V>,
+        children: Vec<NonNull<PlusNode<K, V>>>,
+        next: Option<NonNull<PlusNode<K, V>>>,
+    }
+    impl<K, V> PlusNode<K, V> {
+        fn allocate(keys: Vec<K>, values: Vec<V>, children: Vec<NonNull<PlusNode<K, V>>>) -> NonNull<Self> {
+            return NonNull::from(Box::leak(Box::new(PlusNode {
+                keys,
+                values,
+                children,
+                next: None,
+            })));
+        }
+        fn is_leaf(&self) -> bool {
+            return self.children.is_empty();
+        }
+    }
+    // Every node is owned through exactly one slot, its parent's `children` entry or the tree's
+    // `root`, so a node is borrowed for as long as the slot holding it; leaf `next` links are only
+    // followed through shared borrows.
+    fn plus<K, V>(node: &NonNull<PlusNode<K, V>>) -> &PlusNode<K, V> {
+        return unsafe { node.as_ref() };
+    }
+    fn plus_mut<K, V>(node: &mut NonNull<PlusNode<K, V>>) -> &mut PlusNode<K, V> {
+        return unsafe { node.as_mut() };
+    }
+    // Borrows the neighbouring children `left` and `left + 1` at once.
+    fn plus_pair<K, V>(children: &mut [NonNull<PlusNode<K, V>>], left: usize) -> (&mut PlusNode<K, V>, &mut PlusNode<K, V>) {
+        let (first, second) = children.split_at_mut(left + 1);
+        return (plus_mut(&mut first[left]), plus_mut(&mut second[0]));
+    }
+    pub struct BPlusTree<K: Ord + Clone, V, const B: usize> {
+        root: NonNull<PlusNode<K, V>>,
+        amount: usize,
+    }
+    impl<K, V, const B: usize> BPlusTree<K, V, B> where K: Ord + Clone {
+        pub fn new() -> Self {
+            const { assert!(B >= 2) };
+            return BPlusTree {
+                root: PlusNode::allocate(Vec::new(), Vec::new(), Vec::new()),
+                amount: 0,
+            };
+        }
+        pub fn from_sorted<I>(items: I) -> Result<Self, Box<dyn core::error::Error>> where I: IntoIterator<Item = (K, V)> {
+            let entries: Vec<(K, V)> = items.into_iter().collect();
+            if entries.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut tree = BPlusTree::new();
+            tree.rebuild(entries);
+            return Ok(tree);
+        }
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            match Self::insert_into(plus_mut(&mut self.root), key, value) {
+                Insertion::Replaced(old) => {
+                    return Some(old);
+                },
+                Insertion::Fitted => {},
+                Insertion::Split(key, _, right) => {
+                    self.root = PlusNode::allocate(alloc::vec![key], Vec::new(), alloc::vec![self.root, right]);
+                }
+            }
+            self.amount += 1;
+            return None;
+        }
+        pub fn remove(&mut self, key: &K) -> Option<V> {
+            let removed = Self::remove_from(plus_mut(&mut self.root), key);
+            let root = plus(&self.root);
+            if root.keys.is_empty() && !root.is_leaf() {
+                let child = root.children[0];
+                let old = core::mem::replace(&mut self.root, child);
+                drop(unsafe { Box::from_raw(old.as_ptr()) });
+            }
+            if removed.is_some() {
+                self.amount -= 1;
+            }
+            return removed;
+        }
+        pub fn get(&self, key: &K) -> Option<&V> {
+            let leaf = self.leaf_for(key);
+            return leaf.keys.binary_search(key).ok().map(|index| &leaf.values[index]);
+        }
+        pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            let mut node = plus_mut(&mut self.root);
+            while !node.is_leaf() {
+                let index = node.keys.partition_point(|value| value <= key);
+                node = plus_mut(&mut node.children[index]);
+            }
+            return node.keys.binary_search(key).ok().map(|index| &mut node.values[index]);
+        }
+        pub fn contains_key(&self, key: &K) -> bool {
+            return self.get(key).is_some();
+        }
+        pub fn first(&self) -> Option<(&K, &V)> {
+            let leaf = Self::edge(&self.root, false);
+            return leaf.keys.first().map(|key| (key, &leaf.values[0]));
+        }
+        pub fn last(&self) -> Option<(&K, &V)> {
+            let leaf = Self::edge(&self.root, true);
+            return leaf.keys.last().map(|key| (key, &leaf.values[leaf.values.len() - 1]));
+        }
+        pub fn range<R>(&self, range: R) -> BPlusRange<'_, K, V> where R: RangeBounds<K> {
+            let (leaf, index) = match range.start_bound() {
+                Bound::Included(key) => {
+                    let leaf = self.leaf_for(key);
+                    (leaf, leaf.keys.partition_point(|value| value < key))
+                },
+                Bound::Excluded(key) => {
+                    let leaf = self.leaf_for(key);
+                    (leaf, leaf.keys.partition_point(|value| value <= key))
+                },
+                Bound::Unbounded => (Self::edge(&self.root, false), 0),
+            };
+            let start = match leaf.keys.get(index) {
+                None => leaf.next.as_ref().and_then(|next| plus(next).keys.first()),
+                Some(key) => Some(key),
+            };
+            let end = match range.end_bound() {
+                Bound::Included(key) => self.below(key, true),
+                Bound::Excluded(key) => self.below(key, false),
+                Bound::Unbounded => self.last().map(|(key, _)| key),
+            };
+            return BPlusRange {
+                leaf: Some(leaf),
+                index,
+                end: end.filter(|stop| start.is_some_and(|value| value <= *stop)),
+            };
+        }
+        pub fn iter(&self) -> BPlusRange<'_, K, V> {
+            return self.range(..);
+        }
+        // Splits along the key's path and joins the pieces back together by height; only counting
+        // the smaller half walks entries.
+        pub fn split_off(&mut self, key: &K) -> Self {
+            let total = self.amount;
+            let ((left, _), (right, _)) = Self::split(self.root, Self::height(&self.root), key);
+            self.root = left;
+            let mut other = BPlusTree {
+                root: right,
+                amount: 0,
+            };
+            // The last leaf of each half may still link to a leaf of the other half.
+            Self::edge_mut(&mut self.root, true).next = None;
+            Self::edge_mut(&mut other.root, true).next = None;
+            other.amount = count_split(other.iter(), self.iter(), total);
+            self.amount = total - other.amount;
+            return other;
+        }
+        // Joins by height when every key of one tree sorts below the other's; overlapping trees
+        // are merged entry by entry and rebuilt in O(n + m).
+        pub fn append(&mut self, other: &mut Self) {
+            let below = self.last().zip(other.first()).is_none_or(|((last, _), (first, _))| last < first);
+            let above = other.last().zip(self.first()).is_some_and(|((last, _), (first, _))| last < first);
+            if !below && !above {
+                let entries = merge_sorted(self.take_entries(), other.take_entries());
+                self.rebuild(entries);
+                return;
+            }
+            let amount = self.amount + other.amount;
+            let lower = (self.root, Self::height(&self.root));
+            let upper = (other.root, Self::height(&other.root));
+            other.root = PlusNode::allocate(Vec::new(), Vec::new(), Vec::new());
+            self.root = if above { Self::join(upper, lower).0 } else { Self::join(lower, upper).0 };
+            self.amount = amount;
+            other.amount = 0;
+        }
+        pub fn validate(&self) -> Result<(), Box<dyn core::error::Error>> {
+            let mut leaves = Vec::new();
+            if Self::check(plus(&self.root), true, None, None, &mut leaves).is_none() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut chained: Vec<*const PlusNode<K, V>> = Vec::new();
+            let mut current = Some(Self::edge(&self.root, false));
+            while let Some(leaf) = current {
+                chained.push(leaf);
+                current = leaf.next.as_ref().map(plus);
+            }
+            if chained != leaves || self.iter().count() != self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where K: core::fmt::Display, V: core::fmt::Display {
+            println!("=====================tree=begin=====================");
+            for (key, value) in self.iter() {
+                println!("{}: {}", key, value);
+            }
+            println!("======================tree=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn leaf_for(&self, key: &K) -> &PlusNode<K, V> {
+            let mut node = plus(&self.root);
+            while !node.is_leaf() {
+                node = plus(&node.children[node.keys.partition_point(|value| value <= key)]);
+            }
+            return node;
+        }
+        fn edge(node: &NonNull<PlusNode<K, V>>, rightmost: bool) -> &PlusNode<K, V> {
+            let mut node = plus(node);
+            while !node.is_leaf() {
+                let children = &node.children;
+                node = plus(if rightmost { &children[children.len() - 1] } else { &children[0] });
+            }
+            return node;
+        }
+        fn edge_mut(node: &mut NonNull<PlusNode<K, V>>, rightmost: bool) -> &mut PlusNode<K, V> {
+            let mut node = plus_mut(node);
+            while !node.is_leaf() {
+                let index = if rightmost { node.children.len() - 1 } else { 0 };
+                node = plus_mut(&mut node.children[index]);
+            }
+            return node;
+        }
+        fn below(&self, key: &K, inclusive: bool) -> Option<&K> {
+            let mut node = plus(&self.root);
+            let mut left = None;
+            while !node.is_leaf() {
+                let index = node.keys.partition_point(|value| value <= key);
+                if index > 0 {
+                    left = Some(plus(&node.children[index - 1]));
+                }
+                node = plus(&node.children[index]);
+            }
+            let index = node.keys.partition_point(|value| value < key || (inclusive && value == key));
+            if index > 0 {
+                return Some(&node.keys[index - 1]);
+            }
+            let mut node = left?;
+            while !node.is_leaf() {
+                node = plus(&node.children[node.children.len() - 1]);
+            }
+            return node.keys.last();
+        }
+        fn insert_into(current: &mut PlusNode<K, V>, key: K, value: V) -> Insertion<K, V, NonNull<PlusNode<K, V>>> {
+            if current.is_leaf() {
+                match current.keys.binary_search(&key) {
+                    Ok(index) => {
+                        return Insertion::Replaced(core::mem::replace(&mut current.values[index], value));
+                    },
+                    Err(index) => {
+                        current.keys.insert(index, key);
+                        current.values.insert(index, value);
+                    }
+                }
+                if current.keys.len() < 2 * B {
+                    return Insertion::Fitted;
+                }
+                return Self::split_full(current);
+            }
+            let index = current.keys.partition_point(|value| value <= &key);
+            match Self::insert_into(plus_mut(&mut current.children[index]), key, value) {
+                Insertion::Split(key, _, right) => {
+                    current.keys.insert(index, key);
+                    current.children.insert(index + 1, right);
+                },
+                other => {
+                    return other;
+                }
+            }
+            return Self::split_full(current);
+        }
+        // Splits a node holding `2 * B` or more keys in half, handing the right half up to the
+        // parent; a split leaf passes up a copy of its right half's first key.
+        fn split_full(current: &mut PlusNode<K, V>) -> Insertion<K, V, NonNull<PlusNode<K, V>>> {
+            if current.keys.len() < 2 * B {
+                return Insertion::Fitted;
+            }
+            let half = current.keys.len() / 2;
+            if current.is_leaf() {
+                let mut right = PlusNode::allocate(current.keys.split_off(half), current.values.split_off(half), Vec::new());
+                plus_mut(&mut right).next = current.next;
+                current.next = Some(right);
+                return Insertion::Split(plus(&right).keys[0].clone(), None, right);
+            }
+            let keys = current.keys.split_off(half + 1);
+            let children = current.children.split_off(half + 1);
+            let separator = current.keys.pop().unwrap();
+            return Insertion::Split(separator, None, PlusNode::allocate(keys, Vec::new(), children));
+        }
+        fn remove_from(current: &mut PlusNode<K, V>, key: &K) -> Option<V> {
+            if current.is_leaf() {
+                let index = current.keys.binary_search(key).ok()?;
+                current.keys.remove(index);
+                return Some(current.values.remove(index));
+            }
+            let index = current.keys.partition_point(|value| value <= key);
+            let removed = Self::remove_from(plus_mut(&mut current.children[index]), key);
+            if removed.is_some() {
+                Self::fix(current, index);
+            }
+            return removed;
+        }
+        // Refills child `index` after it dropped below `B - 1` keys; leaf separators are recopied
+        // from the first key of the right-hand leaf.
+        fn fix(node: &mut PlusNode<K, V>, index: usize) {
+            if plus(&node.children[index]).keys.len() + 1 >= B {
+                return;
+            }
+            if index > 0 && plus(&node.children[index - 1]).keys.len() >= B {
+                let (sibling, child) = plus_pair(&mut node.children, index - 1);
+                if child.is_leaf() {
+                    child.keys.insert(0, sibling.keys.pop().unwrap());
+                    child.values.insert(0, sibling.values.pop().unwrap());
+                    node.keys[index - 1] = child.keys[0].clone();
+                } else {
+                    child.keys.insert(0, core::mem::replace(&mut node.keys[index - 1], sibling.keys.pop().unwrap()));
+                    child.children.insert(0, sibling.children.pop().unwrap());
+                }
+            } else if index + 1 < node.children.len() && plus(&node.children[index + 1]).keys.len() >= B {
+                let (child, sibling) = plus_pair(&mut node.children, index);
+                if child.is_leaf() {
+                    child.keys.push(sibling.keys.remove(0));
+                    child.values.push(sibling.values.remove(0));
+                    node.keys[index] = sibling.keys[0].clone();
+                } else {
+                    child.keys.push(core::mem::replace(&mut node.keys[index], sibling.keys.remove(0)));
+                    child.children.push(sibling.children.remove(0));
+                }
+            } else {
+                Self::merge(node, index.saturating_sub(1));
+            }
+        }
+        fn merge(node: &mut PlusNode<K, V>, at: usize) {
+            let right = unsafe { Box::from_raw(node.children.remove(at + 1).as_ptr()) };
+            let separator = node.keys.remove(at);
+            let left = plus_mut(&mut node.children[at]);
+            if !left.is_leaf() {
+                left.keys.push(separator);
+            }
+            left.keys.extend(right.keys);
+            left.values.extend(right.values);
+            left.children.extend(right.children);
+            left.next = right.next;
+        }
+        // Evens out children `at` and `at + 1` when either may be short: merges them, then splits
+        // the result again if it overflows.
+        fn balance(node: &mut PlusNode<K, V>, at: usize) {
+            Self::merge(node, at);
+            if let Insertion::Split(key, _, right) = Self::split_full(plus_mut(&mut node.children[at])) {
+                node.keys.insert(at, key);
+                node.children.insert(at + 1, right);
+            }
+        }
+        fn height(node: &NonNull<PlusNode<K, V>>) -> usize {
+            let mut node = plus(node);
+            let mut height = 1;
+            while let Some(child) = node.children.first() {
+                node = plus(child);
+                height += 1;
+            }
+            return height;
+        }
+        fn first_leaf(mut node: NonNull<PlusNode<K, V>>) -> NonNull<PlusNode<K, V>> {
+            while !plus(&node).is_leaf() {
+                node = plus(&node).children[0];
+            }
+            return node;
+        }
+        fn is_empty(node: &NonNull<PlusNode<K, V>>) -> bool {
+            let node = plus(node);
+            return node.keys.is_empty() && node.is_leaf();
+        }
+        // Frees keyless roots left behind by merges.
+        fn collapse(mut node: NonNull<PlusNode<K, V>>, mut height: usize) -> Rooted<NonNull<PlusNode<K, V>>> {
+            while plus(&node).keys.is_empty() && !plus(&node).is_leaf() {
+                let child = plus(&node).children[0];
+                drop(unsafe { Box::from_raw(core::mem::replace(&mut node, child).as_ptr()) });
+                height -= 1;
+            }
+            return (node, height);
+        }
+        // Splits a subtree into the entries below `key` and the rest. The children left over on
+        // either side of the path at each level form trees of their own, joined onto the halves
+        // coming up from below; the separators next to the path are dropped and recopied by `join`.
+        fn split(mut node: NonNull<PlusNode<K, V>>, height: usize, key: &K) -> Halves<NonNull<PlusNode<K, V>>> {
+            let current = plus_mut(&mut node);
+            if current.is_leaf() {
+                let index = current.keys.partition_point(|value| value < key);
+                let mut right = PlusNode::allocate(current.keys.split_off(index), current.values.split_off(index), Vec::new());
+                plus_mut(&mut right).next = current.next.take();
+                return ((node, 1), (right, 1));
+            }
+            let index = current.keys.partition_point(|value| value <= key);
+            let mut right_keys = current.keys.split_off(index);
+            let right_children = current.children.split_off(index + 1);
+            let (low, high) = Self::split(current.children.pop().unwrap(), height - 1, key);
+            current.keys.pop();
+            let left = if current.children.is_empty() {
+                drop(unsafe { Box::from_raw(node.as_ptr()) });
+                low
+            } else {
+                Self::join(Self::collapse(node, height), low)
+            };
+            if right_children.is_empty() {
+                return (left, high);
+            }
+            right_keys.remove(0);
+            let rest = PlusNode::allocate(right_keys, Vec::new(), right_children);
+            return (left, Self::join(high, Self::collapse(rest, height)));
+        }
+        // Joins two trees whose keys all sort below one another by hanging the shorter tree off the
+        // facing spine of the taller one, separated by a copy of the right tree's first key.
+        fn join(left: Rooted<NonNull<PlusNode<K, V>>>, right: Rooted<NonNull<PlusNode<K, V>>>) -> Rooted<NonNull<PlusNode<K, V>>> {
+            let ((mut left, left_height), (mut right, right_height)) = (left, right);
+            if Self::is_empty(&right) {
+                Self::free(right);
+                return (left, left_height);
+            }
+            if Self::is_empty(&left) {
+                Self::free(left);
+                return (right, right_height);
+            }
+            Self::edge_mut(&mut left, true).next = Some(Self::first_leaf(right));
+            let separator = Self::edge(&right, false).keys[0].clone();
+            let (mut root, height, grown) = match left_height.cmp(&right_height) {
+                Ordering::Equal => {
+                    let mut root = PlusNode::allocate(alloc::vec![separator], Vec::new(), alloc::vec![left, right]);
+                    Self::balance(plus_mut(&mut root), 0);
+                    return Self::collapse(root, left_height + 1);
+                },
+                Ordering::Greater => {
+                    let grown = Self::graft(plus_mut(&mut left), left_height - right_height - 1, separator, right, true);
+                    (left, left_height, grown)
+                },
+                Ordering::Less => {
+                    let grown = Self::graft(plus_mut(&mut right), right_height - left_height - 1, separator, left, false);
+                    (right, right_height, grown)
+                }
+            };
+            if let Insertion::Split(key, _, right) = grown {
+                root = PlusNode::allocate(alloc::vec![key], Vec::new(), alloc::vec![root, right]);
+                return (root, height + 1);
+            }
+            return (root, height);
+        }
+        // Hangs `tree` off the rightmost (or leftmost) node `depth` levels down, splitting any node
+        // that overflows on the way back up.
+        fn graft(node: &mut PlusNode<K, V>, depth: usize, separator: K, tree: NonNull<PlusNode<K, V>>, rightmost: bool) -> Insertion<K, V, NonNull<PlusNode<K, V>>> {
+            if depth == 0 {
+                if rightmost {
+                    node.keys.push(separator);
+                    node.children.push(tree);
+                    Self::balance(node, node.keys.len() - 1);
+                } else {
+                    node.keys.insert(0, separator);
+                    node.children.insert(0, tree);
+                    Self::balance(node, 0);
+                }
+            } else {
+                let index = if rightmost { node.children.len() - 1 } else { 0 };
+                match Self::graft(plus_mut(&mut node.children[index]), depth - 1, separator, tree, rightmost) {
+                    Insertion::Split(key, _, right) => {
+                        node.keys.insert(index, key);
+                        node.children.insert(index + 1, right);
+                    },
+                    other => {
+                        return other;
+                    }
+                }
+            }
+            return Self::split_full(node);
+        }
+        fn take_entries(&mut self) -> Vec<(K, V)> {
+            let mut entries = Vec::with_capacity(self.amount);
+            Self::drain(core::mem::replace(&mut self.root, PlusNode::allocate(Vec::new(), Vec::new(), Vec::new())), &mut entries);
+            self.amount = 0;
+            return entries;
+        }
+        // Frees the subtree, moving the leaf entries out in key order.
+        fn drain(node: NonNull<PlusNode<K, V>>, entries: &mut Vec<(K, V)>) {
+            let node = unsafe { Box::from_raw(node.as_ptr()) };
+            entries.extend(node.keys.into_iter().zip(node.values));
+            for child in node.children {
+                Self::drain(child, entries);
+            }
+        }
+        fn free(node: NonNull<PlusNode<K, V>>) {
+            let node = unsafe { Box::from_raw(node.as_ptr()) };
+            for child in node.children.iter() {
+                Self::free(*child);
+            }
+        }
+        fn rebuild(&mut self, entries: Vec<(K, V)>) {
+            Self::free(self.root);
+            self.amount = entries.len();
+            let groups = entries.len().div_ceil(2 * B - 1).max(1);
+            let total = entries.len();
+            let mut entries = entries.into_iter();
+            let mut nodes: Vec<NonNull<PlusNode<K, V>>> = Vec::with_capacity(groups);
+            let mut firsts = Vec::with_capacity(groups);
+            for group in 0..groups {
+                let take = total / groups + usize::from(group < total % groups);
+                let (keys, values): (Vec<K>, Vec<V>) = entries.by_ref().take(take).unzip();
+                firsts.extend(keys.first().cloned());
+                let leaf = PlusNode::allocate(keys, values, Vec::new());
+                if let Some(previous) = nodes.last_mut() {
+                    plus_mut(previous).next = Some(leaf);
+                }
+                nodes.push(leaf);
+            }
+            while nodes.len() > 1 {
+                let groups = nodes.len().div_ceil(2 * B);
+                let total = nodes.len();
+                let mut children = nodes.into_iter();
+                let mut mins = firsts.into_iter();
+                nodes = Vec::with_capacity(groups);
+                firsts = Vec::with_capacity(groups);
+                for group in 0..groups {
+                    let take = total / groups + usize::from(group < total % groups);
+                    let mut keys: Vec<K> = mins.by_ref().take(take).collect();
+                    firsts.push(keys.remove(0));
+                    nodes.push(PlusNode::allocate(keys, Vec::new(), children.by_ref().take(take).collect()));
+                }
+            }
+            self.root = nodes[0];
+        }
+        // Returns the depth of the subtree when key order, separator bounds, fill bounds and leaf
+        // depth all hold, collecting the leaves in order.
+        fn check(current: &PlusNode<K, V>, root: bool, lower: Option<&K>, upper: Option<&K>, leaves: &mut Vec<*const PlusNode<K, V>>) -> Option<usize> {
+            let sorted = current.keys.windows(2).all(|pair| pair[0] < pair[1]);
+            let bounded = current.keys.first().is_none_or(|key| lower.is_none_or(|value| value <= key)) && current.keys.last().is_none_or(|key| upper.is_none_or(|value| key < value));
+            let filled = current.keys.len() < 2 * B && (root || current.keys.len() + 1 >= B);
+            if !sorted || !bounded || !filled {
+                return None;
+            }
+            if current.is_leaf() {
+                leaves.push(current);
+                return (current.values.len() == current.keys.len()).then_some(1);
+            }
+            if current.children.len() != current.keys.len() + 1 || !current.values.is_empty() {
+                return None;
+            }
+            let mut depth = None;
+            for (index, child) in current.children.iter().enumerate() {
+                let lower = if index == 0 { lower } else { Some(&current.keys[index - 1]) };
+                let upper = current.keys.get(index).or(upper);
+                let value = Self::check(plus(child), false, lower, upper, leaves)?;
+                if depth.is_some_and(|other| other != value) {
+                    return None;
+                }
+                depth = Some(value);
+            }
+            return depth.map(|value| value + 1);
+        }
+    }
+    impl<K, V, const B: usize> Drop for BPlusTree<K, V, B> where K: Ord + Clone {
+        fn drop(&mut self) {
+            Self::free(self.root);
+        }
+    }
+    pub struct BPlusRange<'a, K, V> {
+        leaf: Option<&'a PlusNode<K, V>>,
+        index: usize,
+        end: Option<&'a K>,
+    }
+    impl<'a, K, V> Iterator for BPlusRange<'a, K, V> {
+        type Item = (&'a K, &'a V);
+        fn next(&mut self) -> Option<(&'a K, &'a V)> {
+            let end = self.end?;
+            loop {
+                let leaf = self.leaf?;
+                if self.index < leaf.keys.len() {
+                    let index = self.index;
+                    self.index += 1;
+                    if core::ptr::eq(&leaf.keys[index], end) {
+                        self.end = None;
+                    }
+                    return Some((&leaf.keys[index], &leaf.values[index]));
+                }
+                self.leaf = leaf.next.as_ref().map(plus);
+                self.index = 0;
+            }
+        }
+    }
+
+    struct StatNode<K> {
+        key: K,
+        copies: usize,
+        height: usize,
+        weight: usize,
+        left: Option<Box<StatNode<K>>>,
+        right: Option<Box<StatNode<K>>>,
+    }
+    // An AVL tree whose nodes also count the elements below them; equal keys share a node.
+    pub struct OrderStatTree<K: Ord> {
+        root: Option<Box<StatNode<K>>>,
+    }
+    impl<K> OrderStatTree<K> where K: Ord {
+        pub fn new() -> Self {
+            return OrderStatTree {
+                root: None,
+            };
+        }
+        pub fn insert(&mut self, key: K) {
+            self.root = Some(Self::insert_node(self.root.take(), key));
+        }
+        pub fn remove(&mut self, key: &K) -> bool {
+            let mut removed = 0;
+            self.root = Self::remove_node(self.root.take(), key, false, &mut removed);
+            return removed > 0;
+        }
+        pub fn remove_all(&mut self, key: &K) -> usize {
+            let mut removed = 0;
+            self.root = Self::remove_node(self.root.take(), key, true, &mut removed);
+            return removed;
+        }
+        pub fn count(&self, key: &K) -> usize {
+            let mut node = self.root.as_deref();
+            while let Some(current) = node {
+                match key.cmp(&current.key) {
+                    Ordering::Less => {
+                        node = current.left.as_deref();
+                    },
+                    Ordering::Greater => {
+                        node = current.right.as_deref();
+                    },
+                    Ordering::Equal => {
+                        return current.copies;
+                    }
+                }
+            }
+            return 0;
+        }
+        pub fn contains(&self, key: &K) -> bool {
+            return self.count(key) > 0;
+        }
+        pub fn select(&self, index: usize) -> Option<&K> {
+            let mut node = self.root.as_deref();
+            let mut index = index;
+            while let Some(current) = node {
+                let left = Self::weight(&current.left);
+                if index < left {
+                    node = current.left.as_deref();
+                } else if index < left + current.copies {
+                    return Some(&current.key);
+                } else {
+                    index -= left + current.copies;
+                    node = current.right.as_deref();
+                }
+            }
+            return None;
+        }
+        pub fn rank(&self, key: &K) -> usize {
+            return self.count_below(key, false);
+        }
+        pub fn count_range<R>(&self, range: R) -> usize where R: RangeBounds<K> {
+            let lower = match range.start_bound() {
+                Bound::Included(key) => self.count_below(key, false),
+                Bound::Excluded(key) => self.count_below(key, true),
+                Bound::Unbounded => 0,
+            };
+            let upper = match range.end_bound() {
+                Bound::Included(key) => self.count_below(key, true),
+                Bound::Excluded(key) => self.count_below(key, false),
+                Bound::Unbounded => *self.size(),
+            };
+            return upper.saturating_sub(lower);
+        }
+        pub fn first(&self) -> Option<&K> {
+            return self.select(0);
+        }
+        pub fn last(&self) -> Option<&K> {
+            return self.size().checked_sub(1).and_then(|index| self.select(index));
+        }
+        pub fn iter(&self) -> StatIter<'_, K> {
+            let mut iter = StatIter {
+                stack: Stack::new(),
+                repeat: 0,
+            };
+            iter.descend(self.root.as_deref());
+            return iter;
+        }
+        pub fn height(&self) -> usize {
+            return Self::height_of(&self.root);
+        }
+        pub fn validate(&self) -> Result<(), Box<dyn core::error::Error>> {
+            let mut previous: Option<&K> = None;
+            let mut stack = Stack::new();
+            let mut node = self.root.as_deref();
+            loop {
+                while let Some(current) = node {
+                    stack.push(current);
+                    node = current.left.as_deref();
+                }
+                let Ok(current) = stack.get() else {
+                    break;
+                };
+                if previous.is_some_and(|key| key >= &current.key) || current.copies == 0 {
+                    return Err(Box::new(core::fmt::Error));
+                }
+                previous = Some(&current.key);
+                node = current.right.as_deref();
+            }
+            if Self::check(self.root.as_deref()).is_none() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where K: core::fmt::Display {
+            println!("=====================tree=begin=====================");
+            for key in self.iter() {
+                println!("{}", key);
+            }
+            println!("======================tree=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return self.root.as_ref().map_or(&0, |node| &node.weight);
+        }
+        fn count_below(&self, key: &K, inclusive: bool) -> usize {
+            let mut node = self.root.as_deref();
+            let mut total = 0;
+            while let Some(current) = node {
+                match key.cmp(&current.key) {
+                    Ordering::Less => {
+                        node = current.left.as_deref();
+                    },
+                    Ordering::Greater => {
+                        total += Self::weight(&current.left) + current.copies;
+                        node = current.right.as_deref();
+                    },
+                    Ordering::Equal => {
+                        total += Self::weight(&current.left);
+                        if inclusive {
+                            total += current.copies;
+                        }
+                        return total;
+                    }
+                }
+            }
+            return total;
+        }
+        fn height_of(node: &Option<Box<StatNode<K>>>) -> usize {
+            return node.as_ref().map_or(0, |value| value.height);
+        }
+        fn weight(node: &Option<Box<StatNode<K>>>) -> usize {
+            return node.as_ref().map_or(0, |value| value.weight);
+        }
+        fn update(node: &mut StatNode<K>) {
+            node.height = 1 + Self::height_of(&node.left).max(Self::height_of(&node.right));
+            node.weight = node.copies + Self::weight(&node.left) + Self::weight(&node.right);
+        }
+        fn rotate_left(mut node: Box<StatNode<K>>) -> Box<StatNode<K>> {
+            let mut right = node.right.take().unwrap();
+            node.right = right.left.take();
+            Self::update(&mut node);
+            right.left = Some(node);
+            Self::update(&mut right);
+            return right;
+        }
+        fn rotate_right(mut node: Box<StatNode<K>>) -> Box<StatNode<K>> {
+            let mut left = node.left.take().unwrap();
+            node.left = left.right.take();
+            Self::update(&mut node);
+            left.right = Some(node);
+            Self::update(&mut left);
+            return left;
+        }
+        fn rebalance(mut node: Box<StatNode<K>>) -> Box<StatNode<K>> {
+            Self::update(&mut node);
+            let left = Self::height_of(&node.left);
+            let right = Self::height_of(&node.right);
+            if left > right + 1 {
+                let child = node.left.take().unwrap();
+                if Self::height_of(&child.left) < Self::height_of(&child.right) {
+                    node.left = Some(Self::rotate_left(child));
+                } else {
+                    node.left = Some(child);
+                }
+                return Self::rotate_right(node);
+            }
+            if right > left + 1 {
+                let child = node.right.take().unwrap();
+                if Self::height_of(&child.right) < Self::height_of(&child.left) {
+                    node.right = Some(Self::rotate_right(child));
+                } else {
+                    node.right = Some(child);
+                }
+                return Self::rotate_left(node);
+            }
+            return node;
+        }
+        fn insert_node(node: Option<Box<StatNode<K>>>, key: K) -> Box<StatNode<K>> {
+            match node {
+                None => {
+                    return Box::new(StatNode {
+                        key,
+                        copies: 1,
+                        height: 1,
+                        weight: 1,
+                        left: None,
+                        right: None,
+                    });
+                },
+                Some(mut current) => {
+                    match key.cmp(&current.key) {
+                        Ordering::Less => {
+                            current.left = Some(Self::insert_node(current.left.take(), key));
+                        },
+                        Ordering::Greater => {
+                            current.right = Some(Self::insert_node(current.right.take(), key));
+                        },
+                        Ordering::Equal => {
+                            current.copies += 1;
+                            current.weight += 1;
+                            return current;
+                        }
+                    }
+                    return Self::rebalance(current);
+                }
+            }
+        }
+        fn remove_node(node: Option<Box<StatNode<K>>>, key: &K, all: bool, removed: &mut usize) -> Option<Box<StatNode<K>>> {
+            let mut current = node?;
+            match key.cmp(&current.key) {
+                Ordering::Less => {
+                    current.left = Self::remove_node(current.left.take(), key, all, removed);
+                },
+                Ordering::Greater => {
+                    current.right = Self::remove_node(current.right.take(), key, all, removed);
+                },
+                Ordering::Equal => {
+                    if !all && current.copies > 1 {
+                        current.copies -= 1;
+                        current.weight -= 1;
+                        *removed = 1;
+                        return Some(current);
+                    }
+                    *removed = current.copies;
+                    match (current.left.take(), current.right.take()) {
+                        (None, right) => {
+                            return right;
+                        },
+                        (left, None) => {
+                            return left;
+                        },
+                        (Some(left), Some(right)) => {
+                            let (rest, mut successor) = Self::remove_min(right);
+                            successor.left = Some(left);
+                            successor.right = rest;
+                            return Some(Self::rebalance(successor));
+                        }
+                    }
+                }
+            }
+            return Some(Self::rebalance(current));
+        }
+        fn remove_min(mut node: Box<StatNode<K>>) -> (Option<Box<StatNode<K>>>, Box<StatNode<K>>) {
+            match node.left.take() {
+                None => {
+                    let rest = node.right.take();
+                    return (rest, node);
+                },
+                Some(left) => {
+                    let (rest, min) = Self::remove_min(left);
+                    node.left = rest;
+                    return (Some(Self::rebalance(node)), min);
+                }
+            }
+        }
+        // Returns the subtree height when stored heights, weights and balance factors are consistent.
+        fn check(node: Option<&StatNode<K>>) -> Option<usize> {
+            match node {
+                None => {
+                    return Some(0);
+                },
+                Some(value) => {
+                    let left = Self::check(value.left.as_deref())?;
+                    let right = Self::check(value.right.as_deref())?;
+                    let height = 1 + left.max(right);
+                    let weight = value.copies + Self::weight(&value.left) + Self::weight(&value.right);
+                    if left.abs_diff(right) > 1 || value.height != height || value.weight != weight {
+                        return None;
+                    }
+                    return Some(height);
+                }
+            }
+        }
+    }
+    pub struct StatIter<'a, K> {
+        stack: Stack<&'a StatNode<K>>,
+        repeat: usize,
+    }
+    impl<'a, K> StatIter<'a, K> {
+        fn descend(&mut self, mut node: Option<&'a StatNode<K>>) {
+            while let Some(current) = node {
+                self.stack.push(current);
+                node = current.left.as_deref();
+            }
+        }
+    }
+    impl<'a, K> Iterator for StatIter<'a, K> {
+        type Item = &'a K;
+        fn next(&mut self) -> Option<&'a K> {
+            let node = self.stack.top().ok()?;
+            self.repeat += 1;
+            if self.repeat == node.copies {
+                self.repeat = 0;
+                let _ = self.stack.get();
+                self.descend(node.right.as_deref());
+            }
+            return Some(&node.key);
+        }
+    }
+
+    const TREAP_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+    type TreapLink<T> = Option<Box<TreapNode<T>>>;
+    // `reversed` marks a pending reversal of the whole subtree that has not been pushed to the children yet.
+    struct TreapNode<T> {
+        value: T,
+        priority: u64,
+        weight: usize,
+        reversed: bool,
+        left: TreapLink<T>,
+        right: TreapLink<T>,
+    }
+    // A sequence addressed by position: the in-order index is the key and random priorities keep it balanced.
+    pub struct Treap<T> {
+        root: TreapLink<T>,
+        state: u64,
+    }
+    impl<T> Treap<T> {
+        pub fn new() -> Self {
+            return Treap::with_seed(TREAP_SEED);
+        }
+        pub fn with_seed(seed: u64) -> Self {
+            return Treap {
+                root: None,
+                state: if seed == 0 { TREAP_SEED } else { seed },
+            };
+        }
+        pub fn push_back(&mut self, value: T) {
+            let node = self.node(value);
+            self.root = Self::merge_nodes(self.root.take(), node);
+        }
+        pub fn push_front(&mut self, value: T) {
+            let node = self.node(value);
+            self.root = Self::merge_nodes(node, self.root.take());
+        }
+        pub fn insert(&mut self, index: usize, value: T) -> Result<(), Box<dyn core::error::Error>> {
+            if index > *self.size() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let (left, right) = Self::split_nodes(self.root.take(), index);
+            let node = self.node(value);
+            self.root = Self::merge_nodes(Self::merge_nodes(left, node), right);
+            return Ok(());
+        }
+        pub fn remove(&mut self, index: usize) -> Result<T, Box<dyn core::error::Error>> {
+            if index >= *self.size() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let (left, rest) = Self::split_nodes(self.root.take(), index);
+            let (middle, right) = Self::split_nodes(rest, 1);
+            self.root = Self::merge_nodes(left, right);
+            return Ok(middle.unwrap().value);
+        }
+        pub fn get(&self, index: usize) -> Option<&T> {
+            let mut node = self.root.as_deref();
+            let mut index = index;
+            let mut flipped = false;
+            while let Some(current) = node {
+                flipped ^= current.reversed;
+                let (left, right) = if flipped { (&current.right, &current.left) } else { (&current.left, &current.right) };
+                let size = Self::weight(left);
+                match index.cmp(&size) {
+                    Ordering::Less => {
+                        node = left.as_deref();
+                    },
+                    Ordering::Equal => {
+                        return Some(&current.value);
+                    },
+                    Ordering::Greater => {
+                        index -= size + 1;
+                        node = right.as_deref();
+                    }
+                }
+            }
+            return None;
+        }
+        pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+            let mut node = self.root.as_deref_mut();
+            let mut index = index;
+            while let Some(current) = node {
+                Self::push(current);
+                let size = Self::weight(&current.left);
+                match index.cmp(&size) {
+                    Ordering::Less => {
+                        node = current.left.as_deref_mut();
+                    },
+                    Ordering::Equal => {
+                        return Some(&mut current.value);
+                    },
+                    Ordering::Greater => {
+                        index -= size + 1;
+                        node = current.right.as_deref_mut();
+                    }
+                }
+            }
+            return None;
+        }
+        pub fn split(&mut self, at: usize) -> Result<Self, Box<dyn core::error::Error>> {
+            if at > *self.size() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let (left, right) = Self::split_nodes(self.root.take(), at);
+            self.root = left;
+            return Ok(Treap {
+                root: right,
+                state: self.next_priority(),
+            });
+        }
+        pub fn merge(&mut self, other: Self) {
+            let mut other = other;
+            self.root = Self::merge_nodes(self.root.take(), other.root.take());
+        }
+        pub fn reverse<R>(&mut self, range: R) -> Result<(), Box<dyn core::error::Error>> where R: RangeBounds<usize> {
+            let (start, end) = crate::linked_list::resolve_range(range, *self.size())?;
+            let (left, rest) = Self::split_nodes(self.root.take(), start);
+            let (mut middle, right) = Self::split_nodes(rest, end - start);
+            if let Some(node) = middle.as_mut() {
+                node.reversed ^= true;
+            }
+            self.root = Self::merge_nodes(Self::merge_nodes(left, middle), right);
+            return Ok(());
+        }
+        pub fn iter(&self) -> TreapIter<'_, T> {
+            let mut iter = TreapIter {
+                stack: Stack::new(),
+            };
+            iter.descend(self.root.as_deref(), false);
+            return iter;
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where T: core::fmt::Display {
+            println!("=====================treap=begin====================");
+            for value in self.iter() {
+                println!("{}", value);
+            }
+            println!("======================treap=end=====================");
+        }
+        pub fn size(&self) -> &usize {
+            return self.root.as_ref().map_or(&0, |node| &node.weight);
+        }
+        fn node(&mut self, value: T) -> TreapLink<T> {
+            return Some(Box::new(TreapNode {
+                value,
+                priority: self.next_priority(),
+                weight: 1,
+                reversed: false,
+                left: None,
+                right: None,
+            }));
+        }
+        fn next_priority(&mut self) -> u64 {
+            self.state ^= self.state >> 12;
+            self.state ^= self.state << 25;
+            self.state ^= self.state >> 27;
+            return self.state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        }
+        fn weight(node: &TreapLink<T>) -> usize {
+            return node.as_ref().map_or(0, |value| value.weight);
+        }
+        fn update(node: &mut TreapNode<T>) {
+            node.weight = 1 + Self::weight(&node.left) + Self::weight(&node.right);
+        }
+        fn push(node: &mut TreapNode<T>) {
+            if node.reversed {
+                core::mem::swap(&mut node.left, &mut node.right);
+                if let Some(left) = node.left.as_mut() {
+                    left.reversed ^= true;
+                }
+                if let Some(right) = node.right.as_mut() {
+                    right.reversed ^= true;
+                }
+                node.reversed = false;
+            }
+        }
+        // Splits off the first `at` elements; the second half holds the rest.
+        fn split_nodes(node: TreapLink<T>, at: usize) -> (TreapLink<T>, TreapLink<T>) {
+            let mut current = match node {
+                None => {
+                    return (None, None);
+                },
+                Some(value) => {
+                    value
+                }
+            };
+            Self::push(&mut current);
+            let size = Self::weight(&current.left);
+            if at <= size {
+                let (left, right) = Self::split_nodes(current.left.take(), at);
+                current.left = right;
+                Self::update(&mut current);
+                return (left, Some(current));
+            }
+            let (left, right) = Self::split_nodes(current.right.take(), at - size - 1);
+            current.right = left;
+            Self::update(&mut current);
+            return (Some(current), right);
+        }
+        fn merge_nodes(left: TreapLink<T>, right: TreapLink<T>) -> TreapLink<T> {
+            match (left, right) {
+                (None, right) => {
+                    return right;
+                },
+                (left, None) => {
+                    return left;
+                },
+                (Some(mut left), Some(mut right)) => {
+                    if left.priority > right.priority {
+                        Self::push(&mut left);
+                        left.right = Self::merge_nodes(left.right.take(), Some(right));
+                        Self::update(&mut left);
+                        return Some(left);
+                    }
+                    Self::push(&mut right);
+                    right.left = Self::merge_nodes(Some(left), right.left.take());
+                    Self::update(&mut right);
+                    return Some(right);
+                }
+            }
+        }
+    }
+    pub struct TreapIter<'a, T> {
+        stack: Stack<(&'a TreapNode<T>, bool)>,
+    }
+    impl<'a, T> TreapIter<'a, T> {
+        fn descend(&mut self, mut node: Option<&'a TreapNode<T>>, mut flipped: bool) {
+            while let Some(current) = node {
+                flipped ^= current.reversed;
+                self.stack.push((current, flipped));
+                node = if flipped { current.right.as_deref() } else { current.left.as_deref() };
+            }
+        }
+    }
+    impl<'a, T> Iterator for TreapIter<'a, T> {
+        type Item = &'a T;
+        fn next(&mut self) -> Option<&'a T> {
+            let (node, flipped) = self.stack.get().ok()?;
+            self.descend(if flipped { node.left.as_deref() } else { node.right.as_deref() }, flipped);
+            return Some(&node.value);
+        }
+    }
+
+    // Self-adjusting map: every access splays the touched key to the root, top-down and without recursion.
+    pub struct SplayTree<K: Ord, V> {
+        root: Link<K, V>,
+        amount: usize,
+    }
+    impl<K, V> SplayTree<K, V> where K: Ord {
+        pub fn new() -> Self {
+            return SplayTree {
+                root: None,
+                amount: 0,
+            };
+        }
+        pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+            let Some(root) = self.root.take() else {
+                self.root = Some(Node::new(key, value, 0));
+                self.amount += 1;
+                return None;
+            };
+            let mut root = Self::splay(root, &key);
+            let node = match key.cmp(&root.key) {
+                Ordering::Equal => {
+                    let old = core::mem::replace(&mut root.value, value);
+                    self.root = Some(root);
+                    return Some(old);
+                },
+                Ordering::Less => {
+                    let mut node = Node::new(key, value, 0);
+                    node.left = root.left.take();
+                    node.right = Some(root);
+                    node
+                },
+                Ordering::Greater => {
+                    let mut node = Node::new(key, value, 0);
+                    node.right = root.right.take();
+                    node.left = Some(root);
+                    node
+                }
+            };
+            self.root = Some(node);
+            self.amount += 1;
+            return None;
+        }
+        pub fn remove(&mut self, key: &K) -> Option<V> {
+            let root = Self::splay(self.root.take()?, key);
+            if root.key != *key {
+                self.root = Some(root);
+                return None;
+            }
+            let Node { value, left, right, .. } = *root;
+            self.root = match left {
+                None => right,
+                Some(left) => {
+                    let mut top = Self::splay(left, key);
+                    top.right = right;
+                    Some(top)
+                }
+            };
+            self.amount -= 1;
+            return Some(value);
+        }
+        pub fn get(&mut self, key: &K) -> Option<&V> {
+            return self.get_mut(key).map(|value| &*value);
+        }
+        pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+            let root = Self::splay(self.root.take()?, key);
+            let root = self.root.insert(root);
+            if root.key != *key {
+                return None;
+            }
+            return Some(&mut root.value);
+        }
+        pub fn contains_key(&mut self, key: &K) -> bool {
+            return self.get(key).is_some();
+        }
+        pub fn peek(&self, key: &K) -> Option<&V> {
+            return find(self.root.as_deref(), key).map(|node| &node.value);
+        }
+        pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+            return below(self.root.as_deref(), key, true).map(entry);
+        }
+        pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+            return above(self.root.as_deref(), key, true).map(entry);
+        }
+        pub fn first(&self) -> Option<(&K, &V)> {
+            return first(self.root.as_deref()).map(entry);
+        }
+        pub fn last(&self) -> Option<(&K, &V)> {
+            return last(self.root.as_deref()).map(entry);
+        }
+        pub fn range<R>(&self, range: R) -> Range<'_, K, V> where R: RangeBounds<K> {
+            return self::range(self.root.as_deref(), range);
+        }
+        pub fn iter(&self) -> InOrder<'_, K, V> {
+            return InOrder::new(self.root.as_deref());
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where K: core::fmt::Display, V: core::fmt::Display {
+            println!("=====================tree=begin=====================");
+            for (key, value) in self.iter() {
+                println!("{}: {}", key, value);
+            }
+            println!("======================tree=end======================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        // Nodes passed on the way down are hung off a left and a right spine, which become the
+        // new root's subtrees once the search stops.
+        fn splay(mut root: Box<Node<K, V>>, key: &K) -> Box<Node<K, V>> {
+            let mut lesser = Vec::new();
+            let mut greater = Vec::new();
+            loop {
+                match key.cmp(&root.key) {
+                    Ordering::Less => {
+                        let Some(mut child) = root.left.take() else {
+                            break;
+                        };
+                        if *key < child.key {
+                            root.left = child.right.take();
+                            child.right = Some(root);
+                            root = child;
+                            let Some(next) = root.left.take() else {
+                                break;
+                            };
+                            greater.push(root);
+                            root = next;
+                        } else {
+                            greater.push(root);
+                            root = child;
+                        }
+                    },
+                    Ordering::Greater => {
+                        let Some(mut child) = root.right.take() else {
+                            break;
+                        };
+                        if *key > child.key {
+                            root.right = child.left.take();
+                            child.left = Some(root);
+                            root = child;
+                            let Some(next) = root.right.take() else {
+                                break;
+                            };
+                            lesser.push(root);
+                            root = next;
+                        } else {
+                            lesser.push(root);
+                            root = child;
+                        }
+                    },
+                    Ordering::Equal => {
+                        break;
+                    }
+                }
+            }
+            let mut left = root.left.take();
+            while let Some(mut node) = lesser.pop() {
+                node.right = left;
+                left = Some(node);
+            }
+            let mut right = root.right.take();
+            while let Some(mut node) = greater.pop() {
+                node.left = right;
+                right = Some(node);
+            }
+            root.left = left;
+            root.right = right;
+            return root;
+        }
+    }
+    impl<K, V> Drop for SplayTree<K, V> where K: Ord {
+        fn drop(&mut self) {
+            let mut pending: Vec<Box<Node<K, V>>> = self.root.take().into_iter().collect();
+            while let Some(mut node) = pending.pop() {
+                pending.extend(node.left.take());
+                pending.extend(node.right.take());
+            }
+        }
+    }
+
+    // An associative operation with an identity element; the values a `SegmentTree` folds over ranges.
+    pub trait Monoid {
+        type Value: Clone;
+        fn identity() -> Self::Value;
+        fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+        // `value` combined with itself `times` times, used by range assignment.
+        fn repeat(value: &Self::Value, times: usize) -> Self::Value {
+            let mut result = Self::identity();
+            let mut power = value.clone();
+            let mut times = times;
+            while times > 0 {
+                if times & 1 == 1 {
+                    result = Self::combine(&result, &power);
+                }
+                power = Self::combine(&power, &power);
+                times >>= 1;
+            }
+            return result;
+        }
+        // The fold of `length` elements after `delta` is added to each of them, or `None` when the
+        // monoid has no range addition.
+        fn shift(_value: &Self::Value, _delta: &Self::Value, _length: usize) -> Option<Self::Value> {
+            return None;
+        }
+    }
+    pub trait Integer: Copy + Ord + core::ops::Add<Output = Self> + core::ops::Mul<Output = Self> + core::ops::Rem<Output = Self> {
+        const ZERO: Self;
+        const ONE: Self;
+        const MIN: Self;
+        const MAX: Self;
+        fn from_usize(value: usize) -> Self;
+    }
+    macro_rules! integer {
+        ($($kind:ty),*) => {
+            $(
+                impl Integer for $kind {
+                    const ZERO: Self = 0;
+                    const ONE: Self = 1;
+                    const MIN: Self = <$kind>::MIN;
+                    const MAX: Self = <$kind>::MAX;
+                    fn from_usize(value: usize) -> Self {
+                        return value as $kind;
+                    }
+                }
+            )*
+        };
+    }
+    integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+    pub struct Sum<T>(PhantomData<T>);
+    impl<T> Monoid for Sum<T> where T: Integer {
+        type Value = T;
+        fn identity() -> T {
+            return T::ZERO;
+        }
+        fn combine(left: &T, right: &T) -> T {
+            return *left + *right;
+        }
+        fn repeat(value: &T, times: usize) -> T {
+            return *value * T::from_usize(times);
+        }
+        fn shift(value: &T, delta: &T, length: usize) -> Option<T> {
+            return Some(*value + *delta * T::from_usize(length));
+        }
+    }
+    pub struct Min<T>(PhantomData<T>);
+    impl<T> Monoid for Min<T> where T: Integer {
+        type Value = T;
+        fn identity() -> T {
+            return T::MAX;
+        }
+        fn combine(left: &T, right: &T) -> T {
+            return *left.min(right);
+        }
+        fn repeat(value: &T, times: usize) -> T {
+            return if times == 0 { T::MAX } else { *value };
+        }
+        // The identity stands for "no value" (e.g. every leaf of `SegmentTree::new`), so adding to it
+        // leaves it alone instead of overflowing.
+        fn shift(value: &T, delta: &T, length: usize) -> Option<T> {
+            return Some(if length == 0 || *value == T::MAX { *value } else { *value + *delta });
+        }
+    }
+    pub struct Max<T>(PhantomData<T>);
+    impl<T> Monoid for Max<T> where T: Integer {
+        type Value = T;
+        fn identity() -> T {
+            return T::MIN;
+        }
+        fn combine(left: &T, right: &T) -> T {
+            return *left.max(right);
+        }
+        fn repeat(value: &T, times: usize) -> T {
+            return if times == 0 { T::MIN } else { *value };
+        }
+        // As for `Min`, the identity is left alone.
+        fn shift(value: &T, delta: &T, length: usize) -> Option<T> {
+            return Some(if length == 0 || *value == T::MIN { *value } else { *value + *delta });
+        }
+    }
+    pub struct Gcd<T>(PhantomData<T>);
+    impl<T> Monoid for Gcd<T> where T: Integer {
+        type Value = T;
+        fn identity() -> T {
+            return T::ZERO;
+        }
+        fn combine(left: &T, right: &T) -> T {
+            let (mut a, mut b) = (*left, *right);
+            while b != T::ZERO {
+                (a, b) = (b, a % b);
+            }
+            return a;
+        }
+        fn repeat(value: &T, times: usize) -> T {
+            return if times == 0 { T::ZERO } else { *value };
+        }
+    }
+    // `(a, b)` is the map `x -> a * x + b`; folding a range composes its maps from left to right.
+    pub struct Affine<T>(PhantomData<T>);
+    impl<T> Monoid for Affine<T> where T: Integer {
+        type Value = (T, T);
+        fn identity() -> (T, T) {
+            return (T::ONE, T::ZERO);
+        }
+        fn combine(left: &(T, T), right: &(T, T)) -> (T, T) {
+            return (left.0 * right.0, left.1 * right.0 + right.1);
+        }
+    }
+    #[derive(Clone)]
+    enum Tag<V> {
+        Assign(V),
+        Add(V),
+    }
+    // Array-backed tree over a power-of-two number of leaves. `tags[k]` holds an update already
+    // applied to `values[k]` but not yet to its children.
+    pub struct SegmentTree<M: Monoid> {
+        values: Vec<M::Value>,
+        tags: Vec<Option<Tag<M::Value>>>,
+        width: usize,
+        log: u32,
+        amount: usize,
+    }
+    impl<M> SegmentTree<M> where M: Monoid {
+        pub fn new(amount: usize) -> Self {
+            return SegmentTree::from_vec(alloc::vec![M::identity(); amount]);
+        }
+        pub fn from_vec(items: Vec<M::Value>) -> Self {
+            let amount = items.len();
+            let width = amount.next_power_of_two();
+            let mut values = alloc::vec![M::identity(); 2 * width];
+            for (index, item) in items.into_iter().enumerate() {
+                values[width + index] = item;
+            }
+            let mut tree = SegmentTree {
+                values,
+                tags: alloc::vec![None; width],
+                width,
+                log: width.trailing_zeros(),
+                amount,
+            };
+            for node in (1..width).rev() {
+                tree.update(node);
+            }
+            return tree;
+        }
+        pub fn set(&mut self, index: usize, value: M::Value) -> Result<(), Box<dyn core::error::Error>> {
+            if index >= self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let leaf = index + self.width;
+            for level in (1..=self.log).rev() {
+                self.push(leaf >> level);
+            }
+            self.values[leaf] = value;
+            for level in 1..=self.log {
+                self.update(leaf >> level);
+            }
+            return Ok(());
+        }
+        pub fn get(&mut self, index: usize) -> Option<M::Value> {
+            if index >= self.amount {
+                return None;
+            }
+            let leaf = index + self.width;
+            for level in (1..=self.log).rev() {
+                self.push(leaf >> level);
+            }
+            return Some(self.values[leaf].clone());
+        }
+        pub fn query<R>(&mut self, range: R) -> Result<M::Value, Box<dyn core::error::Error>> where R: RangeBounds<usize> {
+            let (start, end) = crate::linked_list::resolve_range(range, self.amount)?;
+            if start == end {
+                return Ok(M::identity());
+            }
+            let (mut left, mut right) = (start + self.width, end + self.width);
+            self.push_bounds(left, right);
+            let (mut front, mut back) = (M::identity(), M::identity());
+            while left < right {
+                if left & 1 == 1 {
+                    front = M::combine(&front, &self.values[left]);
+                    left += 1;
+                }
+                if right & 1 == 1 {
+                    right -= 1;
+                    back = M::combine(&self.values[right], &back);
+                }
+                left >>= 1;
+                right >>= 1;
+            }
+            return Ok(M::combine(&front, &back));
+        }
+        pub fn all(&self) -> M::Value {
+            return self.values[1].clone();
+        }
+        pub fn assign<R>(&mut self, range: R, value: M::Value) -> Result<(), Box<dyn core::error::Error>> where R: RangeBounds<usize> {
+            let (start, end) = crate::linked_list::resolve_range(range, self.amount)?;
+            self.apply_range(start, end, Tag::Assign(value));
+            return Ok(());
+        }
+        pub fn add<R>(&mut self, range: R, delta: M::Value) -> Result<(), Box<dyn core::error::Error>> where R: RangeBounds<usize> {
+            let (start, end) = crate::linked_list::resolve_range(range, self.amount)?;
+            if M::shift(&M::identity(), &delta, 0).is_none() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            self.apply_range(start, end, Tag::Add(delta));
+            return Ok(());
+        }
+        // The largest `end` such that `predicate` holds for the fold of `start..end`; `predicate`
+        // must hold for the identity and be monotone along the range.
+        pub fn max_right<F>(&mut self, start: usize, mut predicate: F) -> Result<usize, Box<dyn core::error::Error>> where F: FnMut(&M::Value) -> bool {
+            if start > self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            if start == self.amount {
+                return Ok(self.amount);
+            }
+            let mut node = start + self.width;
+            for level in (1..=self.log).rev() {
+                self.push(node >> level);
+            }
+            let mut folded = M::identity();
+            loop {
+                while node & 1 == 0 {
+                    node >>= 1;
+                }
+                let next = M::combine(&folded, &self.values[node]);
+                if !predicate(&next) {
+                    while node < self.width {
+                        self.push(node);
+                        node *= 2;
+                        let next = M::combine(&folded, &self.values[node]);
+                        if predicate(&next) {
+                            folded = next;
+                            node += 1;
+                        }
+                    }
+                    return Ok((node - self.width).min(self.amount));
+                }
+                folded = next;
+                node += 1;
+                if node.is_power_of_two() {
+                    return Ok(self.amount);
+                }
+            }
+        }
+        // The smallest `start` such that `predicate` holds for the fold of `start..end`.
+        pub fn min_left<F>(&mut self, end: usize, mut predicate: F) -> Result<usize, Box<dyn core::error::Error>> where F: FnMut(&M::Value) -> bool {
+            if end > self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            if end == 0 {
+                return Ok(0);
+            }
+            let mut node = end + self.width;
+            for level in (1..=self.log).rev() {
+                self.push((node - 1) >> level);
+            }
+            let mut folded = M::identity();
+            loop {
+                node -= 1;
+                while node > 1 && node & 1 == 1 {
+                    node >>= 1;
+                }
+                let next = M::combine(&self.values[node], &folded);
+                if !predicate(&next) {
+                    while node < self.width {
+                        self.push(node);
+                        node = 2 * node + 1;
+                        let next = M::combine(&self.values[node], &folded);
+                        if predicate(&next) {
+                            folded = next;
+                            node -= 1;
+                        }
+                    }
+                    return Ok(node + 1 - self.width);
+                }
+                folded = next;
+                if node.is_power_of_two() {
+                    return Ok(0);
+                }
+            }
+        }
+        pub fn to_vec(&mut self) -> Vec<M::Value> {
+            for node in 1..self.width {
+                self.push(node);
+            }
+            return self.values[self.width..self.width + self.amount].to_vec();
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&mut self) where M::Value: core::fmt::Debug {
+            println!("=================segment=tree=begin================");
+            for (index, value) in self.to_vec().iter().enumerate() {
+                println!("{}: {:?}", index, value);
+            }
+            println!("==================segment=tree=end=================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+        fn update(&mut self, node: usize) {
+            self.values[node] = M::combine(&self.values[2 * node], &self.values[2 * node + 1]);
+        }
+        fn apply(&mut self, node: usize, tag: Tag<M::Value>) {
+            let length = self.width >> (usize::BITS - 1 - node.leading_zeros());
+            match &tag {
+                Tag::Assign(value) => {
+                    self.values[node] = M::repeat(value, length);
+                },
+                Tag::Add(delta) => {
+                    if let Some(value) = M::shift(&self.values[node], delta, length) {
+                        self.values[node] = value;
+                    }
+                }
+            }
+            if node < self.width {
+                let pending = self.tags[node].take();
+                self.tags[node] = Some(match (pending, tag) {
+                    (Some(Tag::Assign(value)), Tag::Add(delta)) => {
+                        Tag::Assign(M::shift(&value, &delta, 1).unwrap_or(value))
+                    },
+                    (Some(Tag::Add(value)), Tag::Add(delta)) => {
+                        Tag::Add(M::shift(&value, &delta, 1).unwrap_or(value))
+                    },
+                    (_, tag) => {
+                        tag
+                    }
+                });
+            }
+        }
+        fn push(&mut self, node: usize) {
+            if let Some(tag) = self.tags[node].take() {
+                self.apply(2 * node, tag.clone());
+                self.apply(2 * node + 1, tag);
+            }
+        }
+        // Pushes pending tags down the two root paths that bound the leaves `left..right`.
+        fn push_bounds(&mut self, left: usize, right: usize) {
+            for level in (1..=self.log).rev() {
+                if (left >> level) << level != left {
+                    self.push(left >> level);
+                }
+                if (right >> level) << level != right {
+                    self.push((right - 1) >> level);
+                }
+            }
+        }
+        fn apply_range(&mut self, start: usize, end: usize, tag: Tag<M::Value>) {
+            if start == end {
+                return;
+            }
+            let (left, right) = (start + self.width, end + self.width);
+            self.push_bounds(left, right);
+            let (mut low, mut high) = (left, right);
+            while low < high {
+                if low & 1 == 1 {
+                    self.apply(low, tag.clone());
+                    low += 1;
+                }
+                if high & 1 == 1 {
+                    high -= 1;
+                    self.apply(high, tag.clone());
+                }
+                low >>= 1;
+                high >>= 1;
+            }
+            for level in 1..=self.log {
+                if (left >> level) << level != left {
+                    self.update(left >> level);
+                }
+                if (right >> level) << level != right {
+                    self.update((right - 1) >> level);
+                }
+            }
+        }
+    }
+
+    // A commutative group under addition, the values a `FenwickTree` accumulates. `times` is
+    // repeated addition, needed by range updates.
+    pub trait Group: Copy + core::ops::Add<Output = Self> + core::ops::Sub<Output = Self> {
+        const ZERO: Self;
+        fn times(self, count: usize) -> Self;
+    }
+    macro_rules! group {
+        ($($kind:ty),*) => {
+            $(
+                impl Group for $kind {
+                    const ZERO: Self = 0 as $kind;
+                    fn times(self, count: usize) -> Self {
+                        return self * count as $kind;
+                    }
+                }
+            )*
+        };
+    }
+    group!(i8, i16, i32, i64, i128, isize, f32, f64);
+    // Binary indexed tree; `cells[i]` (1-based) holds the sum of the `i & i.wrapping_neg()` items ending at `i`.
+    pub struct FenwickTree<T: Group> {
+        cells: Vec<T>,
+        amount: usize,
+    }
+    impl<T> FenwickTree<T> where T: Group {
+        pub fn new(amount: usize) -> Self {
+            return FenwickTree {
+                cells: alloc::vec![T::ZERO; amount + 1],
+                amount,
+            };
+        }
+        pub fn from_vec(items: Vec<T>) -> Self {
+            let amount = items.len();
+            let mut cells = Vec::with_capacity(amount + 1);
+            cells.push(T::ZERO);
+            cells.extend(items);
+            for index in 1..cells.len() {
+                let parent = index + (index & index.wrapping_neg());
+                if parent < cells.len() {
+                    cells[parent] = cells[parent] + cells[index];
+                }
+            }
+            return FenwickTree {
+                cells,
+                amount,
+            };
+        }
+        pub fn add(&mut self, index: usize, delta: T) -> Result<(), Box<dyn core::error::Error>> {
+            if index >= self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut cell = index + 1;
+            while cell < self.cells.len() {
+                self.cells[cell] = self.cells[cell] + delta;
+                cell += cell & cell.wrapping_neg();
+            }
+            return Ok(());
+        }
+        // Sum of the first `end` items.
+        pub fn prefix_sum(&self, end: usize) -> Result<T, Box<dyn core::error::Error>> {
+            if end > self.amount {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut sum = T::ZERO;
+            let mut cell = end;
+            while cell > 0 {
+                sum = sum + self.cells[cell];
+                cell &= cell - 1;
+            }
+            return Ok(sum);
+        }
+        // Sum of the items in `start..end`.
+        pub fn range_sum(&self, start: usize, end: usize) -> Result<T, Box<dyn core::error::Error>> {
+            if start > end {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(self.prefix_sum(end)? - self.prefix_sum(start)?);
+        }
+        pub fn get(&self, index: usize) -> Option<T> {
+            if index >= self.amount {
+                return None;
+            }
+            return self.range_sum(index, index + 1).ok();
+        }
+        // The smallest `end` whose prefix sum reaches `sum`; only meaningful while no item is negative.
+        pub fn lower_bound(&self, sum: T) -> Option<usize> where T: PartialOrd {
+            if sum <= T::ZERO {
+                return Some(0);
+            }
+            let amount = self.amount;
+            let mut position = 0;
+            let mut remaining = sum;
+            let mut step = if amount == 0 { 0 } else { 1 << amount.ilog2() };
+            while step > 0 {
+                if position + step <= amount && self.cells[position + step] < remaining {
+                    position += step;
+                    remaining = remaining - self.cells[position];
+                }
+                step >>= 1;
+            }
+            if position == amount {
+                return None;
+            }
+            return Some(position + 1);
+        }
+        pub fn to_vec(&self) -> Vec<T> {
+            return (0..self.amount).map(|index| self.range_sum(index, index + 1).unwrap_or(T::ZERO)).collect();
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where T: core::fmt::Display {
+            println!("=================fenwick=tree=begin================");
+            for (index, value) in self.to_vec().iter().enumerate() {
+                println!("{}: {}", index, value);
+            }
+            println!("==================fenwick=tree=end=================");
+        }
+        pub fn size(&self) -> &usize {
+            return &self.amount;
+        }
+    }
+    // Range updates with range sums: item `i` is the prefix of `slope`, and the sum of the first
+    // `end` items is `slope(end) * end - offset(end)`.
+    pub struct RangeFenwickTree<T: Group> {
+        slope: FenwickTree<T>,
+        offset: FenwickTree<T>,
+    }
+    impl<T> RangeFenwickTree<T> where T: Group {
+        pub fn new(amount: usize) -> Self {
+            return RangeFenwickTree {
+                slope: FenwickTree::new(amount),
+                offset: FenwickTree::new(amount),
+            };
+        }
+        pub fn from_vec(items: Vec<T>) -> Self {
+            let mut slope = Vec::with_capacity(items.len());
+            let mut offset = Vec::with_capacity(items.len());
+            let mut previous = T::ZERO;
+            for (index, item) in items.into_iter().enumerate() {
+                slope.push(item - previous);
+                offset.push((item - previous).times(index));
+                previous = item;
+            }
+            return RangeFenwickTree {
+                slope: FenwickTree::from_vec(slope),
+                offset: FenwickTree::from_vec(offset),
+            };
+        }
+        // Adds `delta` to every item in `start..end`.
+        pub fn add(&mut self, start: usize, end: usize, delta: T) -> Result<(), Box<dyn core::error::Error>> {
+            if start > end || end > *self.size() {
+                return Err(Box::new(core::fmt::Error));
+            }
+            if start == end {
+                return Ok(());
+            }
+            self.slope.add(start, delta)?;
+            self.offset.add(start, delta.times(start))?;
+            if end < *self.size() {
+                self.slope.add(end, T::ZERO - delta)?;
+                self.offset.add(end, T::ZERO - delta.times(end))?;
+            }
+            return Ok(());
+        }
+        pub fn prefix_sum(&self, end: usize) -> Result<T, Box<dyn core::error::Error>> {
+            return Ok(self.slope.prefix_sum(end)?.times(end) - self.offset.prefix_sum(end)?);
+        }
+        pub fn range_sum(&self, start: usize, end: usize) -> Result<T, Box<dyn core::error::Error>> {
+            if start > end {
+                return Err(Box::new(core::fmt::Error));
+            }
+            return Ok(self.prefix_sum(end)? - self.prefix_sum(start)?);
+        }
+        pub fn get(&self, index: usize) -> Option<T> {
+            if index >= *self.size() {
+                return None;
+            }
+            return self.slope.prefix_sum(index + 1).ok();
+        }
+        pub fn to_vec(&self) -> Vec<T> {
+            let mut items = self.slope.to_vec();
+            let mut total = T::ZERO;
+            for item in items.iter_mut() {
+                total = total + *item;
+                *item = total;
+            }
+            return items;
+        }
+        #[cfg(feature = "std")]
+        pub fn show(&self) where T: core::fmt::Display {
+            println!("=================fenwick=tree=begin================");
+            for (index, value) in self.to_vec().iter().enumerate() {
+                println!("{}: {}", index, value);
+            }
+            println!("==================fenwick=tree=end=================");
+        }
+        pub fn size(&self) -> &usize {
+            return self.slope.size();
+        }
+    }
+    // Point updates and rectangle sums over a `rows` x `columns` grid, stored row-major and 1-based.
+    pub struct FenwickTree2D<T: Group> {
+        cells: Vec<T>,
+        rows: usize,
+        columns: usize,
+    }
+    impl<T> FenwickTree2D<T> where T: Group {
+        pub fn new(rows: usize, columns: usize) -> Self {
+            return FenwickTree2D {
+                cells: alloc::vec![T::ZERO; (rows + 1) * (columns + 1)],
+                rows,
+                columns,
+            };
+        }
+        pub fn add(&mut self, row: usize, column: usize, delta: T) -> Result<(), Box<dyn core::error::Error>> {
+            if row >= self.rows || column >= self.columns {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut outer = row + 1;
+            while outer <= self.rows {
+                let mut inner = column + 1;
+                while inner <= self.columns {
+                    let cell = outer * (self.columns + 1) + inner;
+                    self.cells[cell] = self.cells[cell] + delta;
+                    inner += inner & inner.wrapping_neg();
+                }
+                outer += outer & outer.wrapping_neg();
+            }
+            return Ok(());
+        }
+        // Sum of the items in the first `rows` rows and first `columns` columns.
+        pub fn prefix_sum(&self, rows: usize, columns: usize) -> Result<T, Box<dyn core::error::Error>> {
+            if rows > self.rows || columns > self.columns {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let mut sum = T::ZERO;
+            let mut outer = rows;
+            while outer > 0 {
+                let mut inner = columns;
+                while inner > 0 {
+                    sum = sum + self.cells[outer * (self.columns + 1) + inner];
+                    inner &= inner - 1;
+                }
+                outer &= outer - 1;
+            }
+            return Ok(sum);
+        }
+        // Sum of the items in rows `row_start..row_end` and columns `column_start..column_end`.
+        pub fn range_sum(&self, row_start: usize, column_start: usize, row_end: usize, column_end: usize) -> Result<T, Box<dyn core::error::Error>> {
+            if row_start > row_end || column_start > column_end {
+                return Err(Box::new(core::fmt::Error));
+            }
+            let outer = self.prefix_sum(row_end, column_end)? - self.prefix_sum(row_start, column_end)?;
+            let inner = self.prefix_sum(row_end, column_start)? - self.prefix_sum(row_start, column_start)?;
+            return Ok(outer - inner);
+        }
+        pub fn get(&self, row: usize, column: usize) -> Option<T> {
+            if row >= self.rows || column >= self.columns {
+                return None;
+            }
+            return self.range_sum(row, column, row + 1, column + 1).ok();
+        }
+        pub fn rows(&self) -> &usize {
+            return &self.rows;
+        }
+        pub fn columns(&self) -> &usize {
+            return &self.columns;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::Rng;
+
+        #[test]
+        fn avl_stays_valid_after_every_random_operation() {
+            let mut rng = Rng(99);
+            let mut tree = AvlTree::new();
+            let mut model = alloc::collections::BTreeMap::new();
+            for step in 0..6000u64 {
+                let key = rng.next(400) as i64;
+                match rng.next(3) {
+                    0 | 1 => {
+                        assert_eq!(tree.insert(key, step), model.insert(key, step));
+                    },
+                    _ => {
+                        assert_eq!(tree.remove(&key), model.remove(&key));
+                    }
+                }
+                tree.validate().unwrap();
+                assert_eq!(*tree.size(), model.len());
+                assert_eq!(tree.floor(&key), model.range(..=key).next_back());
+                assert_eq!(tree.ceiling(&key), model.range(key..).next());
+            }
+            assert!(tree.iter().eq(model.iter()));
+            for key in 0..400 {
+                tree.remove(&key);
+                tree.validate().unwrap();
+            }
+            assert_eq!(*tree.size(), 0);
+        }
+
+        #[test]
+        fn avl_stays_valid_on_sorted_input() {
+            let mut tree = AvlTree::new();
+            for key in 0..1023 {
+                tree.insert(key, ());
+                tree.validate().unwrap();
+            }
+            assert_eq!(tree.height(), 10);
+            for key in (0..1023).rev().step_by(2) {
+                tree.remove(&key);
+                tree.validate().unwrap();
+            }
+            tree.root.as_mut().unwrap().tag += 1;
+            assert!(tree.validate().is_err());
+        }
+
+        #[test]
+        fn red_black_stays_valid_after_every_random_operation() {
+            let mut rng = Rng(77);
+            let mut tree = RbTree::new();
+            let mut model = alloc::collections::BTreeMap::new();
+            for step in 0..6000u64 {
+                let key = rng.next(400) as i64;
+                match rng.next(3) {
+                    0 | 1 => {
+                        assert_eq!(tree.insert(key, step), model.insert(key, step));
+                    },
+                    _ => {
+                        assert_eq!(tree.remove(&key), model.remove(&key));
+                    }
+                }
+                tree.validate().unwrap();
+                assert_eq!(*tree.size(), model.len());
+                assert_eq!(tree.floor(&key), model.range(..=key).next_back());
+                assert_eq!(tree.ceiling(&key), model.range(key..).next());
+            }
+            assert!(tree.iter().eq(model.iter()));
+            for key in 0..400 {
+                tree.remove(&key);
+                tree.validate().unwrap();
+            }
+            assert_eq!(*tree.size(), 0);
+        }
+
+        #[test]
+        fn red_black_stays_valid_on_sorted_input() {
+            let mut tree = RbTree::new();
+            for key in 0..1024 {
+                tree.insert(key, ());
+                tree.validate().unwrap();
+            }
+            assert!(tree.height() <= 2 * 11);
+            for key in (0..1024).step_by(2) {
+                tree.remove(&key);
+                tree.validate().unwrap();
+            }
+            tree.root.as_mut().unwrap().tag = RED;
+            assert!(tree.validate().is_err());
+        }
+
+        fn btree_split_and_append<const B: usize>() {
+            let mut rng = Rng(31 + B as u64);
+            for size in [0, 1, 2, 3, 5, 8, 13, 100, 500] {
+                for at in [-1, 0, size / 2, size - 1, size, size + 7] {
+                    let keys: Vec<i64> = (0..size).map(|key| key * 2).collect();
+                    let mut tree: BTreeMap<i64, i64, B> = BTreeMap::from_sorted(keys.iter().map(|key| (*key, -key))).unwrap();
+                    let mut model: alloc::collections::BTreeMap<i64, i64> = keys.iter().map(|key| (*key, -key)).collect();
+                    let mut upper = tree.split_off(&at);
+                    let mut expected = model.split_off(&at);
+                    tree.validate().unwrap();
+                    upper.validate().unwrap();
+                    assert!(tree.iter().eq(model.iter()));
+                    assert!(upper.iter().eq(expected.iter()));
+                    assert_eq!((*tree.size(), *upper.size()), (model.len(), expected.len()));
+                    let mut extra: BTreeMap<i64, i64, B> = BTreeMap::new();
+                    for _ in 0..rng.next(40) {
+                        let key = rng.next(3 * size as u64 + 3) as i64 * 2 + 1;
+                        extra.insert(key, key);
+                        expected.insert(key, key);
+                    }
+                    upper.append(&mut extra);
+                    assert_eq!(*extra.size(), 0);
+                    extra.validate().unwrap();
+                    tree.append(&mut upper);
+                    model.append(&mut expected);
+                    tree.validate().unwrap();
+                    assert!(tree.iter().eq(model.iter()));
+                    assert_eq!(*tree.size(), model.len());
+                    upper.insert(0, 0);
+                    assert_eq!(*upper.size(), 1);
+                }
+            }
+        }
+        fn bplus_split_and_append<const B: usize>() {
+            let mut rng = Rng(53 + B as u64);
+            for size in [0, 1, 2, 3, 5, 8, 13, 100, 500] {
+                for at in [-1, 0, size / 2, size - 1, size, size + 7] {
+                    let keys: Vec<i64> = (0..size).map(|key| key * 2).collect();
+                    let mut tree: BPlusTree<i64, i64, B> = BPlusTree::from_sorted(keys.iter().map(|key| (*key, -key))).unwrap();
+                    let mut model: alloc::collections::BTreeMap<i64, i64> = keys.iter().map(|key| (*key, -key)).collect();
+                    let mut upper = tree.split_off(&at);
+                    let mut expected = model.split_off(&at);
+                    tree.validate().unwrap();
+                    upper.validate().unwrap();
+                    assert!(tree.iter().eq(model.iter()));
+                    assert!(upper.iter().eq(expected.iter()));
+                    assert!(upper.range(at..).eq(expected.range(at..)));
+                    let mut extra: BPlusTree<i64, i64, B> = BPlusTree::new();
+                    for _ in 0..rng.next(40) {
+                        let key = rng.next(3 * size as u64 + 3) as i64 * 2 + 1;
+                        extra.insert(key, key);
+                        expected.insert(key, key);
+                    }
+                    upper.append(&mut extra);
+                    assert_eq!(*extra.size(), 0);
+                    extra.validate().unwrap();
+                    tree.append(&mut upper);
+                    model.append(&mut expected);
+                    tree.validate().unwrap();
+                    assert!(tree.iter().eq(model.iter()));
+                    assert_eq!(tree.last(), model.iter().next_back());
+                    upper.insert(0, 0);
+                    assert_eq!(*upper.size(), 1);
+                }
+            }
+        }
+
+        fn btree_split_and_join<const B: usize>() {
+            let mut rng = Rng(71 + B as u64);
+            for round in 0..60 {
+                let mut tree: BTreeMap<i64, i64, B> = BTreeMap::new();
+                let mut model = alloc::collections::BTreeMap::new();
+                for _ in 0..rng.next(600) {
+                    let key = rng.next(1000) as i64;
+                    if rng.next(4) == 0 {
+                        assert_eq!(tree.remove(&key), model.remove(&key));
+                    } else {
+                        assert_eq!(tree.insert(key, -key), model.insert(key, -key));
+                    }
+                }
+                let at = rng.next(1100) as i64 - 50;
+                let mut upper = tree.split_off(&at);
+                let mut expected = model.split_off(&at);
+                tree.validate().unwrap();
+                upper.validate().unwrap();
+                assert!(tree.iter().eq(model.iter()) && upper.iter().eq(expected.iter()));
+                assert_eq!((*tree.size(), *upper.size()), (model.len(), expected.len()));
+                // Rejoin in either order, so both the lower and the upper tree get to be the taller one.
+                if round % 2 == 0 {
+                    tree.append(&mut upper);
+                } else {
+                    upper.append(&mut tree);
+                    core::mem::swap(&mut tree, &mut upper);
+                }
+                model.append(&mut expected);
+                tree.validate().unwrap();
+                upper.validate().unwrap();
+                assert!(tree.iter().eq(model.iter()));
+                assert_eq!((*tree.size(), *upper.size()), (model.len(), 0));
+            }
+        }
+        fn bplus_split_and_join<const B: usize>() {
+            let mut rng = Rng(89 + B as u64);
+            for round in 0..60 {
+                let mut tree: BPlusTree<i64, i64, B> = BPlusTree::new();
+                let mut model = alloc::collections::BTreeMap::new();
+                for _ in 0..rng.next(600) {
+                    let key = rng.next(1000) as i64;
+                    if rng.next(4) == 0 {
+                        assert_eq!(tree.remove(&key), model.remove(&key));
+                    } else {
+                        assert_eq!(tree.insert(key, -key), model.insert(key, -key));
+                    }
+                }
+                let at = rng.next(1100) as i64 - 50;
+                let mut upper = tree.split_off(&at);
+                let mut expected = model.split_off(&at);
+                tree.validate().unwrap();
+                upper.validate().unwrap();
+                assert!(tree.iter().eq(model.iter()) && upper.iter().eq(expected.iter()));
+                assert_eq!((*tree.size(), *upper.size()), (model.len(), expected.len()));
+                if round % 2 == 0 {
+                    tree.append(&mut upper);
+                } else {
+                    upper.append(&mut tree);
+                    core::mem::swap(&mut tree, &mut upper);
+                }
+                model.append(&mut expected);
+                tree.validate().unwrap();
+                upper.validate().unwrap();
+                assert!(tree.iter().eq(model.iter()));
+                assert!(tree.range(at..).eq(model.range(at..)));
+                assert_eq!((*tree.size(), *upper.size()), (model.len(), 0));
+            }
+        }
+
+        #[test]
+        fn split_off_and_disjoint_append_keep_trees_valid() {
+            btree_split_and_join::<2>();
+            btree_split_and_join::<3>();
+            btree_split_and_join::<5>();
+            bplus_split_and_join::<2>();
+            bplus_split_and_join::<3>();
+            bplus_split_and_join::<5>();
+        }
+
+        #[test]
+        fn btree_split_off_and_append_match_std() {
+            btree_split_and_append::<2>();
+            btree_split_and_append::<3>();
+            btree_split_and_append::<6>();
+        }
+
+        #[test]
+        fn bplus_split_off_and_append_match_std() {
+            bplus_split_and_append::<2>();
+            bplus_split_and_append::<3>();
+            bplus_split_and_append::<8>();
+        }
+
+        #[test]
+        fn order_stat_queries_match_sorted_vec() {
+            let mut rng = Rng(47);
+            let mut tree = OrderStatTree::new();
+            let mut model: Vec<i64> = Vec::new();
+            for step in 0..20_000 {
+                let key = rng.next(300) as i64;
+                match rng.next(5) {
+                    0 | 1 => {
+                        tree.insert(key);
+                        let at = model.partition_point(|other| *other <= key);
+                        model.insert(at, key);
+                    }
+                    2 => {
+                        let found = model.iter().position(|other| *other == key);
+                        assert_eq!(tree.remove(&key), found.is_some());
+                        if let Some(at) = found {
+                            model.remove(at);
+                        }
+                    }
+                    3 if step % 7 == 0 => {
+                        let count = model.iter().filter(|other| **other == key).count();
+                        assert_eq!(tree.remove_all(&key), count);
+                        model.retain(|other| *other != key);
+                    }
+                    _ => {
+                        assert_eq!(tree.rank(&key), model.partition_point(|other| *other < key));
+                        assert_eq!(tree.count(&key), model.iter().filter(|other| **other == key).count());
+                        let index = rng.next(model.len() as u64 + 2) as usize;
+                        assert_eq!(tree.select(index), model.get(index));
+                        let low = rng.next(320) as i64 - 10;
+                        let high = rng.next(320) as i64 - 10;
+                        assert_eq!(tree.count_range(low..high), model.iter().filter(|other| (low..high).contains(*other)).count());
+                        assert_eq!(tree.count_range(low..=high), model.iter().filter(|other| (low..=high).contains(*other)).count());
+                        assert_eq!(tree.count_range((Bound::Excluded(low), Bound::Unbounded)), model.iter().filter(|other| **other > low).count());
+                        assert_eq!(tree.count_range(..), model.len());
+                    }
+                }
+                assert_eq!(*tree.size(), model.len());
+                tree.validate().unwrap();
+                if step % 500 == 0 {
+                    assert!(tree.iter().eq(model.iter()));
+                    assert_eq!((tree.first(), tree.last()), (model.first(), model.last()));
+                }
+            }
+        }
+
+        #[test]
+        fn treap_matches_vec() {
+            let mut rng = Rng(7);
+            let mut treap = Treap::with_seed(3);
+            let mut model: Vec<u64> = Vec::new();
+            for step in 0..20_000u64 {
+                let size = model.len() as u64;
+                match rng.next(8) {
+                    0 | 1 => {
+                        let index = rng.next(size + 1) as usize;
+                        treap.insert(index, step).unwrap();
+                        model.insert(index, step);
+                    },
+                    2 => {
+                        if size > 0 {
+                            let index = rng.next(size) as usize;
+                            assert_eq!(treap.remove(index).unwrap(), model.remove(index));
+                        }
+                        assert!(treap.remove(model.len()).is_err());
+                    },
+                    3 => {
+                        let start = rng.next(size + 1) as usize;
+                        let end = start + rng.next(size - start as u64 + 1) as usize;
+                        treap.reverse(start..end).unwrap();
+                        model[start..end].reverse();
+                    },
+                    4 => {
+                        // Split, reverse the upper part on its own, then merge it back.
+                        let at = rng.next(size + 1) as usize;
+                        let mut upper = treap.split(at).unwrap();
+                        let mut expected = model.split_off(at);
+                        assert!(upper.iter().eq(expected.iter()));
+                        upper.reverse(..).unwrap();
+                        expected.reverse();
+                        treap.merge(upper);
+                        model.extend(expected);
+                    },
+                    5 => {
+                        if size > 0 {
+                            let index = rng.next(size) as usize;
+                            *treap.get_mut(index).unwrap() += 1;
+                            model[index] += 1;
+                        }
+                        assert!(treap.get_mut(model.len()).is_none());
+                    },
+                    6 => {
+                        treap.push_front(step);
+                        model.insert(0, step);
+                    },
+                    _ => {
+                        treap.push_back(step);
+                        model.push(step);
+                    }
+                }
+                assert_eq!(*treap.size(), model.len());
+                if step % 97 == 0 {
+                    assert!(treap.iter().eq(model.iter()));
+                    for index in 0..model.len() + 1 {
+                        assert_eq!(treap.get(index), model.get(index));
+                    }
+                }
+            }
+            let size = model.len();
+            assert!(treap.insert(size + 1, 0).is_err());
+            assert!(treap.split(size + 1).is_err());
+            assert!(treap.reverse(0..size + 1).is_err());
+            assert!(treap.iter().eq(model.iter()));
+        }
+
+        #[test]
+        fn splay_tree_matches_btree_map() {
+            let mut rng = Rng(9);
+            let mut tree = SplayTree::new();
+            let mut model = alloc::collections::BTreeMap::new();
+            for step in 0..50_000u64 {
+                let key = rng.next(500);
+                match rng.next(6) {
+                    0 | 1 => {
+                        assert_eq!(tree.insert(key, step), model.insert(key, step));
+                    },
+                    2 => {
+                        assert_eq!(tree.remove(&key), model.remove(&key));
+                    },
+                    3 => {
+                        assert_eq!(tree.peek(&key), model.get(&key));
+                        assert_eq!(tree.get(&key), model.get(&key));
+                        assert_eq!(tree.contains_key(&key), model.contains_key(&key));
+                    },
+                    4 => {
+                        assert_eq!(tree.floor(&key), model.range(..=key).next_back());
+                        assert_eq!(tree.ceiling(&key), model.range(key..).next());
+                    },
+                    _ => {
+                        let end = key + rng.next(50);
+                        assert!(tree.range(key..end).eq(model.range(key..end)));
+                        assert!(tree.range(..=key).eq(model.range(..=key)));
+                    }
+                }
+                assert_eq!(*tree.size(), model.len());
+                if step % 501 == 0 {
+                    assert!(tree.iter().eq(model.iter()));
+                    assert_eq!((tree.first(), tree.last()), (model.iter().next(), model.iter().next_back()));
+                }
+            }
+        }
+
+        #[test]
+        fn splay_tree_drops_a_sorted_spine() {
+            // Ascending inserts leave every old root as the left child of the new one.
+            let mut tree = SplayTree::new();
+            for key in 0..1_000_000u32 {
+                tree.insert(key, key);
+            }
+            assert_eq!(*tree.size(), 1_000_000);
+            assert_eq!(tree.last(), Some((&999_999, &999_999)));
+            drop(tree);
+            let mut treap = Treap::new();
+            for value in 0..1_000_000u32 {
+                treap.push_back(value);
+            }
+            treap.reverse(..).unwrap();
+            assert_eq!(treap.get(0), Some(&999_999));
+        }
+
+        fn fold<M>(values: &[M::Value]) -> M::Value where M: Monoid {
+            return values.iter().fold(M::identity(), |total, value| M::combine(&total, value));
+        }
+        // Mixes point sets, range assigns and range adds so that pending tags get composed, and checks
+        // every read against a plain Vec.
+        fn segment_matches_vec<M>(seed: u64, make: fn(&mut Rng) -> M::Value, addable: bool) where M: Monoid, M::Value: PartialEq + core::fmt::Debug {
+            for size in [0, 1, 2, 3, 5, 8, 13, 100] {
+                let mut rng = Rng(seed + size as u64);
+                let mut model: Vec<M::Value> = (0..size).map(|_| make(&mut rng)).collect();
+                let mut tree = SegmentTree::<M>::from_vec(model.clone());
+                for _ in 0..3000 {
+                    let start = rng.next(size as u64 + 1) as usize;
+                    let end = start + rng.next((size - start) as u64 + 1) as usize;
+                    match rng.next(6) {
+                        0 => {
+                            let value = make(&mut rng);
+                            if size > 0 {
+                                let index = rng.next(size as u64) as usize;
+                                tree.set(index, value.clone()).unwrap();
+                                model[index] = value;
+                            } else {
+                                assert!(tree.set(0, value).is_err());
+                            }
+                        }
+                        1 => {
+                            let value = make(&mut rng);
+                            tree.assign(start..end, value.clone()).unwrap();
+                            for slot in &mut model[start..end] {
+                                *slot = value.clone();
+                            }
+                        }
+                        2 => {
+                            let delta = make(&mut rng);
+                            if addable {
+                                tree.add(start..end, delta.clone()).unwrap();
+                                for slot in &mut model[start..end] {
+                                    *slot = M::shift(slot, &delta, 1).unwrap();
+                                }
+                            } else {
+                                assert!(tree.add(start..end, delta).is_err());
+                            }
+                        }
+                        3 => {
+                            assert_eq!(tree.query(start..end).unwrap(), fold::<M>(&model[start..end]));
+                        }
+                        4 => {
+                            if size > 0 {
+                                let index = rng.next(size as u64) as usize;
+                                assert_eq!(tree.get(index).unwrap(), model[index]);
+                            }
+                            assert_eq!(tree.get(size), None);
+                        }
+                        _ => {
+                            assert_eq!(tree.to_vec(), model);
+                            assert_eq!(tree.all(), fold::<M>(&model));
+                        }
+                    }
+                }
+                assert!(tree.query(0..size + 1).is_err());
+            }
+        }
+
+        #[test]
+        fn segment_sum_matches_vec() {
+            segment_matches_vec::<Sum<i64>>(1, |rng| rng.next(21) as i64 - 10, true);
+        }
+
+        #[test]
+        fn segment_min_and_max_match_vec() {
+            segment_matches_vec::<Min<i64>>(2, |rng| rng.next(21) as i64 - 10, true);
+            segment_matches_vec::<Max<i32>>(3, |rng| rng.next(21) as i32 - 10, true);
+        }
+
+        #[test]
+        fn segment_affine_and_gcd_match_vec() {
+            segment_matches_vec::<Affine<i64>>(4, |rng| (rng.next(3) as i64 - 1, rng.next(5) as i64 - 2), false);
+            segment_matches_vec::<Gcd<u64>>(5, |rng| rng.next(6) * 6, false);
+        }
+
+        #[test]
+        fn segment_add_on_a_fresh_min_or_max_tree_keeps_the_identity() {
+            let mut low = SegmentTree::<Min<i32>>::new(4);
+            low.add(0..4, 1).unwrap();
+            assert_eq!(low.query(0..4).unwrap(), i32::MAX);
+            low.set(1, 5).unwrap();
+            low.add(0..4, -2).unwrap();
+            assert_eq!(low.to_vec(), [i32::MAX, 3, i32::MAX, i32::MAX]);
+            assert_eq!(low.query(0..4).unwrap(), 3);
+            let mut high = SegmentTree::<Max<i8>>::new(5);
+            high.add(.., -1).unwrap();
+            high.add(1..3, 1).unwrap();
+            assert_eq!(high.all(), i8::MIN);
+            high.assign(2..4, 7).unwrap();
+            high.add(0..5, 1).unwrap();
+            assert_eq!(high.to_vec(), [i8::MIN, i8::MIN, 8, 8, i8::MIN]);
+        }
+
+        #[test]
+        fn segment_add_after_assign_composes() {
+            let mut tree = SegmentTree::<Sum<i64>>::from_vec(Vec::from([1; 8]));
+            tree.assign(0..8, 5).unwrap();
+            tree.add(2..6, 3).unwrap();
+            tree.assign(4..5, -1).unwrap();
+            tree.add(0..8, 1).unwrap();
+            assert_eq!(tree.to_vec(), [6, 6, 9, 9, 0, 9, 6, 6]);
+            assert_eq!(tree.query(3..7).unwrap(), 24);
+        }
+
+        #[test]
+        fn segment_max_right_and_min_left_match_linear_scan() {
+            let mut rng = Rng(77);
+            for size in [0, 1, 2, 3, 5, 8, 13, 100] {
+                let mut model: Vec<i64> = (0..size).map(|_| rng.next(5) as i64).collect();
+                let mut tree = SegmentTree::<Sum<i64>>::from_vec(model.clone());
+                for _ in 0..2000 {
+                    if size > 0 && rng.next(3) == 0 {
+                        let start = rng.next(size as u64) as usize;
+                        let end = start + rng.next((size - start) as u64 + 1) as usize;
+                        let delta = rng.next(4) as i64;
+                        tree.add(start..end, delta).unwrap();
+                        for slot in &mut model[start..end] {
+                            *slot += delta;
+                        }
+                    }
+                    let limit = rng.next(40) as i64;
+                    let from = rng.next(size as u64 + 1) as usize;
+                    let (mut end, mut total) = (from, 0);
+                    while end < size && total + model[end] <= limit {
+                        total += model[end];
+                        end += 1;
+                    }
+                    assert_eq!(tree.max_right(from, |sum| *sum <= limit).unwrap(), end);
+                    let (mut start, mut total) = (from, 0);
+                    while start > 0 && total + model[start - 1] <= limit {
+                        total += model[start - 1];
+                        start -= 1;
+                    }
+                    assert_eq!(tree.min_left(from, |sum| *sum <= limit).unwrap(), start);
+                }
+                assert!(tree.max_right(size + 1, |_| true).is_err());
+                assert!(tree.min_left(size + 1, |_| true).is_err());
+            }
+        }
+
+        #[test]
+        fn fenwick_trees_match_vec() {
+            let mut rng = Rng(3);
+            for size in [0, 1, 2, 3, 5, 8, 13, 100] {
+                let mut model: Vec<i64> = (0..size).map(|_| rng.next(10) as i64).collect();
+                let mut point = FenwickTree::from_vec(model.clone());
+                let mut range = RangeFenwickTree::from_vec(model.clone());
+                for _ in 0..3000 {
+                    let start = rng.next(size as u64 + 1) as usize;
+                    let end = start + rng.next((size - start) as u64 + 1) as usize;
+                    let delta = rng.next(7) as i64;
+                    match rng.next(5) {
+                        0 => {
+                            if size > 0 {
+                                let index = start.min(size - 1);
+                                point.add(index, delta).unwrap();
+                                range.add(index, index + 1, delta).unwrap();
+                                model[index] += delta;
+                            }
+                            assert!(point.add(size, 1).is_err());
+                        }
+                        1 => {
+                            range.add(start, end, delta).unwrap();
+                            for (index, slot) in model.iter_mut().enumerate().take(end).skip(start) {
+                                point.add(index, delta).unwrap();
+                                *slot += delta;
+                            }
+                        }
+                        2 => {
+                            let total: i64 = model[start..end].iter().sum();
+                            assert_eq!(point.range_sum(start, end).unwrap(), total);
+                            assert_eq!(range.range_sum(start, end).unwrap(), total);
+                            assert_eq!(point.prefix_sum(start).unwrap(), model[..start].iter().sum::<i64>());
+                            assert_eq!(range.prefix_sum(start).unwrap(), model[..start].iter().sum::<i64>());
+                        }
+                        3 => {
+                            // The smallest `end` whose prefix sum reaches `target`; sums never shrink
+                            // because every delta is non-negative.
+                            let target = rng.next(400) as i64;
+                            let (mut end, mut total) = (0, 0);
+                            while total < target && end < size {
+                                total += model[end];
+                                end += 1;
+                            }
+                            let expected = if total >= target { Some(end) } else { None };
+                            assert_eq!(point.lower_bound(target), expected);
+                        }
+                        _ => {
+                            assert_eq!(point.to_vec(), model);
+                            assert_eq!(range.to_vec(), model);
+                            for index in 0..size + 1 {
+                                assert_eq!(point.get(index), model.get(index).copied());
+                                assert_eq!(range.get(index), model.get(index).copied());
+                            }
+                        }
+                    }
+                }
+                assert!(point.range_sum(1, 0).is_err());
+                assert!(point.prefix_sum(size + 1).is_err());
+                assert!(range.add(0, size + 1, 1).is_err());
+                assert_eq!(point.get(usize::MAX), None);
+            }
+        }
+
+        #[test]
+        fn fenwick_2d_matches_grid() {
+            let mut rng = Rng(5);
+            for (rows, columns) in [(0, 3), (3, 0), (1, 1), (3, 7), (16, 9)] {
+                let mut model: Vec<Vec<i32>> = (0..rows).map(|_| Vec::from_iter(core::iter::repeat_n(0, columns))).collect();
+                let mut tree = FenwickTree2D::new(rows, columns);
+                for _ in 0..2000 {
+                    if rows > 0 && columns > 0 && rng.next(2) == 0 {
+                        let row = rng.next(rows as u64) as usize;
+                        let column = rng.next(columns as u64) as usize;
+                        let delta = rng.next(9) as i32 - 4;
+                        tree.add(row, column, delta).unwrap();
+                        model[row][column] += delta;
+                    }
+                    let row_start = rng.next(rows as u64 + 1) as usize;
+                    let row_end = row_start + rng.next((rows - row_start) as u64 + 1) as usize;
+                    let column_start = rng.next(columns as u64 + 1) as usize;
+                    let column_end = column_start + rng.next((columns - column_start) as u64 + 1) as usize;
+                    let total: i32 = model[row_start..row_end].iter().map(|line| line[column_start..column_end].iter().sum::<i32>()).sum();
+                    assert_eq!(tree.range_sum(row_start, column_start, row_end, column_end).unwrap(), total);
+                    if row_end > row_start && column_end > column_start {
+                        assert_eq!(tree.get(row_start, column_start), Some(model[row_start][column_start]));
+                    }
+                }
+                assert!(tree.add(rows, 0, 1).is_err());
+                assert!(tree.range_sum(0, 0, rows + 1, columns).is_err());
+                assert_eq!(tree.get(rows, 0), None);
+            }
+        }
+    }
+}
+pub mod allocator {
+    use core::{alloc::Layout, cell::Cell, ptr::NonNull};
+
+    /// # Safety
+    ///
+    /// `allocate` must return a block valid for `layout` that stays usable until it is passed
+    /// back to `deallocate` on the same allocator. Clones count as the same allocator, and so do
+    /// values that compare equal: the linked lists relink nodes in `append` and `merge_sorted` only
+    /// when the two lists' allocators are `==`, and copy the items into fresh nodes otherwise.
+    pub unsafe trait Allocator {
+        fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+        /// # Safety
+        ///
+        /// `ptr` must come from `allocate` on this allocator with the same `layout`.
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+    }
+    unsafe impl<A> Allocator for &A where A: Allocator + ?Sized {
+        fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+            return (**self).allocate(layout);
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            unsafe { (**self).deallocate(ptr, layout) };
+        }
+    }
+
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
+    pub struct Global;
+    unsafe impl Allocator for Global {
+        fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+            // The global allocator must never see a zero-sized request; hand out an aligned dangling pointer instead.
+            if layout.size() == 0 {
+                return NonNull::new(core::ptr::without_provenance_mut(layout.align()));
+            }
+            return NonNull::new(unsafe { alloc::alloc::alloc(layout) });
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if layout.size() == 0 {
+                return;
+            }
+            unsafe { alloc::alloc::dealloc(ptr.as_ptr(), layout) };
+        }
+    }
+
+    pub struct NodePool<A: Allocator = Global> {
+        free: Cell<Option<NonNull<FreeNode>>>,
+        layout: Cell<Option<Layout>>,
+        cached: Cell<usize>,
+        limit: usize,
+        allocator: A,
+    }
+    struct FreeNode {
+        next: Option<NonNull<FreeNode>>,
+    }
+    impl NodePool {
+        pub fn new() -> Self {
+            return NodePool::new_in(Global);
+        }
+        pub fn with_limit(limit: usize) -> Self {
+            let mut pool = NodePool::new();
+            pool.limit = limit;
+            return pool;
+        }
+    }
+    impl<A> NodePool<A> where A: Allocator {
+        pub fn new_in(allocator: A) -> Self {
+            return NodePool {
+                free: Cell::new(None),
+                layout: Cell::new(None),
+                cached: Cell::new(0),
+                limit: usize::MAX,
+                allocator,
+            };
+        }
+        pub fn cached(&self) -> usize {
+            return self.cached.get();
+        }
+        pub fn release(&self) {
+            if let Some(layout) = self.layout.get() {
+                while let Some(node) = self.free.get() {
+                    self.free.set(unsafe { node.as_ref() }.next);
+                    unsafe { self.allocator.deallocate(node.cast(), layout) };
+                }
+            }
+            self.cached.set(0);
+        }
+        fn poolable(&self, layout: Layout) -> bool {
+            if layout.size() < size_of::<FreeNode>() || layout.align() < align_of::<FreeNode>() {
+                return false;
+            }
+            match self.layout.get() {
+                None => {
+                    self.layout.set(Some(layout));
+                    return true;
+                },
+                Some(value) => {
+                    return value == layout;
+                }
+            }
+        }
+    }
+    unsafe impl<A> Allocator for NodePool<A> where A: Allocator {
+        fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+            if self.poolable(layout) && let Some(node) = self.free.get() {
+                self.free.set(unsafe { node.as_ref() }.next);
+                self.cached.set(self.cached.get() - 1);
+                return Some(node.cast());
+            }
+            return self.allocator.allocate(layout);
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if self.cached.get() >= self.limit || !self.poolable(layout) {
+                unsafe { self.allocator.deallocate(ptr, layout) };
+                return;
+            }
+            let node = ptr.cast::<FreeNode>();
+            unsafe { node.as_ptr().write(FreeNode { next: self.free.get() }) };
+            self.free.set(Some(node));
+            self.cached.set(self.cached.get() + 1);
+        }
+    }
+    impl<A> Drop for NodePool<A> where A: Allocator {
+        fn drop(&mut self) {
+            self.release();
+        }
+    }
+    // Each pool keeps its own free list, so only a pool is the same allocator as itself.
+    impl<A> PartialEq for NodePool<A> where A: Allocator {
+        fn eq(&self, other: &Self) -> bool {
+            return core::ptr::eq(self, other);
+        }
+    }
+
+    // Only sound for allocators where every value of `A` can free the others' blocks, such as `Global`.
+    #[cfg(feature = "allocator_api")]
+    pub struct StdAllocator<A: core::alloc::Allocator>(pub A);
+    #[cfg(feature = "allocator_api")]
+    unsafe impl<A> Allocator for StdAllocator<A> where A: core::alloc::Allocator {
+        fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+            return self.0.allocate(layout).ok().map(|block| block.cast());
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            unsafe { self.0.deallocate(ptr, layout) };
+        }
+    }
+    // The wrapper's contract already makes every value interchangeable.
+    #[cfg(feature = "allocator_api")]
+    impl<A> PartialEq for StdAllocator<A> where A: core::alloc::Allocator {
+        fn eq(&self, _other: &Self) -> bool {
+            return true;
+        }
+    }
+
+    pub(crate) fn allocate_node<T, A>(allocator: &A, value: T) -> NonNull<T> where A: Allocator {
+        let layout = Layout::new::<T>();
+        match allocator.allocate(layout) {
+            None => {
+                alloc::alloc::handle_alloc_error(layout);
+            },
+            Some(ptr) => {
+                let node = ptr.cast::<T>();
+                unsafe { node.as_ptr().write(value) };
+                return node;
+            }
+        }
+    }
+    pub(crate) unsafe fn deallocate_node<T, A>(allocator: &A, node: NonNull<T>) -> T where A: Allocator {
+        let value = unsafe { node.as_ptr().read() };
+        unsafe { allocator.deallocate(node.cast(), Layout::new::<T>()) };
+        return value;
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn zero_sized_layouts_get_aligned_dangling_pointers() {
+            for layout in [Layout::new::<()>(), Layout::from_size_align(0, 64).unwrap()] {
+                let ptr = Global.allocate(layout).unwrap();
+                assert_eq!(ptr.as_ptr() as usize % layout.align(), 0);
+                unsafe { Global.deallocate(ptr, layout) };
+            }
+            let node = allocate_node(&Global, ());
+            unsafe { deallocate_node(&Global, node) };
+        }
+
+        #[derive(Default)]
+        struct Counting {
+            allocations: Cell<usize>,
+            live: Cell<usize>,
+        }
+        unsafe impl Allocator for Counting {
+            fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+                self.allocations.set(self.allocations.get() + 1);
+                self.live.set(self.live.get() + 1);
+                return Global.allocate(layout);
+            }
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.live.set(self.live.get() - 1);
+                unsafe { Global.deallocate(ptr, layout) };
+            }
+        }
+        fn pool(counting: &Counting, limit: usize) -> NodePool<&Counting> {
+            let mut pool = NodePool::new_in(counting);
+            pool.limit = limit;
+            return pool;
+        }
+
+        #[test]
+        fn warm_node_cache_serves_push_and_get_without_allocating() {
+            let counting = Counting::default();
+            let mut stack = crate::stack::Stack::new_in(pool(&counting, usize::MAX));
+            let mut queue = crate::queue::Queue::new_in(pool(&counting, usize::MAX));
+            for round in 0..100 {
+                for value in 0..64 {
+                    stack.push(value);
+                    queue.push(value);
+                }
+                for _ in 0..64 {
+                    stack.get().unwrap();
+                    queue.get().unwrap();
+                }
+                if round == 0 {
+                    assert_eq!(counting.allocations.get(), 128);
+                }
+            }
+            assert_eq!(counting.allocations.get(), 128);
+            assert_eq!(stack.allocator().cached(), 64);
+            assert_eq!(queue.allocator().cached(), 64);
+        }
+
+        #[test]
+        fn max_cached_caps_the_free_list() {
+            let counting = Counting::default();
+            let mut stack = crate::stack::Stack::new_in(pool(&counting, 8));
+            let mut queue = crate::queue::Queue::new_in(pool(&counting, 8));
+            for value in 0..32 {
+                stack.push(value);
+                queue.push(value);
+            }
+            while stack.get().is_ok() && queue.get().is_ok() {}
+            assert_eq!(stack.allocator().cached(), 8);
+            assert_eq!(queue.allocator().cached(), 8);
+            assert_eq!(counting.live.get(), 16);
+            let mut cached = crate::stack::Stack::with_node_cache(3);
+            for value in 0..10 {
+                cached.push(value);
+            }
+            while cached.get().is_ok() {}
+            assert_eq!(cached.allocator().cached(), 3);
+        }
+
+        #[test]
+        fn shrink_to_fit_empties_the_cache() {
+            let counting = Counting::default();
+            let mut stack = crate::stack::Stack::new_in(pool(&counting, usize::MAX));
+            let mut queue = crate::queue::Queue::new_in(pool(&counting, usize::MAX));
+            for value in 0..20 {
+                stack.push(value);
+                queue.push(value);
+            }
+            for _ in 0..15 {
+                stack.get().unwrap();
+                queue.get().unwrap();
+            }
+            stack.shrink_to_fit();
+            queue.shrink_to_fit();
+            assert_eq!(stack.allocator().cached(), 0);
+            assert_eq!(queue.allocator().cached(), 0);
+            assert_eq!(counting.live.get(), 10);
+            drop((stack, queue));
+            assert_eq!(counting.live.get(), 0);
+            let mut cached = crate::queue::Queue::with_node_cache(16);
+            cached.push(1);
+            cached.get().unwrap();
+            assert_eq!(cached.allocator().cached(), 1);
+            cached.shrink_to_fit();
+            assert_eq!(cached.allocator().cached(), 0);
+        }
+    }
+}